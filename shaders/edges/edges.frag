@@ -8,8 +8,18 @@ layout (std140, set = 0, binding = 0) uniform UBO
   float tess_levels[5];
 
   float curve_offset;
+
+  float bundling;
+
+  float color_by_path;
 } ubo;
 
+// one color per edge, blended from the paths traversing it; the
+// alpha is zero for edges not on any path
+layout (set = 0, binding = 2) readonly buffer EdgeColors {
+  vec4 colors[];
+} edge_colors;
+
 layout (location = 0) out vec4 f_color;
 
 layout (push_constant) uniform NodePC {
@@ -21,5 +31,14 @@ layout (push_constant) uniform NodePC {
 } node_uniform;
 
 void main() {
+  if (ubo.color_by_path > 0.0) {
+    vec4 path_color = edge_colors.colors[gl_PrimitiveID];
+
+    if (path_color.a > 0.0) {
+      f_color = vec4(path_color.rgb, 1.0);
+      return;
+    }
+  }
+
   f_color = ubo.edge_color;
 }
@@ -5,4 +5,8 @@ struct UBO {
   float tess_levels[5];
 
   float curve_offset;
+
+  float bundling;
+
+  float color_by_path;
 };
@@ -0,0 +1,15 @@
+#version 450
+
+layout (location = 0) out vec4 f_color;
+
+layout (push_constant) uniform Gradient {
+  vec4 top;
+  vec4 bottom;
+  vec2 viewport_dims;
+} gradient;
+
+void main() {
+  float t = clamp(gl_FragCoord.y / gradient.viewport_dims.y, 0.0, 1.0);
+
+  f_color = mix(gradient.top, gradient.bottom, t);
+}
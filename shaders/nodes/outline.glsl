@@ -0,0 +1,40 @@
+// Node cap styles, see `NodeCaps`
+#define NODE_CAPS_SQUARE 0
+#define NODE_CAPS_ROUND 1
+
+// The distance in pixels from a fragment to the edge of its node,
+// given the fragment's coordinates in the node quad, which go from 0
+// to 1 across and along the node. The derivatives are used to convert
+// the distance from quad to screen space.
+//
+// With round caps, the node is drawn as a capsule whose ends are
+// semicircles with a radius of half the node width, and the distance
+// is negative for fragments outside the capsule.
+
+float node_edge_dist(vec2 uv, uint caps) {
+  vec2 per_pixel = max(fwidth(uv), vec2(0.000001));
+
+  if (caps == NODE_CAPS_ROUND) {
+    // the node width and length in pixels
+    vec2 size = 1.0 / per_pixel;
+    vec2 p = uv * size;
+
+    float radius = 0.5 * size.x;
+    float along = clamp(p.y, radius, max(radius, size.y - radius));
+
+    return radius - distance(p, vec2(radius, along));
+  }
+
+  vec2 edge_dist = min(uv, 1.0 - uv) / per_pixel;
+
+  return min(edge_dist.x, edge_dist.y);
+}
+
+// True if a fragment `edge_dist` pixels from the edge of its node is
+// part of an outline that's `width` pixels wide.
+//
+// A width of zero disables the outline.
+
+bool is_outline(float edge_dist, float width) {
+  return width > 0.0 && edge_dist < width;
+}
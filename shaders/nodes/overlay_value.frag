@@ -3,6 +3,7 @@
 layout (early_fragment_tests) in;
 
 flat layout (location = 0) in int node_id;
+layout (location = 1) in vec2 node_uv;
 
 layout (location = 0) out vec4 f_color;
 layout (location = 1) out uint f_id;
@@ -24,9 +25,45 @@ layout (push_constant) uniform NodePC {
   float scale;
   vec2 viewport_dims;
   uint texture_period;
+  uint palette;
+  float outline_width;
+  float outline_r;
+  float outline_g;
+  float outline_b;
+  uint node_caps;
+  float lod_min_pixels;
+  uint id_offset;
+  uint tint;
+  uint path_ribbons;
 } node_uniform;
 
+#include "palette.glsl"
+#include "outline.glsl"
+#include "ribbons.glsl"
+
 void main() {
+  float edge_dist = node_edge_dist(node_uv, node_uniform.node_caps);
+
+  if (edge_dist < 0.0) {
+    discard;
+  }
+
+  // nodes of overlaid graphs are drawn in a single color, and can't
+  // be selected; their IDs follow those of the main graph
+  if (node_uniform.tint != 0) {
+    f_id = uint(node_id) + node_uniform.id_offset;
+    f_mask = vec4(0.0, 0.0, 0.0, 0.0);
+    f_color = unpackUnorm4x8(node_uniform.tint);
+
+    if (is_outline(edge_dist, node_uniform.outline_width)) {
+      f_color = vec4(node_uniform.outline_r,
+                     node_uniform.outline_g,
+                     node_uniform.outline_b,
+                     1.0);
+    }
+    return;
+  }
+
   uint is_selected = selection.flag[node_id - 1];
 
   f_id = uint(node_id);
@@ -39,4 +76,16 @@ void main() {
 
   float node_val = node_value.value[node_id];
   f_color = texture(overlay, node_val);
+  f_color.rgb = apply_palette(f_color.rgb, node_uniform.palette);
+
+  if (node_uniform.path_ribbons != 0) {
+    f_color = apply_path_ribbons(f_color, node_id, node_uv);
+  }
+
+  if (is_outline(edge_dist, node_uniform.outline_width)) {
+    f_color = vec4(node_uniform.outline_r,
+                   node_uniform.outline_g,
+                   node_uniform.outline_b,
+                   1.0);
+  }
 }
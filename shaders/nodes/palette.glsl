@@ -0,0 +1,38 @@
+// Remaps colors for color vision deficiencies by simulating the
+// deficiency in LMS space, and shifting the lost information into
+// the channels that can still be distinguished ("daltonization").
+//
+// palette: 0 = default (no change), 1 = deuteranopia, 2 = protanopia
+
+const mat3 rgb_to_lms = mat3(17.8824, 3.45565, 0.0299566,
+                             43.5161, 27.1554, 0.184309,
+                             4.11935, 3.86714, 1.46709);
+
+const mat3 lms_to_rgb = mat3(0.0809444479, -0.0102485335, -0.000365296938,
+                             -0.130504409, 0.0540193266, -0.00412161469,
+                             0.116721066, -0.113614708, 0.693511405);
+
+const mat3 sim_protanopia = mat3(0.0, 0.0, 0.0,
+                                 2.02344, 1.0, 0.0,
+                                 -2.52581, 0.0, 1.0);
+
+const mat3 sim_deuteranopia = mat3(1.0, 0.494207, 0.0,
+                                   0.0, 0.0, 0.0,
+                                   0.0, 1.24827, 1.0);
+
+const mat3 shift_error = mat3(0.0, 0.7, 0.7,
+                              0.0, 1.0, 0.0,
+                              0.0, 0.0, 1.0);
+
+vec3 apply_palette(vec3 color, uint palette) {
+  if (palette == 0) {
+    return color;
+  }
+
+  mat3 sim = palette == 1 ? sim_deuteranopia : sim_protanopia;
+
+  vec3 simulated = lms_to_rgb * (sim * (rgb_to_lms * color));
+  vec3 error = color - simulated;
+
+  return clamp(color + shift_error * error, 0.0, 1.0);
+}
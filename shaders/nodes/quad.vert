@@ -4,6 +4,7 @@
 
 layout (location = 0) in vec2 position;
 layout (location = 0) out int node_id;
+layout (location = 1) out vec2 node_uv;
 
 layout (push_constant) uniform NodePC {
   mat4 view_transform;
@@ -33,27 +34,35 @@ void main() {
 
   vec2 offset;
 
+  // x is across the node, y is along it
   switch (vx_mod) {
     case 0:
       offset = vec2(0.0, -del);
+      node_uv = vec2(0.0, 0.0);
       break;
     case 1:
       offset = vec2(0.0, del);
+      node_uv = vec2(1.0, 1.0);
       break;
     case 2:
       offset = vec2(0.0, del);
+      node_uv = vec2(1.0, 0.0);
       break;
     case 3:
       offset = vec2(0.0, -del);
+      node_uv = vec2(0.0, 0.0);
       break;
     case 4:
       offset = vec2(0.0, -del);
+      node_uv = vec2(0.0, 1.0);
       break;
     case 5:
       offset = vec2(0.0, del);
+      node_uv = vec2(1.0, 1.0);
       break;
     default:
       offset = vec2(0.0, 0.0);
+      node_uv = vec2(0.0, 0.0);
       break;
   }
 
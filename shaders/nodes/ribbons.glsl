@@ -0,0 +1,38 @@
+// Path ribbons, see `PathRibbons`: each node is split across its
+// width into one band per path on the node, with a ribbon in the
+// path's color down the middle of each band.
+//
+// The buffer holds one offset per node, followed by the total length;
+// the colors of the paths on node `n` are in `[offset[n - 1],
+// offset[n])`, packed as RGBA8.
+
+layout (set = 1, binding = 1) readonly buffer Ribbons {
+  uint data[];
+} ribbons;
+
+// The color of the path ribbon that a fragment is on, given its
+// coordinates in the node quad, or `color` if it's between ribbons,
+// or the node isn't on any paths.
+
+vec4 apply_path_ribbons(vec4 color, int node_id, vec2 uv) {
+  uint start = ribbons.data[node_id - 1];
+  uint end = ribbons.data[node_id];
+
+  if (end <= start) {
+    return color;
+  }
+
+  uint count = end - start;
+  float band = uv.x * float(count);
+
+  // the ribbons cover the middle 70% of each band, so that the node
+  // color shows between them
+  float t = fract(band);
+  if (t < 0.15 || t > 0.85) {
+    return color;
+  }
+
+  uint ix = min(uint(band), count - 1);
+
+  return unpackUnorm4x8(ribbons.data[start + ix]);
+}
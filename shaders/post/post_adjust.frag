@@ -0,0 +1,31 @@
+#version 450
+
+layout (set = 0, binding = 0) uniform sampler2D u_color_sampler;
+
+layout (location = 0) out vec4 f_color;
+
+layout (push_constant) uniform Dims {
+  vec2 texture_size;
+  vec2 screen_size;
+  bool enabled;
+  float brightness;
+  float contrast;
+  float gamma;
+} dims;
+
+void main() {
+  vec2 uv = gl_FragCoord.xy / dims.texture_size;
+
+  vec4 color = texture(u_color_sampler, uv);
+
+  if (dims.enabled) {
+    vec3 result = (color.rgb - 0.5) * dims.contrast + 0.5;
+    result += dims.brightness;
+    result = clamp(result, 0.0, 1.0);
+    result = pow(result, vec3(1.0 / max(dims.gamma, 0.01)));
+
+    f_color = vec4(result, 1.0);
+  } else {
+    f_color = vec4(color.rgb, 1.0);
+  }
+}
@@ -0,0 +1,38 @@
+#version 450
+
+layout (set = 0, binding = 0) uniform sampler2D u_color_sampler;
+
+layout (location = 0) out vec4 f_color;
+
+layout (push_constant) uniform Dims {
+  float width;
+  float height;
+  bool enabled;
+  vec4 outline_color;
+} dims;
+
+vec2 uv_coord(vec2 coord) {
+  return (coord / vec2(dims.width, dims.height));
+}
+
+void main() {
+
+  vec4 fc = gl_FragCoord;
+
+  // the edge detection output is widened by a pixel in each
+  // direction, so the outline stays visible without the blur
+  float edge = 0.0;
+
+  for (int x = -1; x <= 1; x++) {
+    for (int y = -1; y <= 1; y++) {
+      vec2 uv = uv_coord(fc.xy + vec2(float(x), float(y)));
+      edge = max(edge, texture(u_color_sampler, uv).r);
+    }
+  }
+
+  if (dims.enabled && edge > 0.1) {
+    f_color = vec4(dims.outline_color.rgb, 1.0);
+  } else {
+    f_color = vec4(0.0, 0.0, 0.0, 0.0);
+  }
+}
@@ -19,7 +19,10 @@ use parking_lot::Mutex;
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::quad_tree::QuadTree;
-use crate::{app::SharedState, gui::text::LabelPos};
+use crate::{
+    app::SharedState,
+    gui::text::{LabelLayout, LabelPos, LabelPriority},
+};
 use crate::{geometry::*, universe::Node, view::*};
 
 use nalgebra_glm as glm;
@@ -148,10 +151,14 @@ impl ClusterTree {
         }
     }
 
-    pub fn draw_labels(&self, ctx: &egui::CtxRef, shared_state: &SharedState) {
-        let view = shared_state.view();
-        let mouse_pos = shared_state.mouse_pos();
-
+    /// Adds the lines of every cluster to the label layout, stacked
+    /// below the cluster origin, with at most 10 lines per cluster
+    pub fn add_labels(
+        &self,
+        ctx: &egui::CtxRef,
+        view: View,
+        layout: &mut LabelLayout,
+    ) {
         for leaf in self.clusters.leaves() {
             for (origin, cluster) in leaf.elems() {
                 let mut y_offset = 0.0;
@@ -159,45 +166,19 @@ impl ClusterTree {
 
                 let offset = cluster.offset.unwrap_or_default();
 
-                let anchor_dir = Point::new(-offset.x, -offset.y);
                 let offset = offset * 20.0;
 
                 let lines = &cluster.lines;
 
                 for text in cluster.lines.iter() {
-                    let rect =
-                        crate::gui::text::draw_text_at_world_point_offset(
-                            ctx,
-                            view,
-                            origin,
-                            offset + Point::new(0.0, y_offset),
-                            text,
-                        );
-
-                    if let Some(rect) = rect {
-                        let rect = rect.resize(0.98);
-                        if rect.contains(mouse_pos) {
-                            crate::gui::text::draw_rect(ctx, rect);
-
-                            // TODO need some form of configurable callback here
-                            /*
-                            if gui.ctx.input().pointer.any_click() {
-                                match column {
-                                    AnnotationColumn::Gff3(col) => {
-                                        if let Some(gff) = records.downcast_ref::<Gff3Records>() {
-                                            gui.scroll_to_gff_record(gff, col, label.as_bytes());
-                                        }
-                                    }
-                                    AnnotationColumn::Bed(col) => {
-                                        if let Some(bed) = records.downcast_ref::<BedRecords>() {
-                                            gui.scroll_to_bed_record(bed, col, label.as_bytes());
-                                        }
-                                    }
-                                }
-                            }
-                            */
-                        }
-                    }
+                    layout.add_at_world_point(
+                        ctx,
+                        view,
+                        origin,
+                        offset + Point::new(0.0, y_offset),
+                        text,
+                        LabelPriority::Annotation,
+                    );
 
                     y_offset += 15.0;
                     count += 1;
@@ -209,12 +190,13 @@ impl ClusterTree {
                         if rem > 0 {
                             let more_label = format!("and {} more", rem);
 
-                            crate::gui::text::draw_text_at_world_point_offset(
+                            layout.add_at_world_point(
                                 ctx,
                                 view,
                                 origin,
                                 offset + Point::new(0.0, y_offset),
                                 &more_label,
+                                LabelPriority::Annotation,
                             );
                         }
                         break;
@@ -224,6 +206,51 @@ impl ClusterTree {
         }
     }
 
+    /// Adds all cluster labels to `layout`, which may already hold
+    /// other labels such as node IDs, and draws them together, hiding
+    /// or nudging labels that would overlap; returns the screen
+    /// rectangles of the drawn labels
+    pub fn draw_labels(
+        &self,
+        ctx: &egui::CtxRef,
+        shared_state: &SharedState,
+        mut layout: LabelLayout,
+    ) -> Vec<Rect> {
+        let view = shared_state.view();
+        let mouse_pos = shared_state.mouse_pos();
+
+        self.add_labels(ctx, view, &mut layout);
+
+        let drawn = layout.draw(ctx);
+
+        for rect in drawn.iter() {
+            let rect = rect.resize(0.98);
+            if rect.contains(mouse_pos) {
+                crate::gui::text::draw_rect(ctx, rect);
+
+                // TODO need some form of configurable callback here
+                /*
+                if gui.ctx.input().pointer.any_click() {
+                    match column {
+                        AnnotationColumn::Gff3(col) => {
+                            if let Some(gff) = records.downcast_ref::<Gff3Records>() {
+                                gui.scroll_to_gff_record(gff, col, label.as_bytes());
+                            }
+                        }
+                        AnnotationColumn::Bed(col) => {
+                            if let Some(bed) = records.downcast_ref::<BedRecords>() {
+                                gui.scroll_to_bed_record(bed, col, label.as_bytes());
+                            }
+                        }
+                    }
+                }
+                */
+            }
+        }
+
+        drawn
+    }
+
     pub fn draw_clusters(
         &self,
         ctx: &egui::CtxRef,
@@ -248,6 +275,9 @@ impl ClusterTree {
 pub struct Labels {
     // label_trees: HashMap<String, Arc<Mutex<QuadTree<String>>>>,
     label_trees: HashMap<String, QuadTree<(Option<Point>, String)>>,
+
+    // shared with the corresponding `AnnotationLabelSet`s
+    visible: HashMap<String, Arc<AtomicCell<bool>>>,
 }
 
 impl Labels {
@@ -257,6 +287,7 @@ impl Labels {
         nodes: &[Node],
         name: &str,
         labels: &LabelSet,
+        visible: Arc<AtomicCell<bool>>,
     ) {
         let name = name.to_string();
 
@@ -271,10 +302,55 @@ impl Labels {
             let _result = label_tree.insert(world, (offset, text.to_string()));
         }
 
+        self.visible.insert(name.clone(), visible);
         self.label_trees.insert(name, label_tree);
         // .insert(name, Arc::new(Mutex::new(label_tree)));
     }
 
+    pub fn remove_label_set(&mut self, name: &str) {
+        self.label_trees.remove(name);
+        self.visible.remove(name);
+    }
+
+    /// Sets the label at `node` in the label set `name`, replacing
+    /// any label already at that node, and creating the label set if
+    /// it doesn't exist
+    pub fn set_node_label(
+        &mut self,
+        boundary: Rect,
+        nodes: &[Node],
+        name: &str,
+        node: NodeId,
+        text: &str,
+    ) {
+        let label_pos = LabelPos::Handle {
+            handle: Handle::pack(node, false),
+            offset: None,
+        };
+
+        let world = label_pos.world(nodes);
+        let offset = label_pos.offset(nodes);
+
+        if !self.label_trees.contains_key(name) {
+            self.visible
+                .insert(name.to_string(), Arc::new(AtomicCell::new(true)));
+        }
+
+        let label_tree = self
+            .label_trees
+            .entry(name.to_string())
+            .or_insert_with(|| QuadTree::new(boundary));
+
+        if let Some(mut existing) =
+            label_tree.nearest_mut(world).filter(|p| p.point() == world)
+        {
+            *existing.data_mut() = (offset, text.to_string());
+            return;
+        }
+
+        let _result = label_tree.insert(world, (offset, text.to_string()));
+    }
+
     pub fn cluster(
         &self,
         boundary: Rect,
@@ -283,7 +359,14 @@ impl Labels {
     ) -> ClusterTree {
         let mut clusters = ClusterTree::from_boundary(boundary);
 
-        for (_name, tree) in self.label_trees.iter() {
+        for (name, tree) in self.label_trees.iter() {
+            let visible =
+                self.visible.get(name).map(|v| v.load()).unwrap_or(true);
+
+            if !visible {
+                continue;
+            }
+
             let _result =
                 clusters.insert_label_tree(&tree, label_radius, view.scale);
         }
@@ -373,6 +456,15 @@ impl AnnotationLabelSet {
         &self.labels
     }
 
+    /// The label strings at the given node
+    pub fn node_labels(&self, node: NodeId) -> impl Iterator<Item = &str> {
+        self.labels
+            .get(&node)
+            .into_iter()
+            .flatten()
+            .map(move |&ix| self.label_strings[ix].as_str())
+    }
+
     pub fn is_visible(&self) -> bool {
         self.show.load()
     }
@@ -380,6 +472,10 @@ impl AnnotationLabelSet {
     pub fn set_visibility(&self, to: bool) {
         self.show.store(to);
     }
+
+    pub fn visibility_cell(&self) -> &Arc<AtomicCell<bool>> {
+        &self.show
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -404,6 +500,10 @@ pub struct Annotations {
     label_sets: HashMap<String, Arc<AnnotationLabelSet>>,
 
     annotation_default_ref_path: HashMap<String, PathId>,
+
+    // for collections that span several paths, maps each record
+    // sequence ID to the path it's on, per collection
+    annotation_seq_id_paths: HashMap<String, HashMap<String, PathId>>,
 }
 
 impl Annotations {
@@ -424,6 +524,33 @@ impl Annotations {
         }
     }
 
+    pub fn get_seq_id_paths(
+        &self,
+        annot: &str,
+    ) -> Option<&HashMap<String, PathId>> {
+        self.annotation_seq_id_paths.get(annot)
+    }
+
+    /// Sets the path that records with the given sequence ID in the
+    /// collection are placed on, or removes the mapping if `path` is
+    /// `None`
+    pub fn set_seq_id_path(
+        &mut self,
+        annot: &str,
+        seq_id: &str,
+        path: Option<PathId>,
+    ) {
+        if let Some(path) = path {
+            self.annotation_seq_id_paths
+                .entry(annot.to_string())
+                .or_default()
+                .insert(seq_id.to_string(), path);
+        } else if let Some(paths) = self.annotation_seq_id_paths.get_mut(annot)
+        {
+            paths.remove(seq_id);
+        }
+    }
+
     pub fn insert_gff3(&mut self, name: &str, records: Gff3Records) {
         let records = Arc::new(records);
         self.gff3_annotations.insert(name.to_string(), records);
@@ -465,6 +592,10 @@ impl Annotations {
             .insert(name.to_string(), Arc::new(label_set));
     }
 
+    pub fn remove_label_set(&mut self, name: &str) {
+        self.label_sets.remove(name);
+    }
+
     pub fn get_label_set(
         &mut self,
         name: &str,
@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use bstr::ByteSlice;
 
 use anyhow::Result;
@@ -9,6 +11,7 @@ use super::{
 #[derive(Debug, Clone, Default)]
 pub struct BedRecords {
     file_name: String,
+    file_path: PathBuf,
 
     pub records: Vec<BedRecord>,
 
@@ -62,6 +65,11 @@ impl ColumnKey for BedColumn {
 }
 
 impl BedRecords {
+    /// The path the collection was loaded from
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
     pub fn parse_bed_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
         use std::fs::File;
 
@@ -69,6 +77,7 @@ impl BedRecords {
 
         let file_name = path.as_ref().file_name().unwrap();
         let file_name = file_name.to_str().unwrap().to_string();
+        let file_path = path.as_ref().to_owned();
 
         let file = File::open(path)?;
 
@@ -129,6 +138,131 @@ impl BedRecords {
 
         Ok(Self {
             file_name,
+            file_path,
+            records,
+            column_keys,
+
+            headers,
+        })
+    }
+
+    /// Parses a generic delimited file, using the given (0-based)
+    /// columns as the sequence ID, start, and end of each record.
+    ///
+    /// The first line must be a header, which names the rest of the
+    /// columns; files ending in `.csv` are comma-separated, anything
+    /// else is tab-separated. Rows whose coordinates can't be parsed
+    /// are skipped.
+    pub fn parse_delimited_file<P: AsRef<std::path::Path>>(
+        path: P,
+        seq_id_col: usize,
+        start_col: usize,
+        end_col: usize,
+    ) -> Result<Self> {
+        use std::fs::File;
+
+        use std::io::{BufRead, BufReader};
+
+        let file_name = path.as_ref().file_name().unwrap();
+        let file_name = file_name.to_str().unwrap().to_string();
+        let file_path = path.as_ref().to_owned();
+
+        let delimiter: &[u8] =
+            match file_path.extension().and_then(|ext| ext.to_str()) {
+                Some("csv") => b",",
+                _ => b"\t",
+            };
+
+        let coord_cols = [seq_id_col, start_col, end_col];
+
+        let file = File::open(path)?;
+
+        let mut reader = BufReader::new(file);
+
+        let mut buf: Vec<u8> = Vec::new();
+
+        let mut records = Vec::new();
+
+        // the header is reordered so that the coordinate columns come
+        // first, as in a BED file
+        let mut headers: Vec<Vec<u8>> = Vec::new();
+
+        loop {
+            buf.clear();
+
+            let read = reader.read_until(b'\n', &mut buf)?;
+
+            if read == 0 {
+                break;
+            }
+
+            let line = buf[0..read].trim_end_with(|c| c == '\n' || c == '\r');
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields = line.split_str(delimiter).collect::<Vec<_>>();
+
+            if headers.is_empty() {
+                let fields = fields
+                    .iter()
+                    .map(|field| field.trim_start_with(|c| c == '#'));
+
+                let mut names =
+                    fields.map(|f| f.to_owned()).collect::<Vec<_>>();
+
+                if coord_cols.iter().any(|&col| col >= names.len()) {
+                    anyhow::bail!(
+                        "Column out of range, the header has {} columns",
+                        names.len()
+                    );
+                }
+
+                headers
+                    .extend(coord_cols.iter().map(|&col| names[col].clone()));
+                headers.extend(
+                    names
+                        .drain(..)
+                        .enumerate()
+                        .filter(|(ix, _)| !coord_cols.contains(ix))
+                        .map(|(_, name)| name),
+                );
+
+                continue;
+            }
+
+            let mut row: Vec<&[u8]> = coord_cols
+                .iter()
+                .map(|&col| fields.get(col).copied().unwrap_or_default())
+                .collect();
+
+            row.extend(
+                fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(ix, _)| !coord_cols.contains(ix))
+                    .map(|(_, field)| *field),
+            );
+
+            if let Some(record) = BedRecord::parse_row(row.into_iter()) {
+                records.push(record);
+            }
+        }
+
+        let mut column_keys: Vec<BedColumn> =
+            vec![BedColumn::Chr, BedColumn::Start, BedColumn::End];
+
+        column_keys.extend(headers.iter().skip(3).enumerate().map(
+            |(ix, h)| BedColumn::Header {
+                index: ix,
+                name: h.to_owned(),
+            },
+        ));
+
+        Ok(Self {
+            file_name,
+            file_path,
             records,
             column_keys,
 
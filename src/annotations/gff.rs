@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use bstr::ByteSlice;
 
@@ -13,6 +14,7 @@ use super::{
 #[derive(Debug, Clone, Default)]
 pub struct Gff3Records {
     file_name: String,
+    file_path: PathBuf,
 
     pub records: Vec<Gff3Record>,
 
@@ -252,12 +254,18 @@ impl std::fmt::Display for Gff3Column {
 }
 
 impl Gff3Records {
+    /// The path the collection was loaded from
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
     pub fn parse_gff3_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
         use std::fs::File;
         use std::io::{BufRead, BufReader};
 
         let file_name = path.as_ref().file_name().unwrap();
         let file_name = file_name.to_str().unwrap().to_string();
+        let file_path = path.as_ref().to_owned();
 
         let file = File::open(path)?;
 
@@ -301,6 +309,7 @@ impl Gff3Records {
 
         Ok(Self {
             file_name,
+            file_path,
 
             records,
             attribute_keys,
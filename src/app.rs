@@ -1,5 +1,7 @@
+pub mod bench;
 pub mod channels;
 pub mod mainview;
+pub mod ruler;
 pub mod selection;
 pub mod settings;
 pub mod shared_state;
@@ -13,7 +15,9 @@ use crossbeam::channel::Sender;
 
 use rustc_hash::{FxHashMap, FxHashSet};
 
-use handlegraph::handle::NodeId;
+use handlegraph::{handle::NodeId, handlegraph::*, pathhandlegraph::*};
+
+use bstr::ByteSlice;
 
 use anyhow::Result;
 
@@ -22,19 +26,24 @@ use argh::FromArgs;
 use std::sync::Arc;
 
 use self::mainview::MainViewMsg;
+use self::ruler::{PathRuler, Ruler};
 use crate::annotations::{
     AnnotationCollection, AnnotationLabelSet, Annotations, BedRecords,
     Gff3Records, Labels,
 };
 use crate::app::selection::NodeSelection;
+use crate::graph_query::GraphQuery;
 use crate::gui::GuiMsg;
 use crate::view::*;
 use crate::{geometry::*, input::binds::SystemInputBindings};
 use crate::{
-    input::binds::{BindableInput, KeyBind, SystemInput},
+    input::binds::{BindableInput, KeyBind, MouseButtonBind, SystemInput},
     universe::Node,
 };
 
+/// The name of the label set that `set_node_label` adds labels to
+pub const SCRIPT_LABEL_SET: &str = "script_labels";
+
 pub struct App {
     shared_state: SharedState,
     channels: AppChannels,
@@ -45,32 +54,66 @@ pub struct App {
 
     pub selected_nodes_bounding_box: Option<(Point, Point)>,
 
+    // the selection center the view was last moved to when following
+    // the selection
+    followed_center: Option<Point>,
+
+    // the node whose paths are being cycled through with
+    // `KeyCycleNodePaths`, and the index of the last selected path
+    path_cycle: Option<(NodeId, usize)>,
+
     annotations: Annotations,
 
     labels: Labels,
+
+    // the measurement ruler, if ruler mode is enabled
+    ruler: Option<Ruler>,
+
+    // the base pair rulers shown along paths, by path name
+    path_rulers: FxHashMap<String, PathRuler>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AppInput {
     KeyClearSelection,
     KeyToggleTheme,
+    KeyFitSelection,
+    KeyCycleNodePaths,
+    KeyToggleRuler,
+    ButtonRulerPoint,
 }
 
-impl BindableInput for AppInput {
-    fn default_binds() -> SystemInputBindings<Self> {
+impl AppInput {
+    /// The default bindings, but with ruler points placed with the
+    /// given mouse button, which should be the main view's pan button
+    pub fn binds_with_ruler_button(
+        ruler_button: winit::event::MouseButton,
+    ) -> SystemInputBindings<Self> {
         use winit::event::VirtualKeyCode as Key;
         use AppInput as Input;
 
         let key_binds: FxHashMap<Key, Vec<KeyBind<Input>>> = [
             (Key::Escape, Input::KeyClearSelection),
             (Key::F9, Input::KeyToggleTheme),
+            (Key::F, Input::KeyFitSelection),
+            (Key::C, Input::KeyCycleNodePaths),
+            (Key::M, Input::KeyToggleRuler),
         ]
         .iter()
         .copied()
         .map(|(k, i)| (k, vec![KeyBind::new(i)]))
         .collect::<FxHashMap<_, _>>();
 
-        let mouse_binds = FxHashMap::default();
+        let mouse_binds: FxHashMap<
+            winit::event::MouseButton,
+            Vec<MouseButtonBind<Input>>,
+        > = [(
+            ruler_button,
+            vec![MouseButtonBind::new(Input::ButtonRulerPoint)],
+        )]
+        .iter()
+        .cloned()
+        .collect();
 
         let wheel_bind = None;
 
@@ -78,6 +121,12 @@ impl BindableInput for AppInput {
     }
 }
 
+impl BindableInput for AppInput {
+    fn default_binds() -> SystemInputBindings<Self> {
+        Self::binds_with_ruler_button(winit::event::MouseButton::Left)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Select {
     Clear,
@@ -95,7 +144,18 @@ pub enum Select {
 pub enum AppMsg {
     Selection(Select),
     GotoSelection,
+    /// Like `GotoSelection`, but scales the view so that the entire
+    /// selection fits on screen, with some padding
+    FitSelection,
+    /// Scales and centers the view so that the entire graph fits on
+    /// screen, with the same padding as `FitSelection`
+    FitGraph,
     GotoNode(NodeId),
+    /// Like `GotoNode`, but also scales the view so that the node
+    /// spans a quarter of the screen
+    ZoomToNode(NodeId),
+    /// Returns the view to the one the graph was first shown with
+    ResetView,
 
     // TODO these two should not be here (see how they're handled in main)
     RectSelect(Rect),
@@ -113,8 +173,57 @@ pub enum AppMsg {
         label_set: AnnotationLabelSet,
     },
 
+    RemoveNodeLabels {
+        name: String,
+    },
+
+    /// Sets a label in the label set for script-defined labels
+    SetNodeLabel {
+        node: NodeId,
+        text: String,
+    },
+    ClearNodeLabels,
+
+    /// Shows the ruler along the path with the given name, or hides
+    /// it if `ruler` is `None`
+    SetPathRuler {
+        name: String,
+        ruler: Option<PathRuler>,
+    },
+
     RequestSelection(crossbeam::channel::Sender<(Rect, FxHashSet<NodeId>)>),
 
+    /// Replaces the loaded graph with the one in the GFA file, using
+    /// the layout file if the GFA lacks layout tags
+    ReloadGraph {
+        gfa: String,
+        layout: Option<String>,
+    },
+
+    /// Loads a GFA file to draw on top of the main graph, using the
+    /// layout file if the GFA lacks layout tags
+    LoadOverlayGraph {
+        gfa: String,
+        layout: Option<String>,
+    },
+
+    /// Shows or hides the overlaid graph with the given index
+    ToggleOverlayGraph(usize),
+
+    /// Moves the overlaid graph with the given index to a new offset
+    /// from the main graph, in world units
+    SetOverlayGraphOffset {
+        index: usize,
+        offset: Point,
+    },
+
+    /// Writes the visible part of the graph to a file as SVG, sending
+    /// back the number of nodes written
+    ExportSvg {
+        path: String,
+        sender: crossbeam::channel::Sender<Result<usize>>,
+    },
+
     RequestData {
         key: String,
         index: String,
@@ -141,11 +250,19 @@ impl App {
 
             selected_nodes_bounding_box: None,
 
+            followed_center: None,
+
+            path_cycle: None,
+
             settings: AppSettings::default(),
 
             annotations: Annotations::default(),
 
             labels: Labels::default(),
+
+            ruler: None,
+
+            path_rulers: FxHashMap::default(),
         })
     }
 
@@ -205,6 +322,39 @@ impl App {
         &self.annotations
     }
 
+    /// Clears the selection, hovered node, and node labels, which
+    /// refer to the nodes of the previously loaded graph; called when
+    /// the graph is reloaded
+    pub fn clear_graph_state(&mut self) {
+        self.selection_changed = true;
+        self.selected_nodes.clear();
+        self.selected_nodes_bounding_box = None;
+
+        self.followed_center = None;
+        self.path_cycle = None;
+
+        self.shared_state.hover_node.store(None);
+        self.shared_state.node_data.write().clear();
+
+        self.labels = Labels::default();
+
+        if let Some(ruler) = self.ruler.as_mut() {
+            *ruler = Ruler::default();
+        }
+
+        self.path_rulers.clear();
+    }
+
+    /// The measurement ruler, if ruler mode is enabled
+    pub fn ruler(&self) -> Option<&Ruler> {
+        self.ruler.as_ref()
+    }
+
+    /// The rulers shown along paths with `show_path_ruler`
+    pub fn path_rulers(&self) -> impl Iterator<Item = &PathRuler> {
+        self.path_rulers.values()
+    }
+
     pub fn labels(&self) -> &Labels {
         &self.labels
     }
@@ -225,6 +375,34 @@ impl App {
         self.shared_state.screen_dims.store(screen_dims.into());
     }
 
+    /// If the view is set to follow the selection, moves the view to
+    /// the center of the selection's bounding box whenever it has
+    /// changed since the last call. Should be called once per frame.
+    pub fn update_follow_selection(
+        &mut self,
+        main_view_msg_tx: &Sender<MainViewMsg>,
+    ) {
+        if !self.shared_state.follow_selection.load() {
+            self.followed_center = None;
+            return;
+        }
+
+        let center = match self.selected_nodes_bounding_box {
+            Some((p0, p1)) if !self.selected_nodes.is_empty() => {
+                Rect::new(p0, p1).center()
+            }
+            _ => return,
+        };
+
+        if self.followed_center != Some(center) {
+            let mut view = self.shared_state.view();
+            view.center = center;
+            main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
+
+            self.followed_center = Some(center);
+        }
+    }
+
     pub fn apply_app_msg(
         &mut self,
         boundary: Rect,
@@ -237,6 +415,14 @@ impl App {
             AppMsg::RectSelect(_rect) => {
                 //
             }
+            AppMsg::ExportSvg { .. }
+            | AppMsg::ReloadGraph { .. }
+            | AppMsg::LoadOverlayGraph { .. }
+            | AppMsg::ToggleOverlayGraph(_)
+            | AppMsg::SetOverlayGraphOffset { .. } => {
+                // handled in main, where the node positions and
+                // overlays are available
+            }
             AppMsg::TranslateSelected(delta) => {
                 if let Some(bounds) = self.selected_nodes_bounding_box {
                     let min = bounds.0 + delta;
@@ -255,6 +441,27 @@ impl App {
                     main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
                 }
             }
+            AppMsg::FitSelection => {
+                if let Some((p0, p1)) = self.selected_nodes_bounding_box {
+                    let rect = Rect::new(p0, p1);
+                    let view = View::fit_rect(self.dims(), rect, 1.2);
+                    main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
+                }
+            }
+            AppMsg::FitGraph => {
+                // the current node positions are used, rather than
+                // the layout's bounding box, so that translated nodes
+                // are included
+                if !node_positions.is_empty() {
+                    let rect = node_positions
+                        .iter()
+                        .fold(Rect::nowhere(), |rect, node| {
+                            rect.union(Rect::new(node.p0, node.p1))
+                        });
+                    let view = View::fit_rect(self.dims(), rect, 1.2);
+                    main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
+                }
+            }
             AppMsg::GotoNode(id) => {
                 if let Some(node_pos) = node_positions.get((id.0 - 1) as usize)
                 {
@@ -263,6 +470,23 @@ impl App {
                     main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
                 }
             }
+            AppMsg::ZoomToNode(id) => {
+                if let Some(node_pos) = node_positions.get((id.0 - 1) as usize)
+                {
+                    let dims = self.dims();
+                    let screen_len = dims.width.min(dims.height);
+                    let node_len = node_pos.p0.dist(node_pos.p1).max(1.0);
+
+                    let view = View {
+                        center: node_pos.center(),
+                        scale: (4.0 * node_len) / screen_len.max(1.0),
+                    };
+                    main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
+                }
+            }
+            AppMsg::ResetView => {
+                main_view_msg_tx.send(MainViewMsg::ResetView).unwrap();
+            }
             AppMsg::HoverNode(id) => self.shared_state.hover_node.store(id),
 
             AppMsg::Selection(sel) => match sel {
@@ -396,9 +620,33 @@ impl App {
                     node_positions,
                     &name,
                     &label_set_,
+                    label_set.visibility_cell().clone(),
                 );
                 self.annotations.insert_label_set(&name, label_set);
             }
+            AppMsg::RemoveNodeLabels { name } => {
+                self.labels.remove_label_set(&name);
+                self.annotations.remove_label_set(&name);
+            }
+            AppMsg::SetNodeLabel { node, text } => {
+                self.labels.set_node_label(
+                    boundary,
+                    node_positions,
+                    SCRIPT_LABEL_SET,
+                    node,
+                    &text,
+                );
+            }
+            AppMsg::ClearNodeLabels => {
+                self.labels.remove_label_set(SCRIPT_LABEL_SET);
+            }
+            AppMsg::SetPathRuler { name, ruler } => {
+                if let Some(ruler) = ruler {
+                    self.path_rulers.insert(name, ruler);
+                } else {
+                    self.path_rulers.remove(&name);
+                }
+            }
             AppMsg::ToggleDarkMode => {
                 self.toggle_dark_mode(gui_msg);
             }
@@ -461,6 +709,74 @@ impl App {
                             Ok(rhai::Dynamic::from(()))
                         }
                     }
+                    "annotation_seq_id_paths" => {
+                        let paths = self
+                            .annotations
+                            .get_seq_id_paths(&index)
+                            .cloned()
+                            .unwrap_or_default();
+
+                        Ok(rhai::Dynamic::from(paths))
+                    }
+                    "label_set_names" => {
+                        let mut names = self
+                            .annotations
+                            .label_sets()
+                            .keys()
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        names.sort();
+
+                        Ok(rhai::Dynamic::from(names))
+                    }
+                    "annotation_paths" => {
+                        let paths = self
+                            .annotations
+                            .annot_names()
+                            .iter()
+                            .filter_map(|(name, _)| {
+                                let path = if let Some(records) =
+                                    self.annotations.get_gff3(name)
+                                {
+                                    records.file_path()
+                                } else {
+                                    self.annotations.get_bed(name)?.file_path()
+                                };
+                                Some(path.to_string_lossy().to_string())
+                            })
+                            .collect::<Vec<_>>();
+
+                        Ok(rhai::Dynamic::from(paths))
+                    }
+                    "label_sets" => {
+                        let mut label_sets = self
+                            .annotations
+                            .label_sets()
+                            .values()
+                            .map(|label_set| label_set.as_ref().clone())
+                            .collect::<Vec<_>>();
+                        label_sets.sort_by(|a, b| {
+                            a.label_set_name.cmp(&b.label_set_name)
+                        });
+
+                        Ok(rhai::Dynamic::from(label_sets))
+                    }
+                    "node_positions" => {
+                        Ok(rhai::Dynamic::from(node_positions.to_vec()))
+                    }
+                    "node_position" => {
+                        let node = index
+                            .parse::<u64>()
+                            .ok()
+                            .and_then(|id| id.checked_sub(1))
+                            .and_then(|ix| node_positions.get(ix as usize));
+                        handle!(node, "Node not found")
+                    }
+                    "overlay_colors" | "node_id_at" => {
+                        // answered in main, where the overlays and the
+                        // node ID buffer are
+                        return;
+                    }
                     _ => {
                         let err =
                             anyhow::anyhow!("Requested unknown key from App");
@@ -482,6 +798,21 @@ impl App {
                             .set_default_ref_path(&index, Some(path));
                     }
                 }
+                "annotation_seq_id_path" => {
+                    if let Some((seq_id, path)) =
+                        value.try_cast::<(String, Option<PathId>)>()
+                    {
+                        self.annotations.set_seq_id_path(&index, &seq_id, path);
+                    }
+                }
+                "label_set_visible" => {
+                    if let (Some(label_set), Some(visible)) = (
+                        self.annotations.get_label_set(&index),
+                        value.try_cast::<bool>(),
+                    ) {
+                        label_set.set_visibility(visible);
+                    }
+                }
                 _ => (),
             },
         }
@@ -499,12 +830,108 @@ impl App {
         gui_msg.send(msg).unwrap();
     }
 
+    /// Selects the next path through the node the cycle started
+    /// from; a cycle starts from the selected node when exactly one
+    /// node is selected, and continues while the selection is a path
+    /// from the cycle
+    fn cycle_node_paths(&mut self, graph_query: &GraphQuery) {
+        let single_node = if self.selected_nodes.len() == 1 {
+            self.selected_nodes.iter().next().copied()
+        } else {
+            None
+        };
+
+        let (node, path_ix) = match (single_node, self.path_cycle) {
+            (Some(node), Some((cycle_node, ix))) if node == cycle_node => {
+                (node, ix + 1)
+            }
+            (Some(node), _) => (node, 0),
+            (None, Some((cycle_node, ix))) => {
+                // only continue if the selection is still the path the
+                // cycle selected last; otherwise the cycle is over
+                let cycled = graph_query
+                    .node_paths(cycle_node)
+                    .get(ix)
+                    .map(|&path| Self::path_nodes(graph_query, path));
+
+                if cycled.as_ref() != Some(&self.selected_nodes) {
+                    self.path_cycle = None;
+                    return;
+                }
+
+                (cycle_node, ix + 1)
+            }
+            (None, None) => return,
+        };
+
+        let paths = graph_query.node_paths(node);
+
+        if paths.is_empty() {
+            self.path_cycle = None;
+            return;
+        }
+
+        let path_ix = path_ix % paths.len();
+        let path = paths[path_ix];
+
+        let graph = graph_query.graph();
+
+        let nodes = Self::path_nodes(graph_query, path);
+
+        if let Some(name) = graph.get_path_name_vec(path) {
+            log::info!(
+                "selecting path {} ({}/{}) through node {}",
+                name.as_bstr(),
+                path_ix + 1,
+                paths.len(),
+                node.0
+            );
+        }
+
+        self.path_cycle = Some((node, path_ix));
+
+        self.channels
+            .app_tx
+            .send(AppMsg::Selection(Select::Many { nodes, clear: true }))
+            .unwrap();
+    }
+
+    fn path_nodes(graph_query: &GraphQuery, path: PathId) -> FxHashSet<NodeId> {
+        graph_query
+            .graph()
+            .path_steps(path)
+            .map(|steps| {
+                steps
+                    .map(|step| step.handle().id())
+                    .collect::<FxHashSet<_>>()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn apply_input(
         &mut self,
         input: SystemInput<AppInput>,
         gui_msg: &Sender<GuiMsg>,
+        graph_query: &GraphQuery,
     ) {
-        if let SystemInput::Keyboard { state, payload } = input {
+        if let SystemInput::MouseButton {
+            pos,
+            state,
+            payload,
+        } = input
+        {
+            if let AppInput::ButtonRulerPoint = payload {
+                if state.pressed() {
+                    let view = self.shared_state.view();
+                    let world = view.screen_point_to_world(self.dims(), pos);
+                    let node = self.hover_node();
+
+                    if let Some(ruler) = self.ruler.as_mut() {
+                        ruler.click(graph_query, world, node);
+                    }
+                }
+            }
+        } else if let SystemInput::Keyboard { state, payload } = input {
             match payload {
                 AppInput::KeyClearSelection => {
                     if state.pressed() {
@@ -518,11 +945,44 @@ impl App {
                         self.toggle_dark_mode(gui_msg);
                     }
                 }
+                AppInput::KeyFitSelection => {
+                    if state.pressed() {
+                        self.channels
+                            .app_tx
+                            .send(AppMsg::FitSelection)
+                            .unwrap();
+                    }
+                }
+                AppInput::KeyCycleNodePaths => {
+                    if state.pressed() {
+                        self.cycle_node_paths(graph_query);
+                    }
+                }
+                AppInput::KeyToggleRuler => {
+                    if state.pressed() {
+                        self.ruler = match self.ruler {
+                            Some(_) => None,
+                            None => Some(Ruler::default()),
+                        };
+                    }
+                }
+                AppInput::ButtonRulerPoint => (),
             }
         }
     }
 }
 
+/// The window title, including the file name of the loaded GFA, so
+/// that multiple instances can be told apart
+pub fn window_title(gfa_path: &str) -> String {
+    let file_name = std::path::Path::new(gfa_path)
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_else(|| gfa_path.into());
+
+    format!("Gfaestus - {}", file_name)
+}
+
 #[derive(FromArgs)]
 /// Gfaestus
 pub struct Args {
@@ -530,14 +990,26 @@ pub struct Args {
     #[argh(positional)]
     pub gfa: String,
 
-    /// the layout file to use
+    /// the layout file to use; can be omitted if the GFA segments have LX/LY tags
     #[argh(positional)]
-    pub layout: String,
+    pub layout: Option<String>,
 
     /// load and run a Rhai script file at startup, e.g. for configuration
     #[argh(option)]
     pub run_script: Option<String>,
 
+    /// accept console scripts over TCP on the given localhost port
+    #[argh(option)]
+    pub listen: Option<u16>,
+
+    /// import all .rhai files in a directory as console modules at startup
+    #[argh(option, long = "scripts-dir")]
+    pub scripts_dir: Option<std::path::PathBuf>,
+
+    /// cache script overlays in a directory, reusing them across sessions
+    #[argh(option, long = "overlay-cache")]
+    pub overlay_cache: Option<std::path::PathBuf>,
+
     #[cfg(target_os = "linux")]
     /// force use of X11 window (only applicable in Wayland contexts)
     #[argh(switch)]
@@ -571,6 +1043,18 @@ pub struct Args {
         from_str_fn(annotation_files_to_str)
     )]
     pub annotation_files: Vec<std::path::PathBuf>,
+
+    /// run a pan and zoom sweep for the given number of frames, write the frame times to the `--bench-out` file, and exit
+    #[argh(option)]
+    pub bench: Option<usize>,
+
+    /// the CSV file frame times are written to when running with `--bench`
+    #[argh(
+        option,
+        long = "bench-out",
+        default = "std::path::PathBuf::from(\"frame_times.csv\")"
+    )]
+    pub bench_out: std::path::PathBuf,
 }
 
 fn annotation_files_to_str(input: &str) -> Result<std::path::PathBuf, String> {
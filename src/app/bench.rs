@@ -0,0 +1,80 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::geometry::*;
+use crate::view::View;
+
+/// Runs a fixed pan and zoom sweep over the graph for a number of
+/// frames, recording the time taken by each frame, for `--bench`.
+///
+/// The sweep starts from the view at the first recorded frame,
+/// zooms in to a twentieth of its scale and back out, while panning
+/// in a circle around its center.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    frames: usize,
+    out_path: PathBuf,
+
+    start_view: Option<View>,
+    frame_times: Vec<f32>,
+}
+
+impl Benchmark {
+    pub fn new(frames: usize, out_path: PathBuf) -> Self {
+        Self {
+            frames,
+            out_path,
+
+            start_view: None,
+            frame_times: Vec::with_capacity(frames),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.frame_times.len() >= self.frames
+    }
+
+    /// Records the time of the frame that was just drawn, in
+    /// seconds, and returns the view to use for the next frame
+    pub fn record_frame(
+        &mut self,
+        current_view: View,
+        frame_time: f32,
+    ) -> View {
+        let start = *self.start_view.get_or_insert(current_view);
+
+        self.frame_times.push(frame_time);
+
+        let t = self.frame_times.len() as f32 / self.frames.max(1) as f32;
+        let angle = t * 2.0 * std::f32::consts::PI;
+
+        let scale = start.scale * (1.0 - 0.95 * (angle / 2.0).sin());
+
+        // the pan radius is 200 pixels at the starting zoom level
+        let radius = 200.0 * start.scale;
+        let center = start.center
+            + Point::new(radius * angle.sin(), radius * (1.0 - angle.cos()));
+
+        View { center, scale }
+    }
+
+    /// Writes the recorded frame times to the output file as CSV,
+    /// with one row per frame, in milliseconds
+    pub fn write_csv(&self) -> Result<()> {
+        let mut file = std::fs::File::create(&self.out_path)?;
+
+        writeln!(file, "frame,frame_time_ms")?;
+
+        for (frame, time) in self.frame_times.iter().enumerate() {
+            writeln!(file, "{},{}", frame, time * 1000.0)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn out_path(&self) -> &PathBuf {
+        &self.out_path
+    }
+}
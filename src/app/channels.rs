@@ -1,10 +1,11 @@
 use crossbeam::channel::{self, Receiver, Sender};
+use handlegraph::handle::NodeId;
 use winit::event::VirtualKeyCode;
 
-use crate::app::mainview::MainViewMsg;
+use crate::app::mainview::{MainViewMsg, ViewUpdate};
 use crate::app::AppMsg;
 use crate::gui::GuiMsg;
-use crate::overlays::OverlayData;
+use crate::overlays::{OverlayData, OverlayKind, OverlayLegend};
 
 pub type BindMsg = (
     VirtualKeyCode,
@@ -12,7 +13,25 @@ pub type BindMsg = (
 );
 
 pub enum OverlayCreatorMsg {
-    NewOverlay { name: String, data: OverlayData },
+    NewOverlay {
+        name: String,
+        data: OverlayData,
+        legend: Option<OverlayLegend>,
+    },
+    /// Like `NewOverlay`, but the ID and kind of the overlay are sent
+    /// on `created_tx` once it has been created
+    NewOverlayNotify {
+        name: String,
+        data: OverlayData,
+        created_tx: futures::channel::oneshot::Sender<(usize, OverlayKind)>,
+    },
+    /// Writes the colors of the given nodes directly into the RGB
+    /// overlay with the given name, creating it if it doesn't exist,
+    /// and makes it the current overlay
+    SetNodeColors {
+        name: String,
+        colors: Vec<(NodeId, rgb::RGBA<f32>)>,
+    },
 }
 
 #[derive(Clone)]
@@ -23,6 +42,9 @@ pub struct AppChannels {
     pub main_view_tx: Sender<MainViewMsg>,
     pub main_view_rx: Receiver<MainViewMsg>,
 
+    pub view_update_tx: Sender<ViewUpdate>,
+    pub view_update_rx: Receiver<ViewUpdate>,
+
     pub gui_tx: Sender<GuiMsg>,
     pub gui_rx: Receiver<GuiMsg>,
 
@@ -40,6 +62,8 @@ impl AppChannels {
     pub(super) fn new() -> Self {
         let (app_tx, app_rx) = channel::unbounded::<AppMsg>();
         let (main_view_tx, main_view_rx) = channel::unbounded::<MainViewMsg>();
+        let (view_update_tx, view_update_rx) =
+            channel::unbounded::<ViewUpdate>();
         let (gui_tx, gui_rx) = channel::unbounded::<GuiMsg>();
         let (binds_tx, binds_rx) = channel::unbounded::<BindMsg>();
         let (new_overlay_tx, new_overlay_rx) =
@@ -54,6 +78,9 @@ impl AppChannels {
             main_view_tx,
             main_view_rx,
 
+            view_update_tx,
+            view_update_rx,
+
             gui_tx,
             gui_rx,
 
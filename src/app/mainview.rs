@@ -7,16 +7,22 @@ use std::sync::Arc;
 
 use anyhow::Result;
 
-use handlegraph::handle::NodeId;
+use handlegraph::{handle::NodeId, packedgraph::PackedGraph};
 
 use rustc_hash::{FxHashMap, FxHashSet};
 
+use parking_lot::RwLock;
+
 use crate::{
     app::{selection::SelectionBuffer, NodeWidth},
+    quad_tree::QuadTree,
+    universe::Node,
     vulkan::texture::GradientTexture,
 };
 use crate::{
     context::ContextEntry,
+    gui::text::{LabelLayout, LabelPriority},
+    gui::widgets::MemoryUsage,
     view::{ScreenDims, View},
 };
 use crate::{geometry::*, vulkan::render_pass::Framebuffers};
@@ -27,12 +33,18 @@ use crate::input::binds::{
 };
 
 use crate::vulkan::{
-    draw_system::nodes::{NodeIdBuffer, NodePipelines},
+    draw_system::{
+        nodes::{
+            NodeCaps, NodeIdBuffer, NodePipelines, NodeVertices, OverlayGraph,
+        },
+        Vertex,
+    },
     GfaestusVk,
 };
 
 use ash::vk;
 
+pub mod svg;
 pub mod view;
 
 use view::*;
@@ -44,22 +56,73 @@ pub struct MainView {
     pub node_id_buffer: NodeIdBuffer,
     pub selection_buffer: SelectionBuffer,
 
+    // the number of nodes in the main graph; the nodes of overlaid
+    // graphs have IDs above this in the node ID buffer
+    node_count: usize,
+    overlay_graphs: Vec<OverlayGraph>,
+
     node_width: Arc<NodeWidth>,
 
     anim_handler: AnimHandler,
 
     view_input_state: ViewInputState,
 
+    // node endpoints in world space, used for box selection and
+    // (optionally) hover detection
+    node_tree: Arc<RwLock<NodeTree>>,
+
     settings: AppSettings,
     shared_state: SharedState,
     channels: AppChannels,
 
     move_delta: AtomicCell<Option<Point>>,
+
+    // the time and screen position of the last click, for detecting
+    // double-clicks
+    last_click: AtomicCell<Option<(std::time::Instant, Point)>>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum MainViewMsg {
     GotoView(View),
+    ResetView,
+}
+
+/// Direct changes to the view, from the console or other systems.
+///
+/// These are queued and applied once per frame by `MainView`, in
+/// priority order: full views first, then the center and scale
+/// changes, and finally the relative nudges, so that updates sent
+/// during the same frame are combined deterministically.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewUpdate {
+    SetFull(View),
+    SetCenter(Point),
+    SetScale(f32),
+    Nudge(Point),
+}
+
+impl ViewUpdate {
+    fn priority(&self) -> usize {
+        match self {
+            ViewUpdate::SetFull(_) => 0,
+            ViewUpdate::SetCenter(_) => 1,
+            ViewUpdate::SetScale(_) => 1,
+            ViewUpdate::Nudge(_) => 2,
+        }
+    }
+
+    fn apply(&self, view: View) -> View {
+        match *self {
+            ViewUpdate::SetFull(view) => view,
+            ViewUpdate::SetCenter(center) => View { center, ..view },
+            ViewUpdate::SetScale(scale) => View { scale, ..view },
+            ViewUpdate::Nudge(delta) => View {
+                center: view.center + delta,
+                ..view
+            },
+        }
+    }
 }
 
 impl MainView {
@@ -105,14 +168,21 @@ impl MainView {
             node_id_buffer,
             selection_buffer,
 
+            node_count,
+            overlay_graphs: Vec::new(),
+
             node_width,
 
             anim_handler,
 
             view_input_state: Default::default(),
 
+            node_tree: Arc::new(RwLock::new(NodeTree::default())),
+
             move_delta: AtomicCell::new(None),
 
+            last_click: AtomicCell::new(None),
+
             settings,
             shared_state,
             channels,
@@ -138,12 +208,13 @@ impl MainView {
                     kind: AnimationKind::Absolute,
                     order: AnimationOrder::Transform {
                         center: view.center,
-                        scale: view.scale,
+                        scale: self.settings.clamp_scale(view.scale),
                     },
                     duration: Duration::from_millis(500),
                 };
                 self.anim_handler.send_anim_def(anim_def);
             }
+            MainViewMsg::ResetView => self.reset_view(),
         }
     }
 
@@ -159,12 +230,45 @@ impl MainView {
     }
 
     pub fn reset_view(&self) {
-        self.shared_state
-            .set_view(self.anim_handler.initial_view.load());
+        self.set_view(self.anim_handler.initial_view.load());
     }
 
     pub fn set_view(&self, view: View) {
-        self.shared_state.set_view(view);
+        self.send_view_update(ViewUpdate::SetFull(view));
+    }
+
+    pub fn send_view_update(&self, update: ViewUpdate) {
+        self.channels.view_update_tx.send(update).unwrap();
+    }
+
+    /// Applies all queued view updates, in priority order, replacing
+    /// any view animation in progress
+    pub fn apply_view_updates(&self) {
+        let mut updates: Vec<ViewUpdate> =
+            self.channels.view_update_rx.try_iter().collect();
+
+        if updates.is_empty() {
+            return;
+        }
+
+        updates.sort_by_key(|update| update.priority());
+
+        let mut view = updates
+            .iter()
+            .fold(self.shared_state.view(), |view, update| update.apply(view));
+        view.scale = self.settings.clamp_scale(view.scale);
+
+        // the animation handler owns the view, so the resolved view
+        // is sent as an instant animation
+        let anim_def = AnimationDef {
+            kind: AnimationKind::Absolute,
+            order: AnimationOrder::Transform {
+                center: view.center,
+                scale: view.scale,
+            },
+            duration: std::time::Duration::from_millis(0),
+        };
+        self.anim_handler.send_anim_def(anim_def);
     }
 
     pub fn node_id_buffer(&self) -> vk::Buffer {
@@ -199,8 +303,316 @@ impl MainView {
         let x = point.x as u32;
         let y = point.y as u32;
 
-        self.node_id_buffer
-            .read(self.node_draw_system.device(), x, y)
+        let device = self.node_draw_system.device();
+
+        let kernel_size = self.settings.hover_kernel_size().load();
+
+        if kernel_size > 1 {
+            self.node_id_buffer.read_majority(device, x, y, kernel_size)
+        } else {
+            self.node_id_buffer.read(device, x, y)
+        }
+    }
+
+    /// Maps a value in the node ID buffer to the graph the node
+    /// belongs to -- `None` for the main graph, otherwise the index of
+    /// the overlaid graph -- and the node's ID in that graph
+    pub fn resolve_node_id(&self, id: u32) -> Option<(Option<usize>, NodeId)> {
+        let id = id as usize;
+
+        if id == 0 {
+            return None;
+        }
+
+        if id <= self.node_count {
+            return Some((None, NodeId::from(id as u64)));
+        }
+
+        self.overlay_graphs
+            .iter()
+            .enumerate()
+            .find_map(|(ix, graph)| {
+                let local = id.checked_sub(graph.id_offset as usize)?;
+                if local >= 1 && local <= graph.node_count {
+                    Some((Some(ix), NodeId::from(local as u64)))
+                } else {
+                    None
+                }
+            })
+    }
+
+    // the node of the main graph under the provided screen position,
+    // according to the node ID buffer
+    fn main_graph_node_at(&self, point: Point) -> Option<NodeId> {
+        let id = self.read_node_id_at(point)?;
+
+        match self.resolve_node_id(id)? {
+            (None, node) => Some(node),
+            (Some(_), _) => None,
+        }
+    }
+
+    /// Returns the name of the overlaid graph, and the node in it,
+    /// under the provided screen position, if any
+    pub fn overlay_graph_node_at(
+        &self,
+        point: Point,
+    ) -> Option<(&str, NodeId)> {
+        let id = self.read_node_id_at(point)?;
+
+        match self.resolve_node_id(id)? {
+            (Some(ix), node) => Some((&self.overlay_graphs[ix].name, node)),
+            (None, _) => None,
+        }
+    }
+
+    /// Adds a graph to draw on top of the main graph, in a single
+    /// color, returning its index
+    pub fn add_overlay_graph(
+        &mut self,
+        app: &GfaestusVk,
+        name: &str,
+        vertices: &[Vertex],
+        node_count: usize,
+        color: rgb::RGB<f32>,
+    ) -> Result<usize> {
+        let renderer_type = app.vk_context().renderer_config.nodes;
+
+        let mut graph_vertices = NodeVertices::new(renderer_type);
+        graph_vertices.upload_vertices(app, vertices)?;
+
+        let id_offset = self
+            .overlay_graphs
+            .last()
+            .map(|g| g.id_offset as usize + g.node_count)
+            .unwrap_or(self.node_count) as u32;
+
+        let ix = self.overlay_graphs.len();
+
+        self.overlay_graphs.push(OverlayGraph {
+            name: name.to_string(),
+            vertices: graph_vertices,
+            node_count,
+            id_offset,
+
+            offset: Point::ZERO,
+            color,
+            visible: true,
+        });
+
+        Ok(ix)
+    }
+
+    pub fn overlay_graphs(&self) -> &[OverlayGraph] {
+        &self.overlay_graphs
+    }
+
+    pub fn overlay_graphs_mut(&mut self) -> &mut [OverlayGraph] {
+        &mut self.overlay_graphs
+    }
+
+    /// Frees the vertex buffers of all overlaid graphs; the GPU must
+    /// be idle
+    pub fn destroy_overlay_graphs(&mut self, app: &GfaestusVk) {
+        for graph in self.overlay_graphs.iter_mut() {
+            graph.destroy(app);
+        }
+        self.overlay_graphs.clear();
+    }
+
+    /// Returns the node under the provided screen position, using
+    /// either the node ID buffer or the quad tree, depending on the
+    /// `quad_tree_hover` setting
+    pub fn hover_node_at(&self, point: Point) -> Option<NodeId> {
+        if self.settings.quad_tree_hover().load() {
+            let view = self.shared_state.view();
+            let screen_dims = self.shared_state.screen_dims();
+
+            let world = view.screen_point_to_world(screen_dims, point);
+
+            // node widths are in pixels
+            let half_width = 0.5 * self.node_width(view) * view.scale;
+
+            self.node_tree.read().node_at(world, half_width)
+        } else {
+            self.main_graph_node_at(point)
+        }
+    }
+
+    /// Rebuilds the quad tree used for box selection and hover
+    /// detection; must be called whenever the node positions change
+    pub fn rebuild_node_tree(&self, nodes: &[Node]) {
+        *self.node_tree.write() = NodeTree::new(nodes);
+    }
+
+    /// Writes the part of the graph visible in the current view to
+    /// `path` as SVG, with the nodes colored by the active overlay.
+    ///
+    /// Returns the number of nodes written.
+    pub fn export_svg(
+        &self,
+        path: &str,
+        graph: &PackedGraph,
+        nodes: &[Node],
+    ) -> Result<usize> {
+        let view = self.shared_state.view();
+
+        let overlay_state = &self.shared_state.overlay_state;
+
+        let node_colors = overlay_state
+            .current_overlay()
+            .and_then(|id| self.node_draw_system.pipelines.overlay(id))
+            .map(|overlay| {
+                let gradient = overlay_state.gradient().gradient();
+                overlay.node_colors(nodes.len(), gradient)
+            })
+            .unwrap_or_default();
+
+        let background = match self.settings.clear_color().load() {
+            Some(color) => color.rgb(),
+            None => self.background_color(),
+        };
+
+        let edges = self.settings.edge_renderer().load();
+
+        let scene = svg::SvgScene {
+            view,
+            dims: self.shared_state.screen_dims(),
+
+            nodes,
+            node_colors: &node_colors,
+            node_width: self.node_width(view),
+            round_caps: self.settings.node_caps().load() == NodeCaps::Round,
+            palette: self.settings.palette().load(),
+
+            edge_color: edges.edge_color,
+            edge_width: edges.edge_width,
+
+            background,
+        };
+
+        let file = std::fs::File::create(path)?;
+        scene.write(graph, std::io::BufWriter::new(file))
+    }
+
+    /// The light or dark background color, depending on the theme
+    fn background_color(&self) -> rgb::RGB<f32> {
+        if self.shared_state.dark_mode.load() {
+            self.settings.background_color_dark().load()
+        } else {
+            self.settings.background_color_light().load()
+        }
+    }
+
+    /// The on-screen width of the nodes at the given view, in pixels
+    fn node_width(&self, view: View) -> f32 {
+        let min = self.node_width.min_node_width();
+        let max = self.node_width.max_node_width();
+
+        let min_scale = self.node_width.min_node_scale();
+        let max_scale = self.node_width.max_node_scale();
+
+        let norm_scale = (view.scale - min_scale) / (max_scale - min_scale);
+
+        let easing_val =
+            EasingExpoOut::value_at_normalized_time(norm_scale as f64) as f32;
+
+        let mut width = min + easing_val * (max - min);
+
+        if view.scale > max_scale {
+            width *= view.scale / (min_scale - max_scale);
+        } else if view.scale < min_scale {
+            width = min
+        }
+        width
+    }
+
+    /// The sizes of the main view's GPU buffers
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let pipelines = &self.node_draw_system.pipelines;
+
+        MemoryUsage {
+            vertex_buffer: self.node_draw_system.vertices.buffer_size(),
+            overlay_buffers: pipelines.overlay_buffer_size(),
+            overlay_count: pipelines.overlay_names().len(),
+            node_id_buffer: self.node_id_buffer.size(),
+            node_id_dims: (
+                self.node_id_buffer.width,
+                self.node_id_buffer.height,
+            ),
+        }
+    }
+
+    /// Draws the box selection rectangle, if the user is currently
+    /// dragging one
+    pub fn draw_box_select(&self, ctx: &egui::CtxRef) {
+        if let Some(rect) = self.view_input_state.box_select_rect() {
+            let view = self.shared_state.view();
+            crate::gui::text::draw_rect_world(ctx, view, rect, None);
+        }
+    }
+
+    /// Labels each node in the view with its ID, if enabled and the
+    /// view is zoomed in past the `node_id_zoom` threshold; labels
+    /// that would overlap are hidden when the layout is drawn,
+    /// preferring to keep those of selected and hovered nodes
+    pub fn add_node_id_labels(
+        &self,
+        ctx: &egui::CtxRef,
+        layout: &mut LabelLayout,
+    ) {
+        let view = self.shared_state.view();
+
+        if !self.settings.show_node_ids().load()
+            || view.scale >= self.settings.node_id_zoom().load()
+        {
+            return;
+        }
+
+        let screen_dims = self.shared_state.screen_dims();
+
+        let visible = Rect::new(
+            view.screen_point_to_world(screen_dims, Point::ZERO),
+            view.screen_point_to_world(
+                screen_dims,
+                Point::new(screen_dims.width, screen_dims.height),
+            ),
+        );
+
+        let tree = self.node_tree.read();
+
+        let node_ids = tree
+            .tree
+            .query_range(visible)
+            .into_iter()
+            .map(|(_, &node_id)| node_id)
+            .collect::<FxHashSet<_>>();
+
+        let hover_node = self.shared_state.hover_node();
+        let selection = self.selection_buffer.selection_set();
+
+        for node_id in node_ids {
+            let node_ix = (node_id.0 - 1) as usize;
+
+            let priority = if selection.contains(&node_id) {
+                LabelPriority::Selection
+            } else if hover_node == Some(node_id) {
+                LabelPriority::Hover
+            } else {
+                LabelPriority::Annotation
+            };
+
+            if let Some(node) = tree.nodes.get(node_ix) {
+                layout.add_at_world_point(
+                    ctx,
+                    view,
+                    node.center(),
+                    Point::new(0.0, -8.0),
+                    &node_id.0.to_string(),
+                    priority,
+                );
+            }
+        }
     }
 
     pub fn draw_nodes(
@@ -211,37 +623,26 @@ impl MainView {
         screen_dims: [f32; 2],
         offset: Point,
         overlay_id: Option<usize>,
+        base_overlay_id: Option<usize>,
         color_scheme: &GradientTexture,
     ) -> Result<()> {
         let view = self.shared_state.view();
 
-        let node_width = {
-            let min = self.node_width.min_node_width();
-            let max = self.node_width.max_node_width();
+        let node_width = self.node_width(view);
 
-            let min_scale = self.node_width.min_node_scale();
-            let max_scale = self.node_width.max_node_scale();
+        let clear_color = self.settings.clear_color().load();
 
-            let norm_scale = (view.scale - min_scale) / (max_scale - min_scale);
+        let background_color =
+            clear_color.unwrap_or_else(|| self.background_color().alpha(1.0));
 
-            let easing_val =
-                EasingExpoOut::value_at_normalized_time(norm_scale as f64)
-                    as f32;
-
-            let mut width = min + easing_val * (max - min);
-
-            if view.scale > max_scale {
-                width *= view.scale / (min_scale - max_scale);
-            } else if view.scale < min_scale {
-                width = min
-            }
-            width
-        };
-
-        let background_color = if self.shared_state.dark_mode.load() {
-            self.settings.background_color_dark().load()
+        let background_gradient = if clear_color.is_none()
+            && self.settings.background_gradient().load()
+        {
+            let top = self.settings.background_color_light().load();
+            let bottom = self.settings.background_color_dark().load();
+            Some((top, bottom))
         } else {
-            self.settings.background_color_light().load()
+            None
         };
 
         if let Some(overlay_id) = overlay_id {
@@ -254,8 +655,17 @@ impl MainView {
                 view,
                 offset,
                 background_color,
+                background_gradient,
+                self.settings.palette().load(),
+                self.settings.node_outline_width().load(),
+                self.settings.node_outline_color().load(),
+                self.settings.node_caps().load(),
+                self.settings.lod_min_pixels().load(),
+                self.settings.path_ribbons().load(),
                 overlay_id,
+                base_overlay_id,
                 color_scheme,
+                &self.overlay_graphs,
             )?;
 
             Ok(())
@@ -286,15 +696,11 @@ impl MainView {
     }
 
     pub fn set_view_center(&self, center: Point) {
-        let mut view = self.shared_state.view();
-        view.center = center;
-        self.shared_state.set_view(view);
+        self.send_view_update(ViewUpdate::SetCenter(center));
     }
 
     pub fn set_view_scale(&self, scale: f32) {
-        let mut view = self.shared_state.view();
-        view.scale = scale;
-        self.shared_state.set_view(view);
+        self.send_view_update(ViewUpdate::SetScale(scale));
     }
 
     pub fn update_view_animation<D: Into<ScreenDims>>(
@@ -302,12 +708,16 @@ impl MainView {
         screen_dims: D,
         mouse_pos: Point,
     ) {
+        self.apply_view_updates();
+
         let screen_dims = screen_dims.into();
         let view = self.shared_state.view();
 
         let mouse_screen = mouse_pos;
         let mouse_world = view.screen_point_to_world(screen_dims, mouse_screen);
 
+        self.view_input_state.update_box_select(mouse_world);
+
         if let Some(anim_def) = self.view_input_state.animation_def(
             view,
             screen_dims,
@@ -321,9 +731,7 @@ impl MainView {
     pub fn send_context(&self, tx: &Sender<ContextEntry>) {
         let mouse_pos = self.shared_state.mouse_pos();
 
-        let hover_node = self
-            .read_node_id_at(mouse_pos)
-            .map(|nid| NodeId::from(nid as u64));
+        let hover_node = self.main_graph_node_at(mouse_pos);
 
         if let Some(node) = hover_node {
             tx.send(ContextEntry::Node(node)).unwrap();
@@ -336,6 +744,66 @@ impl MainView {
         }
     }
 
+    // manual panning takes the view back from `follow_selection`
+    fn stop_following_selection(&self) {
+        self.shared_state.follow_selection.store(false);
+    }
+
+    // records a click at the given screen position, and returns true
+    // if it completes a double-click
+    fn is_double_click(&self, pos: Point) -> bool {
+        let now = std::time::Instant::now();
+        let last = self.last_click.swap(Some((now, pos)));
+
+        let threshold = self.settings.double_click_ms().load();
+
+        if threshold == 0 {
+            return false;
+        }
+
+        match last {
+            Some((time, last_pos))
+                if now.duration_since(time).as_millis()
+                    <= threshold as u128
+                    && last_pos.dist(pos) < 5.0 =>
+            {
+                // so that a third click starts a new double-click
+                self.last_click.store(None);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Selects the node and animates the view to center on it,
+    /// keeping the current scale
+    fn select_and_center_node(&self, node: NodeId) {
+        use crate::app::{AppMsg, Select};
+
+        let center = {
+            let tree = self.node_tree.read();
+            let ix = (node.0 as usize).checked_sub(1);
+            ix.and_then(|ix| tree.nodes.get(ix)).map(|n| {
+                Point::new((n.p0.x + n.p1.x) / 2.0, (n.p0.y + n.p1.y) / 2.0)
+            })
+        };
+
+        self.channels
+            .app_tx
+            .send(AppMsg::Selection(Select::One { node, clear: true }))
+            .unwrap();
+
+        if let Some(center) = center {
+            self.stop_following_selection();
+
+            let view = View {
+                center,
+                ..self.shared_state.view()
+            };
+            self.apply_msg(MainViewMsg::GotoView(view));
+        }
+    }
+
     pub fn apply_input<Dims: Into<ScreenDims>>(
         &self,
         screen_dims: Dims,
@@ -351,15 +819,27 @@ impl MainView {
 
                 match payload {
                     In::KeyPanUp => {
+                        if pressed {
+                            self.stop_following_selection();
+                        }
                         self.view_input_state.key_pan.set_up(pressed);
                     }
                     In::KeyPanRight => {
+                        if pressed {
+                            self.stop_following_selection();
+                        }
                         self.view_input_state.key_pan.set_right(pressed);
                     }
                     In::KeyPanDown => {
+                        if pressed {
+                            self.stop_following_selection();
+                        }
                         self.view_input_state.key_pan.set_down(pressed);
                     }
                     In::KeyPanLeft => {
+                        if pressed {
+                            self.stop_following_selection();
+                        }
                         self.view_input_state.key_pan.set_left(pressed);
                     }
                     In::KeyResetView => {
@@ -374,6 +854,13 @@ impl MainView {
                 let pressed = state.pressed();
                 match payload {
                     In::ButtonMousePan => {
+                        if pressed && self.is_double_click(pos) {
+                            if let Some(node) = self.shared_state.hover_node() {
+                                self.select_and_center_node(node);
+                                return;
+                            }
+                        }
+
                         if pressed {
                             if self.shared_state.hover_node().is_some() {
                                 let view = self.shared_state.view();
@@ -391,6 +878,7 @@ impl MainView {
                                     mouse_pos,
                                 );
 
+                                self.stop_following_selection();
                                 self.view_input_state
                                     .start_click_and_drag_pan(mouse_world);
                             }
@@ -420,18 +908,55 @@ impl MainView {
                         use crate::app::AppMsg;
                         use crate::app::Select;
 
-                        let selected_node = self
-                            .read_node_id_at(pos)
-                            .map(|nid| NodeId::from(nid as u64));
+                        let view = self.shared_state.view();
+                        let mouse_world =
+                            view.screen_point_to_world(screen_dims, pos);
+
+                        if pressed {
+                            self.view_input_state.start_box_select(mouse_world);
+                            return;
+                        }
 
-                        if let Some(node) = selected_node {
+                        let rect = self
+                            .view_input_state
+                            .end_box_select(mouse_world)
+                            .filter(|rect| {
+                                // treat tiny drags as clicks
+                                let screen_w = rect.width() / view.scale;
+                                let screen_h = rect.height() / view.scale;
+                                screen_w > 4.0 || screen_h > 4.0
+                            });
+
+                        if let Some(rect) = rect {
+                            // selected on the GPU, the same as with
+                            // `ButtonRectangleSelect`
                             self.channels
                                 .app_tx
-                                .send(AppMsg::Selection(Select::One {
-                                    node,
-                                    clear: false,
-                                }))
+                                .send(AppMsg::RectSelect(rect))
                                 .unwrap();
+                        } else {
+                            if let Some((name, node)) =
+                                self.overlay_graph_node_at(pos)
+                            {
+                                log::info!(
+                                    "node {} of overlaid graph {} \
+                                     can't be selected",
+                                    node.0,
+                                    name
+                                );
+                            }
+
+                            let selected_node = self.main_graph_node_at(pos);
+
+                            if let Some(node) = selected_node {
+                                self.channels
+                                    .app_tx
+                                    .send(AppMsg::Selection(Select::One {
+                                        node,
+                                        clear: false,
+                                    }))
+                                    .unwrap();
+                            }
                         }
                     }
 
@@ -458,10 +983,14 @@ impl MainView {
             }
             SystemInput::Wheel { delta, .. } => {
                 if let In::WheelZoom = payload {
+                    let min_scale = self.settings.min_scale().load();
+                    let max_scale = self.settings.max_scale().load();
+
                     self.view_input_state.scroll_zoom(
                         self.shared_state.view(),
                         mouse_pos,
-                        delta,
+                        delta * self.settings.zoom_speed().load(),
+                        (min_scale, max_scale.max(min_scale)),
                     );
                 }
             }
@@ -469,6 +998,103 @@ impl MainView {
     }
 }
 
+/// CPU-side copy of the node positions, with a quad tree over points
+/// sampled along each node, so that long nodes can be found from any
+/// part of them, not just their endpoints
+struct NodeTree {
+    tree: QuadTree<NodeId>,
+    nodes: Vec<Node>,
+
+    // the maximum distance between consecutive points of a node in
+    // the tree
+    sample_spacing: f32,
+}
+
+impl std::default::Default for NodeTree {
+    fn default() -> Self {
+        Self {
+            tree: QuadTree::new(Rect::nowhere()),
+            nodes: Vec::new(),
+            sample_spacing: 0.0,
+        }
+    }
+}
+
+impl NodeTree {
+    fn new(nodes: &[Node]) -> Self {
+        let boundary = nodes.iter().fold(Rect::nowhere(), |rect, node| {
+            rect.union(Rect::new(node.p0, node.p1))
+        });
+
+        // pad the boundary so the extreme points are inside
+        let mut tree = QuadTree::new(boundary.resize(1.01));
+
+        let total_len: f32 =
+            nodes.iter().map(|node| node.p0.dist(node.p1)).sum();
+
+        // with the mean node length as the spacing, most nodes are
+        // only represented by their endpoints, while the few long
+        // ones get points along their length
+        let sample_spacing = if nodes.is_empty() {
+            0.0
+        } else {
+            (total_len / nodes.len() as f32).max(1.0)
+        };
+
+        for (ix, node) in nodes.iter().enumerate() {
+            let node_id = NodeId::from((ix + 1) as u64);
+
+            let len = node.p0.dist(node.p1);
+            let segments = (len / sample_spacing).ceil().max(1.0) as usize;
+
+            for i in 0..=segments {
+                let t = i as f32 / segments as f32;
+                let point = node.p0 + (node.p1 - node.p0) * t;
+
+                if tree.insert(point, node_id).is_err() {
+                    log::warn!("could not insert node {} into quad tree", ix);
+                }
+            }
+        }
+
+        Self {
+            tree,
+            nodes: nodes.to_vec(),
+            sample_spacing,
+        }
+    }
+
+    /// Finds the node closest to `point`, if its distance is within
+    /// `half_width`
+    fn node_at(&self, point: Point, half_width: f32) -> Option<NodeId> {
+        // every node that could contain the point has at least one
+        // sampled point within this range, no matter its length
+        let pad = self.sample_spacing / 2.0 + half_width;
+        let range = Rect::new(
+            point - Point::new(pad, pad),
+            point + Point::new(pad, pad),
+        );
+
+        let mut closest: Option<(NodeId, f32)> = None;
+
+        for (_, &node_id) in self.tree.query_range(range) {
+            let node = self.nodes[(node_id.0 - 1) as usize];
+
+            let dist = node.distance_to(point);
+
+            if dist > half_width {
+                continue;
+            }
+
+            if closest.map(|(_, d)| dist < d).unwrap_or(true) {
+                closest = Some((node_id, dist));
+            }
+        }
+
+        closest.map(|(node_id, _)| node_id)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MainViewInput {
     ButtonMousePan,
@@ -482,8 +1108,13 @@ pub enum MainViewInput {
     WheelZoom,
 }
 
-impl BindableInput for MainViewInput {
-    fn default_binds() -> SystemInputBindings<Self> {
+impl MainViewInput {
+    /// The default bindings, but with panning and selection on the
+    /// given mouse buttons
+    pub fn binds_with_buttons(
+        pan_button: winit::event::MouseButton,
+        select_button: winit::event::MouseButton,
+    ) -> SystemInputBindings<Self> {
         use winit::event;
         use winit::event::VirtualKeyCode as Key;
         use MainViewInput as Input;
@@ -502,31 +1133,39 @@ impl BindableInput for MainViewInput {
 
         let shift_mod = winit::event::ModifiersState::SHIFT;
 
-        let mouse_binds: FxHashMap<
+        let mut mouse_binds: FxHashMap<
             event::MouseButton,
             Vec<MouseButtonBind<Input>>,
-        > = [
-            (
-                event::MouseButton::Left,
-                vec![
-                    MouseButtonBind::new(Input::ButtonMousePan),
-                    MouseButtonBind::with_modifiers(
-                        Input::ButtonRectangleSelect,
-                        shift_mod,
-                    ),
-                ],
-            ),
-            (
-                event::MouseButton::Right,
-                vec![MouseButtonBind::new(Input::ButtonSelect)],
-            ),
-        ]
-        .iter()
-        .cloned()
-        .collect();
+        > = FxHashMap::default();
+
+        mouse_binds.insert(
+            pan_button,
+            vec![
+                MouseButtonBind::new(Input::ButtonMousePan),
+                MouseButtonBind::with_modifiers(
+                    Input::ButtonRectangleSelect,
+                    shift_mod,
+                ),
+            ],
+        );
 
-        let wheel_bind = Some(WheelBind::new(true, 0.45, Input::WheelZoom));
+        // the select button may be the same as the pan button
+        mouse_binds
+            .entry(select_button)
+            .or_default()
+            .push(MouseButtonBind::new(Input::ButtonSelect));
+
+        // the zoom per wheel step is the `zoom_speed` setting
+        let wheel_bind = Some(WheelBind::new(true, 1.0, Input::WheelZoom));
 
         SystemInputBindings::new(key_binds, mouse_binds, wheel_bind)
     }
 }
+
+impl BindableInput for MainViewInput {
+    fn default_binds() -> SystemInputBindings<Self> {
+        use winit::event::MouseButton;
+
+        Self::binds_with_buttons(MouseButton::Left, MouseButton::Right)
+    }
+}
@@ -0,0 +1,186 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use handlegraph::{
+    handle::{Edge, Handle},
+    handlegraph::*,
+    packedgraph::PackedGraph,
+};
+
+use crate::geometry::*;
+use crate::overlays::Palette;
+use crate::universe::Node;
+use crate::view::{ScreenDims, View};
+
+/// Everything needed to draw the part of the graph that's visible in
+/// a view as SVG, in screen space, matching what's shown on screen
+pub struct SvgScene<'a> {
+    pub view: View,
+    pub dims: ScreenDims,
+
+    pub nodes: &'a [Node],
+    /// The color of each node, in node ID order
+    pub node_colors: &'a [rgb::RGBA<f32>],
+    /// The node width in pixels
+    pub node_width: f32,
+    pub round_caps: bool,
+    /// Applied to the node colors, as the node shaders do
+    pub palette: Palette,
+
+    pub edge_color: rgb::RGB<f32>,
+    pub edge_width: f32,
+
+    pub background: rgb::RGB<f32>,
+}
+
+impl<'a> SvgScene<'a> {
+    fn to_screen(&self, world: Point) -> Point {
+        let offset = world - self.view.center;
+
+        Point {
+            x: offset.x / self.view.scale + self.dims.width * 0.5,
+            y: offset.y / self.view.scale + self.dims.height * 0.5,
+        }
+    }
+
+    // the screen rectangle, padded by the node width
+    fn screen_rect(&self) -> Rect {
+        let pad = self.node_width;
+
+        Rect::new(
+            Point::new(-pad, -pad),
+            Point::new(self.dims.width + pad, self.dims.height + pad),
+        )
+    }
+
+    // whether any part of the line between the screen points is
+    // visible, even if both ends are outside the screen
+    fn is_visible(&self, p0: Point, p1: Point) -> bool {
+        Node { p0, p1 }.intersects_rect(self.screen_rect())
+    }
+
+    /// Writes the nodes and edges that cross the screen as `<line>`
+    /// elements; edges are drawn as straight lines, below the nodes.
+    ///
+    /// Returns the number of nodes written.
+    pub fn write<W: Write>(
+        &self,
+        graph: &PackedGraph,
+        mut out: W,
+    ) -> Result<usize> {
+        let screen_nodes = self
+            .nodes
+            .iter()
+            .map(|node| (self.to_screen(node.p0), self.to_screen(node.p1)))
+            .collect::<Vec<_>>();
+
+        let visible = screen_nodes
+            .iter()
+            .map(|&(p0, p1)| self.is_visible(p0, p1))
+            .collect::<Vec<_>>();
+
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.dims.width,
+            h = self.dims.height,
+        )?;
+
+        writeln!(
+            out,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            svg_color(self.background)
+        )?;
+
+        writeln!(
+            out,
+            r#"<g stroke="{}" stroke-width="{}">"#,
+            svg_color(self.edge_color),
+            self.edge_width
+        )?;
+
+        // the screen position of the end of a handle that an edge
+        // connects to; edges leave the right side of a handle, which
+        // is the start of the node if the handle is reversed
+        let handle_end = |handle: Handle, right: bool| -> Option<Point> {
+            let ix = (handle.id().0 - 1) as usize;
+            let (p0, p1) = *screen_nodes.get(ix)?;
+            if right != handle.is_reverse() {
+                Some(p1)
+            } else {
+                Some(p0)
+            }
+        };
+
+        for Edge(left, right) in graph.edges() {
+            if let (Some(p0), Some(p1)) =
+                (handle_end(left, true), handle_end(right, false))
+            {
+                if !self.is_visible(p0, p1) {
+                    continue;
+                }
+
+                writeln!(
+                    out,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                    p0.x, p0.y, p1.x, p1.y
+                )?;
+            }
+        }
+
+        writeln!(out, "</g>")?;
+
+        let linecap = if self.round_caps { "round" } else { "butt" };
+
+        writeln!(
+            out,
+            r#"<g stroke-width="{}" stroke-linecap="{}">"#,
+            self.node_width, linecap
+        )?;
+
+        let mut node_count = 0;
+
+        for (ix, &(p0, p1)) in screen_nodes.iter().enumerate() {
+            if !visible[ix] {
+                continue;
+            }
+
+            let color = self
+                .node_colors
+                .get(ix)
+                .copied()
+                .unwrap_or(rgb::RGBA::new(0.0, 0.0, 0.0, 1.0));
+
+            writeln!(
+                out,
+                r#"<line id="node{}" x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-opacity="{}"/>"#,
+                ix + 1,
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                svg_color(self.palette.apply(color.rgb())),
+                color.a
+            )?;
+
+            node_count += 1;
+        }
+
+        writeln!(out, "</g>")?;
+        writeln!(out, "</svg>")?;
+
+        Ok(node_count)
+    }
+}
+
+fn svg_color(color: rgb::RGB<f32>) -> String {
+    let to_u8 = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        to_u8(color.r),
+        to_u8(color.g),
+        to_u8(color.b)
+    )
+}
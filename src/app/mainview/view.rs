@@ -635,6 +635,9 @@ pub struct ScrollZoomState {
     view_start: View,
     mouse_screen_pos: Point,
     scroll_delta: f32,
+
+    // the minimum and maximum view scale
+    scale_limits: (f32, f32),
 }
 
 impl ScrollZoomState {
@@ -642,11 +645,13 @@ impl ScrollZoomState {
         view: View,
         mouse_screen_pos: Point,
         scroll_delta: f32,
+        scale_limits: (f32, f32),
     ) -> Self {
         Self {
             view_start: view,
             mouse_screen_pos,
             scroll_delta,
+            scale_limits,
         }
     }
 
@@ -675,8 +680,11 @@ impl ScrollZoomState {
             1.0 + (self.scroll_delta * mult)
         };
 
-        end.scale *= scroll_delta;
+        let (min_scale, max_scale) = self.scale_limits;
+        end.scale = (end.scale * scroll_delta).max(min_scale).min(max_scale);
 
+        // shift the center by however much the world point under the
+        // cursor moved when scaling, so it stays under the cursor
         let start_mouse_world =
             start.screen_point_to_world(dims, self.mouse_screen_pos);
         let end_mouse_world =
@@ -706,6 +714,8 @@ pub struct ViewInputState {
     pub mouse_pan: Arc<AtomicCell<MousePanState>>,
 
     scroll_zoom: Arc<AtomicCell<Option<ScrollZoomState>>>,
+
+    box_select: Arc<AtomicCell<Option<BoxSelectState>>>,
 }
 
 impl std::default::Default for ViewInputState {
@@ -714,6 +724,7 @@ impl std::default::Default for ViewInputState {
             key_pan: Default::default(),
             mouse_pan: Arc::new(MousePanState::Inactive.into()),
             scroll_zoom: Arc::new(None.into()),
+            box_select: Arc::new(None.into()),
         }
     }
 }
@@ -764,17 +775,71 @@ impl ViewInputState {
         self.mouse_pan.store(MousePanState::Inactive);
     }
 
+    pub fn start_box_select(&self, world_mouse_pos: Point) {
+        let box_select = BoxSelectState {
+            world_start: world_mouse_pos,
+            world_end: world_mouse_pos,
+        };
+
+        self.box_select.store(Some(box_select));
+    }
+
+    pub fn update_box_select(&self, world_mouse_pos: Point) {
+        if let Some(mut box_select) = self.box_select.load() {
+            box_select.world_end = world_mouse_pos;
+            self.box_select.store(Some(box_select));
+        }
+    }
+
+    /// The world space rectangle of the box selection in progress,
+    /// if any
+    pub fn box_select_rect(&self) -> Option<Rect> {
+        let box_select = self.box_select.load()?;
+        Some(box_select.rect())
+    }
+
+    /// Ends the current box selection, returning its final world
+    /// space rectangle
+    pub fn end_box_select(&self, world_mouse_pos: Point) -> Option<Rect> {
+        let mut box_select = self.box_select.swap(None)?;
+        box_select.world_end = world_mouse_pos;
+        Some(box_select.rect())
+    }
+
+    /// Queues a zoom that keeps the world point under the cursor
+    /// fixed. Wheel events received before the zoom is applied are
+    /// combined, and anchored to the view and cursor position of the
+    /// first one, so that fast scrolling doesn't drop events or drift
+    /// away from the cursor. The resulting scale is clamped to
+    /// `scale_limits`, the minimum and maximum scale.
     pub fn scroll_zoom(
         &self,
         view: View,
         cur_mouse_screen: Point,
         scroll_delta: f32,
+        scale_limits: (f32, f32),
     ) {
-        let scroll_zoom = ScrollZoomState::zoom_to_cursor(
-            view,
-            cur_mouse_screen,
-            scroll_delta,
-        );
+        let scroll_zoom = match self.scroll_zoom.load() {
+            Some(pending) => pending.add_scroll_delta(scroll_delta),
+            None => ScrollZoomState::zoom_to_cursor(
+                view,
+                cur_mouse_screen,
+                scroll_delta,
+                scale_limits,
+            ),
+        };
         self.scroll_zoom.store(Some(scroll_zoom));
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxSelectState {
+    pub world_start: Point,
+    pub world_end: Point,
+}
+
+impl BoxSelectState {
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.world_start, self.world_end)
+    }
+}
@@ -0,0 +1,262 @@
+use handlegraph::handle::{Handle, NodeId};
+use handlegraph::handlegraph::*;
+use handlegraph::pathhandlegraph::PathId;
+
+use crate::geometry::*;
+use crate::graph_query::GraphQuery;
+use crate::gui::text::{draw_line_world, draw_text_at_world_point_offset};
+use crate::gui::widgets::round_bp_length;
+use crate::universe::Node;
+use crate::view::{ScreenDims, View};
+
+/// Measures the distance between two clicked points, in world units,
+/// and in base pairs if both points are on nodes that share a path.
+///
+/// Clicking a third time starts a new measurement.
+#[derive(Debug, Default, Clone)]
+pub struct Ruler {
+    start: Option<RulerPoint>,
+    end: Option<RulerPoint>,
+
+    // the base pair distance between the nodes at the ends of the
+    // ruler, computed when the ruler is completed
+    bp_distance: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RulerPoint {
+    world: Point,
+    node: Option<NodeId>,
+}
+
+impl Ruler {
+    /// Adds a point to the ruler, or starts a new measurement if the
+    /// ruler already has both ends
+    pub fn click(
+        &mut self,
+        graph_query: &GraphQuery,
+        world: Point,
+        node: Option<NodeId>,
+    ) {
+        let point = RulerPoint { world, node };
+
+        match (self.start, self.end) {
+            (Some(start), None) => {
+                self.end = Some(point);
+                self.bp_distance = match (start.node, node) {
+                    (Some(a), Some(b)) => path_bp_distance(graph_query, a, b),
+                    _ => None,
+                };
+            }
+            _ => {
+                *self = Self::default();
+                self.start = Some(point);
+            }
+        }
+    }
+
+    /// Draws the ruler and its distance label; until the second point
+    /// is placed, the ruler ends at the cursor
+    pub fn draw(&self, ctx: &egui::CtxRef, view: View, mouse_world: Point) {
+        let start = match self.start {
+            Some(start) => start.world,
+            None => return,
+        };
+
+        let end = self.end.map(|end| end.world).unwrap_or(mouse_world);
+
+        draw_line_world(ctx, view, start, end, None);
+
+        let mut label = format!("{:.1} units", start.dist(end));
+
+        if let Some(bp) = self.bp_distance {
+            label.push_str(&format!(", {} bp", bp));
+        }
+
+        let mid = Point::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+
+        draw_text_at_world_point_offset(
+            ctx,
+            view,
+            mid,
+            Point::new(0.0, -12.0),
+            &label,
+        );
+    }
+}
+
+/// The smallest distance in base pairs between the starts of the two
+/// nodes, along any path that steps on both of them
+fn path_bp_distance(
+    graph_query: &GraphQuery,
+    a: NodeId,
+    b: NodeId,
+) -> Option<usize> {
+    let a_positions = graph_query.handle_positions(Handle::pack(a, false))?;
+    let b_positions = graph_query.handle_positions(Handle::pack(b, false))?;
+
+    let mut closest: Option<usize> = None;
+
+    for &(a_path, _, a_pos) in a_positions.iter() {
+        for &(b_path, _, b_pos) in b_positions.iter() {
+            if a_path != b_path {
+                continue;
+            }
+
+            let dist = a_pos.max(b_pos) - a_pos.min(b_pos);
+            closest = Some(closest.map_or(dist, |c| c.min(dist)));
+        }
+    }
+
+    closest
+}
+
+/// Tick marks with base pair labels along a path's layout, like a
+/// genome browser ruler that follows the path's geometry.
+///
+/// The tick spacing is picked from the view scale when drawing, so
+/// the ticks stay about the same distance apart on screen.
+#[derive(Debug, Clone)]
+pub struct PathRuler {
+    steps: Vec<RulerStep>,
+
+    // the average number of base pairs per world unit along the path
+    bp_per_unit: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RulerStep {
+    offset: usize,
+    len: usize,
+
+    // the layout positions of the first and last base of the step,
+    // taking the step's orientation into account
+    start: Point,
+    end: Point,
+}
+
+impl PathRuler {
+    /// Ticks are at most this far apart, in pixels
+    const MAX_TICK_SPACING: f32 = 150.0;
+
+    pub fn new(
+        graph_query: &GraphQuery,
+        node_positions: &[Node],
+        path: PathId,
+    ) -> Option<Self> {
+        let pos_steps = graph_query.path_pos_steps(path)?;
+
+        let mut steps = Vec::with_capacity(pos_steps.len());
+
+        let mut path_len = 0;
+        let mut world_len = 0.0;
+
+        for (handle, _, offset) in pos_steps {
+            let node = node_positions.get((handle.id().0 - 1) as usize)?;
+            let len = graph_query.graph().node_len(handle);
+
+            let (start, end) = if handle.is_reverse() {
+                (node.p1, node.p0)
+            } else {
+                (node.p0, node.p1)
+            };
+
+            path_len = path_len.max(offset + len);
+            world_len += start.dist(end);
+
+            steps.push(RulerStep {
+                offset,
+                len,
+                start,
+                end,
+            });
+        }
+
+        if world_len <= 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            steps,
+            bp_per_unit: path_len as f32 / world_len,
+        })
+    }
+
+    /// Draws the ticks and labels on the steps that are in view
+    pub fn draw(
+        &self,
+        ctx: &egui::CtxRef,
+        view: View,
+        screen_dims: ScreenDims,
+    ) {
+        let visible = Rect::new(
+            view.screen_point_to_world(screen_dims, Point::ZERO),
+            view.screen_point_to_world(
+                screen_dims,
+                Point::new(screen_dims.width, screen_dims.height),
+            ),
+        );
+
+        let bp_per_px = self.bp_per_unit * view.scale;
+        if !bp_per_px.is_finite() || bp_per_px <= 0.0 {
+            return;
+        }
+
+        let spacing =
+            round_bp_length(bp_per_px * Self::MAX_TICK_SPACING) as usize;
+
+        // half the tick length, in world units
+        let tick_len = 6.0 * view.scale;
+
+        for step in self.steps.iter() {
+            if step.len == 0
+                || !Rect::new(step.start, step.end).intersects(visible)
+            {
+                continue;
+            }
+
+            let dir = step.end - step.start;
+            let normal = if dir.length() > 0.0 {
+                Point::new(-dir.y, dir.x) * (tick_len / dir.length())
+            } else {
+                Point::new(0.0, tick_len)
+            };
+
+            // the first tick at or after the start of the step
+            let mut tick = ((step.offset + spacing - 1) / spacing) * spacing;
+
+            while tick < step.offset + step.len {
+                let t = (tick - step.offset) as f32 / step.len as f32;
+                let point = step.start + dir * t;
+
+                draw_line_world(
+                    ctx,
+                    view,
+                    point - normal,
+                    point + normal,
+                    None,
+                );
+                draw_text_at_world_point_offset(
+                    ctx,
+                    view,
+                    point,
+                    Point::new(0.0, -12.0),
+                    &bp_label(tick, spacing),
+                );
+
+                tick += spacing;
+            }
+        }
+    }
+}
+
+/// Formats a base pair offset in the unit that fits the tick spacing
+fn bp_label(bp: usize, spacing: usize) -> String {
+    if spacing >= 1_000_000 {
+        format!("{} Mbp", bp as f64 / 1_000_000.0)
+    } else if spacing >= 1_000 {
+        format!("{} kbp", bp as f64 / 1_000.0)
+    } else {
+        format!("{} bp", bp)
+    }
+}
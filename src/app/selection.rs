@@ -1,11 +1,13 @@
-use handlegraph::handle::NodeId;
+use handlegraph::{
+    handle::NodeId, packedgraph::PackedGraph, pathhandlegraph::*,
+};
 
 use rustc_hash::FxHashSet;
 
 use ash::version::DeviceV1_0;
 use ash::{vk, Device};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 use crate::geometry::Rect;
 use crate::universe::Node;
@@ -36,6 +38,21 @@ impl NodeSelection {
         Self { nodes }
     }
 
+    /// The nodes of the selection that are on the given path
+    pub fn on_path(&self, graph: &PackedGraph, path: PathId) -> NodeSelection {
+        let path_nodes = graph
+            .path_steps(path)
+            .map(|steps| {
+                steps
+                    .map(|step| step.handle().id())
+                    .collect::<FxHashSet<_>>()
+            })
+            .unwrap_or_default();
+
+        let nodes = self.nodes.intersection(&path_nodes).copied().collect();
+        Self { nodes }
+    }
+
     pub fn add_one(&mut self, clear: bool, node: NodeId) {
         if clear {
             self.nodes.clear();
@@ -194,11 +211,27 @@ impl SelectionBuffer {
         Ok(())
     }
 
+    // the index of the node in the selection buffer, or an error if
+    // the node is not in the buffer, e.g. if it refers to a node in a
+    // different graph
+    fn node_index(&self, node: NodeId) -> Result<usize> {
+        let node_count = (self.size / 4) as usize;
+        let ix = (node.0 as usize).wrapping_sub(1);
+
+        if ix >= node_count {
+            bail!("node {} does not exist in the selection buffer", node.0);
+        }
+
+        Ok(ix)
+    }
+
     pub fn add_select_one(
         &mut self,
         device: &Device,
         node: NodeId,
     ) -> Result<()> {
+        let ix = self.node_index(node)?;
+
         if self.latest_selection.insert(node) {
             unsafe {
                 let data_ptr = device.map_memory(
@@ -209,11 +242,6 @@ impl SelectionBuffer {
                 )?;
 
                 let val_ptr = data_ptr as *mut u32;
-                let ix = (node.0 - 1) as usize;
-
-                if ix >= (self.size / 4) as usize {
-                    panic!("attempted to select a node that does not exist");
-                }
 
                 let val_ptr = val_ptr.add(ix);
                 // let val_ptr = val_ptr.add(2);
@@ -263,6 +291,13 @@ impl SelectionBuffer {
         let removed = self.latest_selection.difference(new_selection);
         let added = new_selection.difference(&self.latest_selection);
 
+        let removed = removed
+            .map(|&node| self.node_index(node))
+            .collect::<Result<Vec<_>>>()?;
+        let added = added
+            .map(|&node| self.node_index(node))
+            .collect::<Result<Vec<_>>>()?;
+
         unsafe {
             let data_ptr = device.map_memory(
                 self.memory,
@@ -271,26 +306,14 @@ impl SelectionBuffer {
                 vk::MemoryMapFlags::empty(),
             )?;
 
-            for &node in removed {
+            for ix in removed {
                 let val_ptr = data_ptr as *mut u32;
-                let ix = (node.0 - 1) as usize;
-
-                if ix >= (self.size / 4) as usize {
-                    panic!("attempted to deselect a node that does not exist");
-                }
-
                 let val_ptr = val_ptr.add(ix);
                 val_ptr.write(0);
             }
 
-            for &node in added {
+            for ix in added {
                 let val_ptr = data_ptr as *mut u32;
-                let ix = (node.0 - 1) as usize;
-
-                if ix >= (self.size / 4) as usize {
-                    panic!("attempted to select a node that does not exist");
-                }
-
                 let val_ptr = val_ptr.add(ix);
                 val_ptr.write(1);
             }
@@ -1,7 +1,15 @@
 use crossbeam::atomic::AtomicCell;
-use std::sync::Arc;
+use parking_lot::Mutex;
+use std::{path::PathBuf, sync::Arc};
+use winit::event::MouseButton;
 
+use crate::gui::widgets::Corner;
+use crate::overlays::{Palette, PathColors};
 use crate::vulkan::draw_system::edges::EdgesUBO;
+use crate::vulkan::draw_system::nodes::NodeCaps;
+use crate::vulkan::draw_system::post::ColorAdjustment;
+use crate::vulkan::draw_system::selection::SelectionStyle;
+use crate::vulkan::PresentMode;
 
 #[derive(Debug, Clone)]
 pub struct AppSettings {
@@ -13,10 +21,67 @@ pub struct AppSettings {
 
     background_color_light: Arc<AtomicCell<rgb::RGB<f32>>>,
     background_color_dark: Arc<AtomicCell<rgb::RGB<f32>>>,
+
+    background_gradient: Arc<AtomicCell<bool>>,
+
+    clear_color: Arc<AtomicCell<Option<rgb::RGBA<f32>>>>,
+
+    console_lines: Arc<AtomicCell<usize>>,
+
+    quad_tree_hover: Arc<AtomicCell<bool>>,
+
+    hover_kernel_size: Arc<AtomicCell<u32>>,
+
+    msaa_samples: Arc<AtomicCell<u32>>,
+
+    present_mode: Arc<AtomicCell<PresentMode>>,
+
+    palette: Arc<AtomicCell<Palette>>,
+
+    stats_anchor: Arc<AtomicCell<Corner>>,
+    fps_anchor: Arc<AtomicCell<Corner>>,
+    scale_bar_anchor: Arc<AtomicCell<Corner>>,
+
+    color_adjustment: Arc<AtomicCell<ColorAdjustment>>,
+
+    node_outline_width: Arc<AtomicCell<f32>>,
+    node_outline_color: Arc<AtomicCell<rgb::RGB<f32>>>,
+
+    node_caps: Arc<AtomicCell<NodeCaps>>,
+
+    selection_style: Arc<AtomicCell<SelectionStyle>>,
+    selection_outline_color: Arc<AtomicCell<rgb::RGB<f32>>>,
+
+    hover_detail: Arc<AtomicCell<bool>>,
+
+    lod_min_pixels: Arc<AtomicCell<f32>>,
+
+    double_click_ms: Arc<AtomicCell<u32>>,
+
+    show_node_ids: Arc<AtomicCell<bool>>,
+    node_id_zoom: Arc<AtomicCell<f32>>,
+
+    path_ribbons: Arc<AtomicCell<bool>>,
+
+    pan_button: Arc<AtomicCell<MouseButton>>,
+    select_button: Arc<AtomicCell<MouseButton>>,
+
+    zoom_speed: Arc<AtomicCell<f32>>,
+    min_scale: Arc<AtomicCell<f32>>,
+    max_scale: Arc<AtomicCell<f32>>,
+
+    path_colors: PathColors,
+
+    scripts_dir: Arc<Mutex<Option<PathBuf>>>,
+
+    overlay_cache_dir: Arc<Mutex<Option<PathBuf>>>,
 }
 
 impl std::default::Default for AppSettings {
     fn default() -> Self {
+        // shared with the path colors, which are remapped with it
+        let palette = Arc::new(AtomicCell::new(Palette::default()));
+
         Self {
             node_width: Default::default(),
             edge_renderer: Default::default(),
@@ -28,6 +93,62 @@ impl std::default::Default for AppSettings {
             background_color_dark: Arc::new(
                 rgb::RGB::new(0.1, 0.1, 0.2).into(),
             ),
+
+            background_gradient: Arc::new(false.into()),
+
+            clear_color: Arc::new(None.into()),
+
+            console_lines: Arc::new(20.into()),
+
+            quad_tree_hover: Arc::new(false.into()),
+
+            hover_kernel_size: Arc::new(1.into()),
+
+            msaa_samples: Arc::new(0.into()),
+
+            present_mode: Arc::new(PresentMode::default().into()),
+
+            palette: palette.clone(),
+
+            stats_anchor: Arc::new(Corner::TopLeft.into()),
+            fps_anchor: Arc::new(Corner::TopRight.into()),
+            scale_bar_anchor: Arc::new(Corner::BottomLeft.into()),
+
+            color_adjustment: Arc::new(ColorAdjustment::default().into()),
+
+            node_outline_width: Arc::new(0.0.into()),
+            node_outline_color: Arc::new(rgb::RGB::new(0.0, 0.0, 0.0).into()),
+
+            node_caps: Arc::new(NodeCaps::default().into()),
+
+            selection_style: Arc::new(SelectionStyle::default().into()),
+            selection_outline_color: Arc::new(
+                rgb::RGB::new(1.0, 0.8, 0.0).into(),
+            ),
+
+            hover_detail: Arc::new(false.into()),
+
+            lod_min_pixels: Arc::new(0.0.into()),
+
+            double_click_ms: Arc::new(400.into()),
+
+            show_node_ids: Arc::new(false.into()),
+            node_id_zoom: Arc::new(5.0.into()),
+
+            path_ribbons: Arc::new(false.into()),
+
+            pan_button: Arc::new(MouseButton::Left.into()),
+            select_button: Arc::new(MouseButton::Right.into()),
+
+            zoom_speed: Arc::new(0.45.into()),
+            min_scale: Arc::new(0.001.into()),
+            max_scale: Arc::new(1_000_000.0.into()),
+
+            path_colors: PathColors::new(palette),
+
+            scripts_dir: Arc::new(Mutex::new(None)),
+
+            overlay_cache_dir: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -56,6 +177,201 @@ impl AppSettings {
     pub fn background_color_dark(&self) -> &Arc<AtomicCell<rgb::RGB<f32>>> {
         &self.background_color_dark
     }
+
+    /// If true, the main view background is a vertical gradient from
+    /// the light background color (top) to the dark one (bottom)
+    pub fn background_gradient(&self) -> &Arc<AtomicCell<bool>> {
+        &self.background_gradient
+    }
+
+    /// If set, the color the main view is cleared to, overriding the
+    /// theme's background color and gradient
+    pub fn clear_color(&self) -> &Arc<AtomicCell<Option<rgb::RGBA<f32>>>> {
+        &self.clear_color
+    }
+
+    /// The number of output lines shown in the console; updated when
+    /// the console window is resized
+    pub fn console_lines(&self) -> &Arc<AtomicCell<usize>> {
+        &self.console_lines
+    }
+
+    /// If true, the node under the cursor is found using a CPU-side
+    /// quad tree, rather than by reading back the node ID buffer
+    pub fn quad_tree_hover(&self) -> &Arc<AtomicCell<bool>> {
+        &self.quad_tree_hover
+    }
+
+    /// The width of the square of pixels around the cursor that the
+    /// node ID buffer is sampled from; the most common node in the
+    /// square is used, which reduces hover flicker on thin nodes. A
+    /// size of 1 reads only the pixel under the cursor (and its
+    /// direct neighbors, if it's empty)
+    pub fn hover_kernel_size(&self) -> &Arc<AtomicCell<u32>> {
+        &self.hover_kernel_size
+    }
+
+    /// The requested MSAA sample count; the render passes and
+    /// pipelines are rebuilt when this differs from the one in use
+    pub fn msaa_samples(&self) -> &Arc<AtomicCell<u32>> {
+        &self.msaa_samples
+    }
+
+    /// The requested swapchain present mode; the swapchain is
+    /// recreated when this changes, and falls back to FIFO if the
+    /// mode isn't supported
+    pub fn present_mode(&self) -> &Arc<AtomicCell<PresentMode>> {
+        &self.present_mode
+    }
+
+    /// The color vision deficiency palette used to remap overlay
+    /// colors, path colors, and the selection outline
+    pub fn palette(&self) -> &Arc<AtomicCell<Palette>> {
+        &self.palette
+    }
+
+    /// The screen corner the graph stats widget is pinned to
+    pub fn stats_anchor(&self) -> &Arc<AtomicCell<Corner>> {
+        &self.stats_anchor
+    }
+
+    /// The screen corner the FPS widget is pinned to
+    pub fn fps_anchor(&self) -> &Arc<AtomicCell<Corner>> {
+        &self.fps_anchor
+    }
+
+    /// The screen corner the scale bar is pinned to
+    pub fn scale_bar_anchor(&self) -> &Arc<AtomicCell<Corner>> {
+        &self.scale_bar_anchor
+    }
+
+    /// The brightness, contrast, and gamma applied to the rendered
+    /// graph, before the selection outline and GUI are drawn
+    pub fn color_adjustment(&self) -> &Arc<AtomicCell<ColorAdjustment>> {
+        &self.color_adjustment
+    }
+
+    /// The width in pixels of the outline drawn around each node; no
+    /// outline is drawn if this is zero, the default
+    pub fn node_outline_width(&self) -> &Arc<AtomicCell<f32>> {
+        &self.node_outline_width
+    }
+
+    pub fn node_outline_color(&self) -> &Arc<AtomicCell<rgb::RGB<f32>>> {
+        &self.node_outline_color
+    }
+
+    /// Whether nodes are drawn with square or rounded ends; square by
+    /// default
+    pub fn node_caps(&self) -> &Arc<AtomicCell<NodeCaps>> {
+        &self.node_caps
+    }
+
+    /// Whether the border around the selected nodes is blurred, the
+    /// default, or drawn as a solid outline in
+    /// `selection_outline_color`
+    pub fn selection_style(&self) -> &Arc<AtomicCell<SelectionStyle>> {
+        &self.selection_style
+    }
+
+    pub fn selection_outline_color(&self) -> &Arc<AtomicCell<rgb::RGB<f32>>> {
+        &self.selection_outline_color
+    }
+
+    /// If true, the tooltip for the hovered node includes the node's
+    /// length, degree, and path coverage, rather than just its ID
+    pub fn hover_detail(&self) -> &Arc<AtomicCell<bool>> {
+        &self.hover_detail
+    }
+
+    /// Nodes shorter than this many pixels on screen are thinned out,
+    /// with the remaining ones lengthened to stand in for their
+    /// neighbors; zero, the default, draws every node. Only applies
+    /// to the tessellation node renderer.
+    pub fn lod_min_pixels(&self) -> &Arc<AtomicCell<f32>> {
+        &self.lod_min_pixels
+    }
+
+    /// The maximum time between two clicks on a node, in
+    /// milliseconds, for them to count as a double-click, which
+    /// selects the node and centers the view on it; zero disables
+    /// double-clicking
+    pub fn double_click_ms(&self) -> &Arc<AtomicCell<u32>> {
+        &self.double_click_ms
+    }
+
+    /// If true, each visible node is labeled with its ID when the
+    /// view is zoomed in past `node_id_zoom`
+    pub fn show_node_ids(&self) -> &Arc<AtomicCell<bool>> {
+        &self.show_node_ids
+    }
+
+    /// The view scale, in world units per pixel, below which node ID
+    /// labels are shown
+    pub fn node_id_zoom(&self) -> &Arc<AtomicCell<f32>> {
+        &self.node_id_zoom
+    }
+
+    /// Whether each node is drawn with one colored ribbon per path
+    /// on the node, across its width; the ribbon colors are computed
+    /// the first time this is enabled
+    pub fn path_ribbons(&self) -> &Arc<AtomicCell<bool>> {
+        &self.path_ribbons
+    }
+
+    /// The mouse button that pans the main view, and drags nodes;
+    /// holding shift with it draws a selection rectangle
+    pub fn pan_button(&self) -> &Arc<AtomicCell<MouseButton>> {
+        &self.pan_button
+    }
+
+    /// The mouse button that selects the node under the cursor
+    pub fn select_button(&self) -> &Arc<AtomicCell<MouseButton>> {
+        &self.select_button
+    }
+
+    /// How much the view zooms per mouse wheel step
+    pub fn zoom_speed(&self) -> &Arc<AtomicCell<f32>> {
+        &self.zoom_speed
+    }
+
+    /// The smallest view scale, in world units per pixel, that the
+    /// view can be zoomed in to
+    pub fn min_scale(&self) -> &Arc<AtomicCell<f32>> {
+        &self.min_scale
+    }
+
+    /// The largest view scale, in world units per pixel, that the
+    /// view can be zoomed out to
+    pub fn max_scale(&self) -> &Arc<AtomicCell<f32>> {
+        &self.max_scale
+    }
+
+    /// Clamps the view scale to `min_scale` and `max_scale`
+    pub fn clamp_scale(&self, scale: f32) -> f32 {
+        let min = self.min_scale.load();
+        let max = self.max_scale.load().max(min);
+        scale.max(min).min(max)
+    }
+
+    /// The colors of paths, and any overrides set with
+    /// `set_path_color`; the edge path colors and path ribbons are
+    /// rebuilt when these change
+    pub fn path_colors(&self) -> &PathColors {
+        &self.path_colors
+    }
+
+    /// A directory of Rhai scripts that are all imported as modules
+    /// when the console is created
+    pub fn scripts_dir(&self) -> &Arc<Mutex<Option<PathBuf>>> {
+        &self.scripts_dir
+    }
+
+    /// The directory overlays created with `create_overlay_from_fn`
+    /// are cached in; the cache is disabled if this is `None`
+    pub fn overlay_cache_dir(&self) -> &Arc<Mutex<Option<PathBuf>>> {
+        &self.overlay_cache_dir
+    }
 }
 
 #[derive(Debug)]
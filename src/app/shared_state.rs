@@ -2,8 +2,11 @@ use std::sync::Arc;
 
 use crossbeam::atomic::AtomicCell;
 use handlegraph::handle::NodeId;
+use parking_lot::{Mutex, RwLock};
+use rustc_hash::FxHashMap;
 
-use crate::{geometry::*, gui::GuiFocusState};
+use crate::overlays::{Colormaps, OverlayLegend};
+use crate::{geometry::*, gui::widgets::FrameRate, gui::GuiFocusState};
 use crate::{view::*, vulkan::texture::GradientName};
 
 #[derive(Clone)]
@@ -26,6 +29,26 @@ pub struct SharedState {
     pub dark_mode: Arc<AtomicCell<bool>>,
 
     pub show_modal: Arc<AtomicCell<bool>>,
+
+    /// The FPS and frame time averages most recently shown in the
+    /// GUI, updated by the main loop
+    pub frame_rate: Arc<AtomicCell<FrameRate>>,
+
+    /// Arbitrary values attached to nodes by scripts, using
+    /// `set_node_data` and `get_node_data`
+    pub node_data: Arc<RwLock<FxHashMap<(NodeId, String), rhai::Dynamic>>>,
+
+    /// The fraction of nodes colored by the overlay script currently
+    /// being computed, if any; cleared when the overlay arrives
+    pub overlay_progress: Arc<AtomicCell<Option<f32>>>,
+
+    /// If true, the view is kept centered on the selection as it
+    /// changes; disabled by any manual panning
+    pub follow_selection: Arc<AtomicCell<bool>>,
+
+    /// The colormaps registered by scripts, used to color overlays by
+    /// annotation values
+    pub colormaps: Colormaps,
 }
 
 impl SharedState {
@@ -47,6 +70,16 @@ impl SharedState {
             edges_enabled: Arc::new(true.into()),
             dark_mode: Arc::new(false.into()),
             show_modal: Arc::new(false.into()),
+
+            frame_rate: Arc::new(FrameRate::default().into()),
+
+            node_data: Arc::new(RwLock::new(FxHashMap::default())),
+
+            overlay_progress: Arc::new(None.into()),
+
+            follow_selection: Arc::new(false.into()),
+
+            colormaps: Colormaps::default(),
         }
     }
 
@@ -163,7 +196,13 @@ impl std::default::Default for MouseRect {
 pub struct OverlayState {
     pub current_overlay: Arc<AtomicCell<Option<usize>>>,
 
+    // drawn under the current overlay, which is blended on top
+    base_overlay: Arc<AtomicCell<Option<usize>>>,
+
     gradient: Arc<AtomicCell<GradientName>>,
+
+    // the legends of the overlays that have one, by overlay ID
+    legends: Arc<Mutex<FxHashMap<usize, OverlayLegend>>>,
 }
 
 impl OverlayState {
@@ -171,6 +210,14 @@ impl OverlayState {
         self.current_overlay.load()
     }
 
+    /// The overlay that's always drawn under the current one, if any
+    pub fn base_overlay(&self) -> Option<usize> {
+        self.base_overlay.load()
+    }
+
+    /// The gradient used by value overlays; only builtin gradients
+    /// can be used, not colormaps registered by scripts (see
+    /// `Colormaps`)
     pub fn gradient(&self) -> GradientName {
         self.gradient.load()
     }
@@ -179,9 +226,27 @@ impl OverlayState {
         self.current_overlay.store(overlay_id);
     }
 
+    pub fn set_base_overlay(&self, overlay_id: Option<usize>) {
+        self.base_overlay.store(overlay_id);
+    }
+
     pub fn set_gradient(&self, gradient: GradientName) {
         self.gradient.store(gradient);
     }
+
+    pub fn legend(&self, overlay_id: usize) -> Option<OverlayLegend> {
+        self.legends.lock().get(&overlay_id).cloned()
+    }
+
+    pub fn set_legend(&self, overlay_id: usize, legend: Option<OverlayLegend>) {
+        let mut legends = self.legends.lock();
+
+        if let Some(legend) = legend {
+            legends.insert(overlay_id, legend);
+        } else {
+            legends.remove(&overlay_id);
+        }
+    }
 }
 
 impl std::default::Default for OverlayState {
@@ -192,7 +257,9 @@ impl std::default::Default for OverlayState {
 
         Self {
             current_overlay,
+            base_overlay: Arc::new(AtomicCell::new(None)),
             gradient,
+            legends: Default::default(),
         }
     }
 }
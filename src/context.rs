@@ -21,7 +21,9 @@ use bstr::ByteSlice;
 use rustc_hash::FxHashSet;
 
 use crate::{
-    app::{selection::NodeSelection, App, AppChannels, AppMsg, SharedState},
+    app::{
+        selection::NodeSelection, App, AppChannels, AppMsg, Select, SharedState,
+    },
     geometry::{Point, Rect},
     reactor::{ModalError, ModalHandler, ModalSuccess, Reactor},
 };
@@ -39,6 +41,9 @@ pub enum ContextEntry {
 // TODO this should be handled dynamically
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ContextAction {
+    SelectNode,
+    GotoNode,
+    SelectPath(PathId),
     CopyNodeId,
     CopyNodeSeq,
     CopyPathName,
@@ -78,6 +83,9 @@ pub struct ContextMenu {
 impl ContextMenu {
     const ID: &'static str = "context_menu";
 
+    // the maximum number of paths through the node listed in the menu
+    const MAX_PATH_ENTRIES: usize = 8;
+
     const POPUP_ID: &'static str = "context_menu_popup_id";
 
     pub fn new(app: &App) -> Self {
@@ -129,6 +137,40 @@ impl ContextMenu {
         contexts: &Contexts,
     ) {
         match action {
+            ContextAction::SelectNode => {
+                if let Some(node) = contexts.node {
+                    let select = Select::One { node, clear: true };
+                    self.channels
+                        .app_tx
+                        .send(AppMsg::Selection(select))
+                        .unwrap();
+                }
+            }
+            ContextAction::GotoNode => {
+                if let Some(node) = contexts.node {
+                    self.channels.app_tx.send(AppMsg::GotoNode(node)).unwrap();
+                }
+            }
+            ContextAction::SelectPath(path) => {
+                let nodes = reactor
+                    .graph_query
+                    .graph
+                    .path_steps(path)
+                    .map(|steps| {
+                        steps
+                            .map(|step| step.handle().id())
+                            .collect::<FxHashSet<_>>()
+                    })
+                    .unwrap_or_default();
+
+                if !nodes.is_empty() {
+                    let select = Select::Many { nodes, clear: true };
+                    self.channels
+                        .app_tx
+                        .send(AppMsg::Selection(select))
+                        .unwrap();
+                }
+            }
             ContextAction::CopyNodeId => {
                 if let Some(node) = contexts.node {
                     let contents = node.0.to_string();
@@ -272,7 +314,44 @@ impl ContextMenu {
                         ui.with_layout(
                             egui::Layout::top_down_justified(egui::Align::LEFT),
                             |ui| {
-                                if let Some(_node) = self.contexts.node {
+                                if let Some(node) = self.contexts.node {
+                                    if ui.button("Select node").clicked() {
+                                        process(ContextAction::SelectNode);
+                                    }
+                                    if ui.button("Go to node").clicked() {
+                                        process(ContextAction::GotoNode);
+                                    }
+
+                                    let graph_query = &reactor.graph_query;
+                                    let paths = graph_query.node_paths(node);
+
+                                    for &path in paths
+                                        .iter()
+                                        .take(Self::MAX_PATH_ENTRIES)
+                                    {
+                                        let name = graph_query
+                                            .graph
+                                            .get_path_name_vec(path)
+                                            .map(|n| {
+                                                n.to_str_lossy().into_owned()
+                                            })
+                                            .unwrap_or_default();
+
+                                        if ui
+                                            .button(format!(
+                                                "Select path {}",
+                                                name
+                                            ))
+                                            .clicked()
+                                        {
+                                            process(ContextAction::SelectPath(
+                                                path,
+                                            ));
+                                        }
+                                    }
+
+                                    ui.separator();
+
                                     if ui.button("Copy node ID").clicked() {
                                         process(ContextAction::CopyNodeId);
                                     }
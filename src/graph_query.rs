@@ -21,10 +21,11 @@ use rustc_hash::{FxHashMap};
 
 use crossbeam::channel;
 
-use std::{sync::Arc, ops::RangeInclusive};
+use std::{collections::BTreeMap, sync::Arc, ops::RangeInclusive};
 
 use anyhow::Result;
 
+use crate::app::selection::NodeSelection;
 use crate::asynchronous::AsyncResult;
 
 pub struct GraphQueryWorker {
@@ -138,6 +139,32 @@ impl GraphQuery {
         result
     }
 
+    /// The IDs of the paths that step on `node`, sorted and without
+    /// duplicates
+    pub fn node_paths(&self, node: NodeId) -> Vec<PathId> {
+        let handle = Handle::pack(node, false);
+
+        let mut paths = self
+            .graph
+            .steps_on_handle(handle)
+            .map(|steps| steps.map(|(path, _)| path).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Returns true if `path` steps on `node`
+    pub fn node_on_path(&self, node: NodeId, path: PathId) -> bool {
+        let handle = Handle::pack(node, false);
+
+        self.graph
+            .steps_on_handle(handle)
+            .map(|mut steps| steps.any(|(step_path, _)| step_path == path))
+            .unwrap_or(false)
+    }
+
     pub fn handle_positions(
         &self,
         handle: Handle,
@@ -234,6 +261,139 @@ impl GraphQuery {
 
         self.path_range(path_id, start, end)
     }
+
+    /// Selects all nodes with IDs in the inclusive range
+    /// `start..=end`, skipping IDs that aren't in the graph; the range
+    /// is clamped to the graph's node IDs first
+    pub fn node_range_selection(
+        &self,
+        start: NodeId,
+        end: NodeId,
+    ) -> NodeSelection {
+        let (start, end) = (start.0.min(end.0), start.0.max(end.0));
+
+        let start = start.max(self.graph.min_node_id().0);
+        let end = end.min(self.graph.max_node_id().0);
+
+        let nodes = (start..=end)
+            .map(NodeId::from)
+            .filter(|&id| self.graph.has_node(id))
+            .collect();
+
+        NodeSelection { nodes }
+    }
+
+    /// Computes the summary statistics written by `:graph_report`;
+    /// the node length and degree statistics are computed on the
+    /// rayon pool
+    pub fn graph_report(&self, rayon_pool: &rayon::ThreadPool) -> GraphReport {
+        use rayon::prelude::*;
+
+        let graph = &self.graph;
+
+        let handles = graph.handles().collect::<Vec<_>>();
+
+        let (mut node_lens, degrees) = rayon_pool.install(|| {
+            let node_lens = handles
+                .par_iter()
+                .map(|&handle| graph.node_len(handle))
+                .collect::<Vec<_>>();
+
+            let degrees = handles
+                .par_iter()
+                .map(|&handle| {
+                    graph.degree(handle, Direction::Left)
+                        + graph.degree(handle, Direction::Right)
+                })
+                .collect::<Vec<_>>();
+
+            (node_lens, degrees)
+        });
+
+        rayon_pool.install(|| node_lens.par_sort_unstable_by(|a, b| b.cmp(a)));
+
+        let total_len: usize = node_lens.iter().sum();
+
+        // the length of the shortest node among the longest nodes
+        // that together make up half the total length
+        let mut n50 = 0;
+        let mut cumulative = 0;
+        for &len in node_lens.iter() {
+            cumulative += len;
+            if cumulative * 2 >= total_len {
+                n50 = len;
+                break;
+            }
+        }
+
+        let mut degree_histogram: BTreeMap<usize, usize> = BTreeMap::new();
+        for &degree in degrees.iter() {
+            *degree_histogram.entry(degree).or_default() += 1;
+        }
+
+        let node_count = handles.len();
+
+        let mean_degree = if node_count > 0 {
+            degrees.iter().sum::<usize>() as f64 / node_count as f64
+        } else {
+            0.0
+        };
+
+        GraphReport {
+            node_count,
+            edge_count: graph.edge_count(),
+            path_count: graph.path_count(),
+            total_len,
+            n50,
+            min_node_len: node_lens.last().copied().unwrap_or(0),
+            max_node_len: node_lens.first().copied().unwrap_or(0),
+            mean_degree,
+            degree_histogram,
+        }
+    }
+}
+
+/// Summary statistics of the graph, see `GraphQuery::graph_report`
+#[derive(Debug, Clone)]
+pub struct GraphReport {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub path_count: usize,
+    pub total_len: usize,
+
+    pub n50: usize,
+    pub min_node_len: usize,
+    pub max_node_len: usize,
+
+    pub mean_degree: f64,
+    /// The number of nodes with each degree, counting the edges on
+    /// both sides of the node
+    pub degree_histogram: BTreeMap<usize, usize>,
+}
+
+impl GraphReport {
+    pub fn write<W: std::io::Write>(&self, mut out: W) -> std::io::Result<()> {
+        writeln!(out, "nodes\t{}", self.node_count)?;
+        writeln!(out, "edges\t{}", self.edge_count)?;
+        writeln!(out, "paths\t{}", self.path_count)?;
+        writeln!(out, "total_length\t{}", self.total_len)?;
+
+        writeln!(out, "node_length_n50\t{}", self.n50)?;
+        writeln!(out, "node_length_min\t{}", self.min_node_len)?;
+        writeln!(out, "node_length_max\t{}", self.max_node_len)?;
+
+        writeln!(out, "degree_mean\t{:.3}", self.mean_degree)?;
+        let max_degree = self.degree_histogram.keys().last().copied();
+        writeln!(out, "degree_max\t{}", max_degree.unwrap_or(0))?;
+
+        writeln!(out)?;
+        writeln!(out, "degree\tnodes")?;
+        for (degree, count) in self.degree_histogram.iter() {
+            writeln!(out, "{}\t{}", degree, count)?;
+        }
+
+        Ok(())
+    }
 }
 
 struct QueryThread {
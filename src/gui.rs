@@ -67,12 +67,20 @@ pub struct Gui {
     frame_input: FrameInput,
 
     shared_state: SharedState,
-    #[allow(dead_code)]
     settings: AppSettings,
 
     pub draw_system: GuiPipeline,
 
     hover_node_id: Option<NodeId>,
+    // the name of the overlaid graph and the node in it, if the
+    // cursor is over a node of an overlaid graph
+    hover_overlay_graph_node: Option<(String, NodeId)>,
+
+    // the view and cursor position when the stats panels were
+    // frozen, if they are
+    frozen_stats: Option<(crate::view::View, Point)>,
+
+    selection_stats: SelectionStats,
 
     open_windows: OpenWindows,
 
@@ -84,6 +92,9 @@ pub struct Gui {
     app_msg_tx: crossbeam::channel::Sender<AppMsg>,
 
     menu_bar: MenuBar,
+    node_search: NodeSearch,
+
+    command_palette: CommandPalette,
 
     dropped_file: Arc<std::sync::Mutex<Option<PathBuf>>>,
 
@@ -110,6 +121,8 @@ pub enum Windows {
 
     Paths,
 
+    SelectionStats,
+
     Themes,
     Overlays,
 
@@ -201,8 +214,13 @@ impl AppViewState {
             edge_count: graph.edge_count(),
             path_count: graph.path_count(),
             total_len: graph.total_length(),
+            layout_source: Default::default(),
+            bp_per_unit: 0.0,
+            memory: Default::default(),
         };
 
+        let palette = settings.palette().clone();
+
         let settings = SettingsWindow::new(settings, shared_state);
 
         let node_details_state = NodeDetails::default();
@@ -226,7 +244,7 @@ impl AppViewState {
         let path_list =
             ViewStateChannel::<PathList, PathListMsg>::new(path_list_state);
 
-        let overlay_list_state = OverlayList::new(overlay_state);
+        let overlay_list_state = OverlayList::new(overlay_state, palette);
         let overlay_list = ViewStateChannel::<OverlayList, OverlayListMsg>::new(
             overlay_list_state,
         );
@@ -272,14 +290,22 @@ impl AppViewState {
         &self.node_details
     }
 
-    pub fn apply_received(&mut self) {
-        self.fps.apply_received(|state, msg| {
-            *state = FrameRate::apply_msg(state, msg);
-        });
-
-        self.graph_stats.apply_received(|state, msg| {
-            *state = GraphStats::apply_msg(state, msg);
-        });
+    /// If `freeze_stats` is true, the FPS and graph stats messages are
+    /// discarded, so that they don't pile up while the stats are
+    /// frozen
+    pub fn apply_received(&mut self, freeze_stats: bool) {
+        if freeze_stats {
+            self.fps.apply_received(|_, _| ());
+            self.graph_stats.apply_received(|_, _| ());
+        } else {
+            self.fps.apply_received(|state, msg| {
+                *state = FrameRate::apply_msg(state, msg);
+            });
+
+            self.graph_stats.apply_received(|state, msg| {
+                *state = GraphStats::apply_msg(state, msg);
+            });
+        }
 
         self.node_list.apply_received(|state, msg| {
             state.apply_msg(msg);
@@ -309,6 +335,8 @@ pub struct OpenWindows {
     paths: bool,
     path_details: bool,
 
+    selection_stats: bool,
+
     themes: bool,
     overlays: bool,
     overlay_creator: bool,
@@ -329,6 +357,8 @@ impl std::default::Default for OpenWindows {
             paths: false,
             path_details: false,
 
+            selection_stats: false,
+
             themes: false,
             overlays: false,
             overlay_creator: false,
@@ -341,6 +371,10 @@ pub enum GuiMsg {
     SetLightMode,
     SetDarkMode,
 
+    // Stop (or resume) updating the values shown in the view info,
+    // cursor info, FPS, and graph stats panels; `None` toggles
+    FreezeStats(Option<bool>),
+
     EguiEvent(egui::Event),
     FileDropped { path: std::path::PathBuf },
 
@@ -432,44 +466,13 @@ impl Gui {
 
         let clipboard_ctx = ClipboardProvider::new().unwrap();
 
-        let mut path_picker_source = PathPickerSource::new(graph_query)?;
-
         let annotation_file_list = AnnotationFileList::new(
             reactor,
             app_msg_tx.clone(),
             gui_msg_tx.clone(),
         )?;
 
-        let gff3_list = {
-            let mut list = RecordList::new(
-                reactor,
-                egui::Id::new("gff3_records_list"),
-                path_picker_source.create_picker(),
-            );
-
-            use Gff3Column as Gff;
-
-            list.set_default_columns(
-                [Gff::Source, Gff::Type, Gff::Frame],
-                [Gff::SeqId, Gff::Start, Gff::End, Gff::Strand],
-            );
-
-            list
-        };
-
-        let bed_list = {
-            let mut list = RecordList::new(
-                reactor,
-                egui::Id::new("bed_records_list"),
-                path_picker_source.create_picker(),
-            );
-
-            use BedColumn as Bed;
-
-            list.set_default_columns([], [Bed::Chr, Bed::Start, Bed::End]);
-
-            list
-        };
+        let (gff3_list, bed_list) = Self::record_lists(reactor, graph_query)?;
 
         let console = Console::new(
             reactor,
@@ -489,6 +492,11 @@ impl Gui {
             draw_system,
 
             hover_node_id,
+            hover_overlay_graph_node: None,
+
+            frozen_stats: None,
+
+            selection_stats: SelectionStats::default(),
 
             open_windows,
 
@@ -500,6 +508,9 @@ impl Gui {
             app_msg_tx,
 
             menu_bar,
+            node_search: NodeSearch::default(),
+
+            command_palette: CommandPalette::default(),
 
             dropped_file,
 
@@ -517,6 +528,74 @@ impl Gui {
         Ok(gui)
     }
 
+    fn record_lists(
+        reactor: &mut Reactor,
+        graph_query: &Arc<GraphQuery>,
+    ) -> Result<(RecordList<Gff3Records>, RecordList<BedRecords>)> {
+        let mut path_picker_source = PathPickerSource::new(graph_query)?;
+
+        let gff3_list = {
+            let mut list = RecordList::new(
+                reactor,
+                egui::Id::new("gff3_records_list"),
+                path_picker_source.create_picker(),
+            );
+
+            use Gff3Column as Gff;
+
+            list.set_default_columns(
+                [Gff::Source, Gff::Type, Gff::Frame],
+                [Gff::SeqId, Gff::Start, Gff::End, Gff::Strand],
+            );
+
+            list
+        };
+
+        let bed_list = {
+            let mut list = RecordList::new(
+                reactor,
+                egui::Id::new("bed_records_list"),
+                path_picker_source.create_picker(),
+            );
+
+            use BedColumn as Bed;
+
+            list.set_default_columns([], [Bed::Chr, Bed::Start, Bed::End]);
+
+            list
+        };
+
+        Ok((gff3_list, bed_list))
+    }
+
+    /// Recreates the windows and console state that refer to the
+    /// loaded graph; called when the graph is reloaded
+    pub fn set_graph(
+        &mut self,
+        reactor: &mut Reactor,
+        graph_query: &Arc<GraphQuery>,
+    ) -> Result<()> {
+        self.view_state = AppViewState::new(
+            reactor,
+            graph_query,
+            &self.settings,
+            &self.shared_state,
+            self.shared_state.overlay_state().clone(),
+            self.dropped_file.clone(),
+        );
+
+        let (gff3_list, bed_list) = Self::record_lists(reactor, graph_query)?;
+        self.gff3_list = gff3_list;
+        self.bed_list = bed_list;
+
+        self.hover_node_id = None;
+        self.hover_overlay_graph_node = None;
+
+        self.console.set_graph(graph_query);
+
+        Ok(())
+    }
+
     pub fn clone_gui_msg_tx(&self) -> crossbeam::channel::Sender<GuiMsg> {
         self.gui_msg_tx.clone()
     }
@@ -525,6 +604,26 @@ impl Gui {
         self.hover_node_id = node;
     }
 
+    pub fn set_hover_overlay_graph_node(
+        &mut self,
+        node: Option<(&str, NodeId)>,
+    ) {
+        self.hover_overlay_graph_node =
+            node.map(|(name, node)| (name.to_string(), node));
+    }
+
+    /// Replaces the statistics shown in the selection window, see
+    /// `SelectionStats`
+    pub fn set_selection_stats(&mut self, stats: SelectionStats) {
+        self.selection_stats = stats;
+    }
+
+    /// The selection statistics are only computed while their window
+    /// is open
+    pub fn selection_stats_open(&self) -> bool {
+        self.open_windows.selection_stats
+    }
+
     pub fn app_view_state(&self) -> &AppViewState {
         &self.view_state
     }
@@ -613,7 +712,52 @@ impl Gui {
 
         self.console.ui(&self.ctx, self.console_down, reactor);
 
-        self.view_state.apply_received();
+        if let Some(action) = self.command_palette.ui(&self.ctx) {
+            self.apply_palette_action(action);
+        }
+
+        if let Some(progress) = self.shared_state.overlay_progress.load() {
+            egui::Window::new("Overlay progress")
+                .id(egui::Id::new("overlay_progress_window"))
+                .title_bar(false)
+                .resizable(false)
+                .anchor(
+                    egui::Align2::RIGHT_BOTTOM,
+                    egui::Vec2::new(-10.0, -10.0),
+                )
+                .show(&self.ctx, |ui| {
+                    ui.label("Computing overlay");
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+        }
+
+        if let Some(node) = self.hover_node_id {
+            if !self.shared_state.gui_focus_state.mouse_over_gui() {
+                let detail = self.settings.hover_detail().load();
+                let mut text = hover_tooltip_text(graph_query, node, detail);
+                text.push_str(&hover_label_text(annotations, node));
+
+                egui::show_tooltip_text(
+                    &self.ctx,
+                    egui::Id::new("hover_node_tooltip"),
+                    text,
+                );
+            }
+        } else if let Some((name, node)) = &self.hover_overlay_graph_node {
+            if !self.shared_state.gui_focus_state.mouse_over_gui() {
+                egui::show_tooltip_text(
+                    &self.ctx,
+                    egui::Id::new("hover_node_tooltip"),
+                    format!("Node {} ({})", node.0, name),
+                );
+            }
+        }
+
+        self.view_state.apply_received(self.frozen_stats.is_some());
 
         let scr = self.ctx.input().screen_rect();
 
@@ -635,6 +779,7 @@ impl Gui {
                 .ui(&self.ctx, overlay_creator);
 
             view_state.overlay_list.state.gradient_picker_ui(&self.ctx);
+            view_state.overlay_list.state.legend_ui(&self.ctx);
         }
 
         if let Some(rect) = self.shared_state.active_mouse_rect_screen() {
@@ -726,28 +871,61 @@ impl Gui {
             .settings
             .ui(&self.ctx, &mut self.open_windows.settings);
 
+        // widgets pinned to the top of the screen are moved below
+        // the menu bar
+        let corner_offset = |corner: Corner| {
+            if corner.is_top() {
+                Point::new(0.0, self.menu_bar.height())
+            } else {
+                Point::ZERO
+            }
+        };
+
         if view_state.settings.gui.show_fps {
-            let top = self.menu_bar.height();
+            let corner = self.settings.fps_anchor().load();
             view_state.fps.state.ui(
                 &self.ctx,
-                Point {
-                    x: scr.max.x - 100.0,
-                    y: top,
-                },
+                corner,
+                corner_offset(corner),
                 None,
             );
         }
 
         if view_state.settings.gui.show_graph_stats {
-            let top = self.menu_bar.height();
-
+            let corner = self.settings.stats_anchor().load();
             view_state.graph_stats.state.ui(
                 &self.ctx,
-                Point { x: 0.0, y: top },
+                corner,
+                corner_offset(corner),
                 None,
             );
         }
 
+        if view_state.settings.gui.show_scale_bar {
+            let corner = self.settings.scale_bar_anchor().load();
+
+            let scale_bar = ScaleBar {
+                bp_per_unit: view_state.graph_stats.state.bp_per_unit,
+                view_scale: self.shared_state.view.load().scale,
+            };
+
+            scale_bar.ui(&self.ctx, corner, corner_offset(corner), None);
+        }
+
+        if view_state.settings.gui.show_node_search {
+            let top = self.menu_bar.height();
+
+            self.node_search.ui(
+                &self.ctx,
+                Point {
+                    x: scr.center().x - 100.0,
+                    y: top,
+                },
+                graph_query,
+                &self.app_msg_tx,
+            );
+        }
+
         {
             let node_list = &self.open_windows.nodes;
             let node_details = &mut self.open_windows.node_details;
@@ -809,6 +987,11 @@ impl Gui {
             }
         }
 
+        if self.open_windows.selection_stats {
+            self.selection_stats
+                .ui(&self.ctx, &mut self.open_windows.selection_stats);
+        }
+
         {
             let debug = &mut view_state.settings.debug;
             let inspection = &mut debug.egui_inspection;
@@ -832,14 +1015,15 @@ impl Gui {
 
         let settings = &self.app_view_state().settings;
 
+        let (view, mouse) = self.frozen_stats.unwrap_or_else(|| {
+            (self.shared_state.view(), self.shared_state.mouse_pos())
+        });
+
         if settings.debug.view_info {
-            let view = self.shared_state.view();
             ViewDebugInfo::ui(&self.ctx, view);
         }
 
         if settings.debug.cursor_info {
-            let view = self.shared_state.view();
-            let mouse = self.shared_state.mouse_pos();
             MouseDebugInfo::ui(&self.ctx, view, mouse);
         }
     }
@@ -908,6 +1092,48 @@ impl Gui {
         self.frame_input.events.push(event);
     }
 
+    fn apply_palette_action(&mut self, action: PaletteAction) {
+        use PaletteAction as Action;
+
+        let app_msg = match action {
+            Action::ResetView => Some(AppMsg::ResetView),
+            Action::GotoSelection => Some(AppMsg::GotoSelection),
+            Action::FitSelection => Some(AppMsg::FitSelection),
+            Action::FitGraph => Some(AppMsg::FitGraph),
+            Action::ClearSelection => {
+                Some(AppMsg::Selection(crate::app::Select::Clear))
+            }
+            Action::ToggleDarkMode => Some(AppMsg::ToggleDarkMode),
+            Action::ToggleOverlay => {
+                let overlay_state = self.shared_state.overlay_state();
+                if !self.command_palette.toggle_overlay(overlay_state) {
+                    self.open_windows.overlays = true;
+                }
+                None
+            }
+            Action::OpenConsole => {
+                self.console_down = true;
+                self.ctx
+                    .memory()
+                    .request_focus(egui::Id::new(console::Console::ID_TEXT));
+                None
+            }
+            Action::OpenWindow(window) => {
+                self.gui_msg_tx
+                    .send(GuiMsg::SetWindowOpen {
+                        window,
+                        open: Some(true),
+                    })
+                    .unwrap();
+                None
+            }
+        };
+
+        if let Some(msg) = app_msg {
+            self.app_msg_tx.send(msg).unwrap();
+        }
+    }
+
     pub fn apply_received_gui_msgs(&mut self) {
         while let Ok(msg) = self.gui_msg_rx.try_recv() {
             match msg {
@@ -923,6 +1149,9 @@ impl Gui {
                         Windows::Nodes => &mut open_windows.nodes,
                         Windows::NodeDetails => &mut open_windows.node_details,
                         Windows::Paths => &mut open_windows.paths,
+                        Windows::SelectionStats => {
+                            &mut open_windows.selection_stats
+                        }
                         Windows::Themes => &mut open_windows.themes,
                         Windows::Overlays => &mut open_windows.overlays,
                         Windows::EguiInspection => {
@@ -948,6 +1177,18 @@ impl Gui {
                 GuiMsg::SetDarkMode => {
                     Self::dark_mode(&self.ctx);
                 }
+                GuiMsg::FreezeStats(freeze) => {
+                    let freeze =
+                        freeze.unwrap_or_else(|| self.frozen_stats.is_none());
+
+                    self.frozen_stats = if freeze {
+                        let view = self.shared_state.view();
+                        let mouse = self.shared_state.mouse_pos();
+                        self.frozen_stats.or(Some((view, mouse)))
+                    } else {
+                        None
+                    };
+                }
                 GuiMsg::EguiEvent(event) => {
                     self.frame_input.events.push(event);
                 }
@@ -1036,7 +1277,21 @@ impl Gui {
                             ));
                         }
                         GuiInput::KeyConsoleUp => {
-                            self.console_down = false;
+                            // the first press aborts any running
+                            // console scripts, without closing it
+                            if self.console.running_script_count() > 0 {
+                                self.console.abort_scripts();
+                            } else {
+                                self.console_down = false;
+                            }
+                        }
+                        GuiInput::KeyCommandPalette => {
+                            self.command_palette.toggle(&self.ctx);
+                        }
+                        GuiInput::KeyFreezeStats => {
+                            self.gui_msg_tx
+                                .send(GuiMsg::FreezeStats(None))
+                                .unwrap();
                         }
                         _ => (),
                     }
@@ -1119,6 +1374,67 @@ impl FrameInput {
     }
 }
 
+/// The text of the tooltip shown for the node under the cursor; if
+/// `detail` is true, the node's length, degree, and path coverage are
+/// included
+fn hover_tooltip_text(
+    graph_query: &GraphQuery,
+    node: NodeId,
+    detail: bool,
+) -> String {
+    let graph = graph_query.graph();
+    let handle = Handle::pack(node, false);
+
+    if !detail || !graph.has_node(node) {
+        return format!("Node {}", node.0);
+    }
+
+    let length = graph.node_len(handle);
+
+    let deg_in = graph.degree(handle, Direction::Left);
+    let deg_out = graph.degree(handle, Direction::Right);
+
+    let coverage = graph
+        .steps_on_handle(handle)
+        .map(|steps| steps.count())
+        .unwrap_or(0);
+
+    format!(
+        "Node {}\nLength: {} bp\nDegree: {} in, {} out\nCoverage: {} steps",
+        node.0, length, deg_in, deg_out, coverage
+    )
+}
+
+/// The labels at the node in each loaded annotation label set, one
+/// line per label set, for the hover tooltip; hidden label sets are
+/// included, so that their labels can be seen without drawing them
+fn hover_label_text(annotations: &Annotations, node: NodeId) -> String {
+    const MAX_LABELS: usize = 5;
+
+    let mut label_sets = annotations
+        .label_sets()
+        .values()
+        .filter(|label_set| label_set.labels().contains_key(&node))
+        .collect::<Vec<_>>();
+    label_sets.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let mut text = String::new();
+
+    for label_set in label_sets {
+        let labels = label_set.node_labels(node).collect::<Vec<_>>();
+
+        text.push_str(&format!("\n{}: ", label_set.name()));
+        text.push_str(&labels[..labels.len().min(MAX_LABELS)].join(", "));
+
+        if labels.len() > MAX_LABELS {
+            let rem = labels.len() - MAX_LABELS;
+            text.push_str(&format!(" and {} more", rem));
+        }
+    }
+
+    text
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum GuiInput {
     KeyEguiInspectionUi,
@@ -1130,6 +1446,8 @@ pub enum GuiInput {
     KeyToggleConsole,
     KeyConsoleDown,
     KeyConsoleUp,
+    KeyCommandPalette,
+    KeyFreezeStats,
 }
 
 impl BindableInput for GuiInput {
@@ -1138,19 +1456,28 @@ impl BindableInput for GuiInput {
         use winit::event::VirtualKeyCode as Key;
         use GuiInput as Input;
 
-        let key_binds: FxHashMap<Key, Vec<KeyBind<Input>>> = [
+        let mut key_binds: FxHashMap<Key, Vec<KeyBind<Input>>> = [
             (Key::F1, Input::KeyEguiInspectionUi),
             (Key::F2, Input::KeyEguiSettingsUi),
             (Key::F3, Input::KeyEguiMemoryUi),
             (Key::Escape, Input::KeyConsoleUp),
             (Key::Grave, Input::KeyConsoleDown),
             (Key::F4, Input::KeyToggleConsole),
+            (Key::F5, Input::KeyFreezeStats),
         ]
         .iter()
         .copied()
         .map(|(k, i)| (k, vec![KeyBind::new(i)]))
         .collect::<FxHashMap<_, _>>();
 
+        key_binds.insert(
+            Key::P,
+            vec![KeyBind::with_modifiers(
+                Input::KeyCommandPalette,
+                event::ModifiersState::CTRL,
+            )],
+        );
+
         let mouse_binds: FxHashMap<
             event::MouseButton,
             Vec<MouseButtonBind<Input>>,
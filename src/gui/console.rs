@@ -17,28 +17,44 @@ use log::debug;
 use crossbeam::atomic::AtomicCell;
 
 use rhai::plugin::*;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use bstr::ByteSlice;
 
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+use handlegraph::packedgraph::{paths::StepPtr, PackedGraph};
+
 use crate::{
     annotations::{
         AnnotationCollection, AnnotationRecord, Annotations, BedColumn,
         BedRecord, BedRecords, ColumnKey, Gff3Column, Gff3Record, Gff3Records,
+        Strand,
     },
-    overlays::OverlayKind,
+    overlays::{self, OverlayData, OverlayKind, OverlayLegend, Palette},
     reactor::{ModalError, ModalHandler, ModalSuccess},
 };
 use crate::{
     app::{
-        selection::NodeSelection, AppChannels, AppMsg, OverlayCreatorMsg,
-        Select,
+        mainview::ViewUpdate, ruler::PathRuler, selection::NodeSelection,
+        AppChannels, AppMsg, OverlayCreatorMsg, Select,
     },
     geometry::*,
+    gui::widgets::Corner,
     quad_tree::*,
-    reactor::Reactor,
-    script::{overlay_colors_tgt_ast, ScriptConfig, ScriptTarget},
+    reactor::{Reactor, RunningScripts},
+    script::{
+        filter_colors_ast, filter_records_ast, overlay_colors_tgt_ast,
+        ScriptConfig, ScriptTarget,
+    },
+    universe::Node,
     view::View,
+    vulkan::{
+        draw_system::{
+            edges::EdgeColorMode, nodes::NodeCaps, selection::SelectionStyle,
+        },
+        PresentMode,
+    },
 };
 use crate::{
     app::{AppSettings, SharedState},
@@ -47,9 +63,17 @@ use crate::{
 
 use parking_lot::Mutex;
 
+mod help;
+mod remote;
+mod session;
+
 pub type ScriptEvalResult =
     std::result::Result<rhai::Dynamic, Box<rhai::EvalAltResult>>;
 
+/// The name of the overlay written to by `set_node_color` and
+/// `set_nodes_color`
+const MANUAL_OVERLAY_NAME: &str = "Manual";
+
 /// The main console that is available in the GUI, and directly
 /// interacted with by the user.
 ///
@@ -63,6 +87,20 @@ pub struct Console<'a> {
     output_offset: usize,
     output_history: Vec<String>,
 
+    // while set, only the output lines matching the search are shown
+    // (see `Console::search_input`)
+    search: Option<OutputSearch>,
+
+    // the number of output lines that fit in the console window,
+    // recomputed every frame
+    visible_lines: usize,
+    resizing: bool,
+
+    // the `console_lines` value the window was last sized to; when
+    // the setting is changed from a script, the window height is
+    // set to match it for one frame
+    sized_lines: usize,
+
     scope: Arc<Mutex<rhai::Scope<'a>>>,
 
     request_focus: bool,
@@ -73,13 +111,35 @@ pub struct Console<'a> {
 
     get_set: Arc<GetSetTruth>,
 
-    remote_handles: HashMap<String, RemoteHandle<()>>,
+    remote_handles: HashMap<String, ScriptInterval>,
+
+    // the cancellation tokens of the scripts evaluated from the
+    // console input, checked by the engine while a script runs
+    running_scripts: RunningScripts,
+
+    // the address of the remote command listener, if started (see
+    // `:listen`)
+    remote_addr: Option<std::net::SocketAddr>,
+
+    // the engine used by remote connections, replaced when the graph
+    // is, so that connections opened before a reload see the new one
+    remote_engine: Option<Arc<Mutex<Arc<rhai::Engine>>>>,
+
+    // the file input lines are appended to while recording (see
+    // `:record_script`)
+    recording: Option<(String, std::fs::File)>,
 
     result_rx: crossbeam::channel::Receiver<ScriptEvalResult>,
     result_tx: crossbeam::channel::Sender<ScriptEvalResult>,
 
     graph: Arc<GraphQuery>,
     modules: Arc<Mutex<Vec<Arc<rhai::Module>>>>,
+    // the source of each imported module, for the overlay cache keys
+    module_sources: Arc<Mutex<Vec<String>>>,
+
+    // computed the first time an overlay is cached, and reset when
+    // the graph is reloaded
+    graph_hash: Arc<Mutex<Option<u64>>>,
 
     key_code_map: Arc<HashMap<String, winit::event::VirtualKeyCode>>,
     overlay_list: Arc<Mutex<Vec<(usize, OverlayKind, String)>>>,
@@ -90,11 +150,76 @@ pub struct Console<'a> {
     // RwLock or something inside
     window_defs: Arc<Mutex<Vec<ConsoleGuiDsl>>>,
 
+    path_walk: Arc<Mutex<Option<PathWalk>>>,
+
+    // relative script and annotation file paths are resolved against
+    // this, see `:cd`
+    working_dir: Arc<Mutex<PathBuf>>,
+
     future_tx: crossbeam::channel::Sender<
         Pin<Box<dyn Future<Output = ()> + Send + Sync + 'static>>,
     >,
 }
 
+/// A path being stepped through node by node, started with
+/// `walk_path_start`
+struct PathWalk {
+    steps: Vec<NodeId>,
+    index: usize,
+}
+
+impl PathWalk {
+    /// Moves `delta` steps along the path, stopping at either end,
+    /// then selects the node at the current step and centers the
+    /// view on it, returning the node
+    fn step(
+        &mut self,
+        app_tx: &crossbeam::channel::Sender<AppMsg>,
+        delta: isize,
+    ) -> Option<NodeId> {
+        let last = self.steps.len().checked_sub(1)?;
+        let index = (self.index as isize + delta).max(0) as usize;
+        self.index = index.min(last);
+
+        let node = self.steps[self.index];
+
+        let msg = AppMsg::Selection(Select::One { node, clear: true });
+        app_tx.send(msg).unwrap();
+        app_tx.send(AppMsg::GotoNode(node)).unwrap();
+
+        Some(node)
+    }
+}
+
+/// A search of the console output, started by entering `/<query>`
+struct OutputSearch {
+    query: String,
+    // the selected match, counting back from the most recent one
+    current: usize,
+}
+
+/// A script file being evaluated at a fixed interval, started with
+/// `:start_interval`, or an overlay being recomputed at a fixed
+/// interval, started with `:animate_overlay`.
+///
+/// Dropping this stops the interval; the future is dropped with the
+/// `RemoteHandle`, and the cancellation flag aborts the script if
+/// it's in the middle of being evaluated.
+struct ScriptInterval {
+    // the script file, or overlay function, shown by `:list_intervals`
+    source: String,
+    period: std::time::Duration,
+
+    cancelled: Arc<AtomicCell<bool>>,
+    _handle: RemoteHandle<()>,
+}
+
+impl Drop for ScriptInterval {
+    fn drop(&mut self) {
+        self.cancelled.store(true);
+    }
+}
+
 /// A "subconsole", spawned from one of the console commands (such as
 /// keybinds).
 ///
@@ -110,6 +235,12 @@ pub struct ConsoleShared {
 
     graph: Arc<GraphQuery>,
 
+    module_sources: Arc<Mutex<Vec<String>>>,
+    graph_hash: Arc<Mutex<Option<u64>>>,
+
+    path_walk: Arc<Mutex<Option<PathWalk>>>,
+    working_dir: Arc<Mutex<PathBuf>>,
+
     overlay_list: Arc<Mutex<Vec<(usize, OverlayKind, String)>>>,
 
     // is this a bad idea? i should probably just use a global pool
@@ -211,12 +342,203 @@ impl Console<'static> {
             "background_color_dark",
             settings.background_color_dark().clone()
         );
+        add_t!(
+            bool,
+            "background_gradient",
+            settings.background_gradient().clone()
+        );
+
+        add_t!(bool, "quad_tree_hover", settings.quad_tree_hover().clone());
+        add_t!(bool, "hover_detail", settings.hover_detail().clone());
+        add_t!(f32, "lod_min_pixels", settings.lod_min_pixels().clone());
+
+        add_t!(bool, "show_node_ids", settings.show_node_ids().clone());
+        add_t!(f32, "node_id_zoom", settings.node_id_zoom().clone());
+        add_t!(bool, "path_ribbons", settings.path_ribbons().clone());
+
+        add_t!(f32, "zoom_speed", settings.zoom_speed().clone());
+        add_t!(f32, "min_scale", settings.min_scale().clone());
+        add_t!(f32, "max_scale", settings.max_scale().clone());
+
+        add_t!(
+            f32,
+            "node_outline_width",
+            settings.node_outline_width().clone()
+        );
+        add_t!(
+            rgb::RGB<f32>,
+            "node_outline_color",
+            settings.node_outline_color().clone()
+        );
+        add_t!(
+            rgb::RGB<f32>,
+            "selection_outline_color",
+            settings.selection_outline_color().clone()
+        );
+
+        // setting the clear color to `()` goes back to using the
+        // background color
+        get_set.add_arc_atomic_cell_get_set(
+            "clear_color",
+            settings.clear_color().clone(),
+            |x| x.map(rhai::Dynamic::from).unwrap_or(rhai::Dynamic::UNIT),
+            |x: rhai::Dynamic| {
+                if x.is::<()>() {
+                    Some(None)
+                } else {
+                    x.try_cast::<rgb::RGBA<f32>>().map(Some)
+                }
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "double_click_ms",
+            settings.double_click_ms().clone(),
+            |x| rhai::Dynamic::from(x as i64),
+            |x: rhai::Dynamic| {
+                let ms = x.try_cast::<i64>()?;
+                Some(ms.max(0) as u32)
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "hover_kernel_size",
+            settings.hover_kernel_size().clone(),
+            |x| rhai::Dynamic::from(x as i64),
+            |x: rhai::Dynamic| {
+                let size = x.try_cast::<i64>()?;
+                (1..=15).contains(&size).then(|| size as u32)
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "console_lines",
+            settings.console_lines().clone(),
+            |x| rhai::Dynamic::from(x as i64),
+            |x: rhai::Dynamic| {
+                let lines = x.try_cast::<i64>()?;
+                Some(lines.max(1) as usize)
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "palette",
+            settings.palette().clone(),
+            |x| rhai::Dynamic::from(x.as_str().to_string()),
+            |x: rhai::Dynamic| {
+                let name = x.try_cast::<String>()?;
+                name.parse::<Palette>().ok()
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "node_caps",
+            settings.node_caps().clone(),
+            |x| rhai::Dynamic::from(x.as_str().to_string()),
+            |x: rhai::Dynamic| {
+                let name = x.try_cast::<String>()?;
+                name.parse::<NodeCaps>().ok()
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "selection_style",
+            settings.selection_style().clone(),
+            |x| rhai::Dynamic::from(x.as_str().to_string()),
+            |x: rhai::Dynamic| {
+                let name = x.try_cast::<String>()?;
+                name.parse::<SelectionStyle>().ok()
+            },
+        );
+
+        let corner_to_dyn =
+            |x: Corner| rhai::Dynamic::from(x.as_str().to_string());
+        let corner_from_dyn = |x: rhai::Dynamic| {
+            let name = x.try_cast::<String>()?;
+            name.parse::<Corner>().ok()
+        };
+
+        get_set.add_arc_atomic_cell_get_set(
+            "stats_anchor",
+            settings.stats_anchor().clone(),
+            corner_to_dyn,
+            corner_from_dyn,
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "fps_anchor",
+            settings.fps_anchor().clone(),
+            corner_to_dyn,
+            corner_from_dyn,
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "scale_bar_anchor",
+            settings.scale_bar_anchor().clone(),
+            corner_to_dyn,
+            corner_from_dyn,
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "msaa",
+            settings.msaa_samples().clone(),
+            |x| rhai::Dynamic::from(x as i64),
+            |x: rhai::Dynamic| {
+                // checked against what the device supports when it's
+                // applied, in the main loop
+                let samples = x.try_cast::<i64>()?;
+                (samples > 0).then(|| samples as u32)
+            },
+        );
+
+        get_set.add_arc_atomic_cell_get_set(
+            "present_mode",
+            settings.present_mode().clone(),
+            |x| rhai::Dynamic::from(x.as_str().to_string()),
+            |x: rhai::Dynamic| {
+                let name = x.try_cast::<String>()?;
+                name.parse::<PresentMode>().ok()
+            },
+        );
+
+        // read-only, updated by the main loop
+        let frame_rate = shared_state.frame_rate.clone();
+        get_set.add_getter("fps", move || {
+            rhai::Dynamic::from(frame_rate.load().fps)
+        });
+
+        let frame_rate = shared_state.frame_rate.clone();
+        get_set.add_getter("frame_time", move || {
+            rhai::Dynamic::from(frame_rate.load().frame_time)
+        });
 
         let edge = settings.edge_renderer().clone();
 
         add_nested_cast!(edge.clone(), edge_color, rgb::RGB<f32>);
         add_nested_cast!(edge.clone(), edge_width, f32);
         add_nested_cast!(edge.clone(), curve_offset, f32);
+        add_nested_cast!(edge.clone(), edge_bundling, bool);
+
+        get_set.add_arc_atomic_cell_get_set(
+            "edge_color_mode",
+            edge.clone(),
+            |ubo| rhai::Dynamic::from(ubo.color_mode.as_str().to_string()),
+            {
+                let edge = edge.clone();
+                move |x: rhai::Dynamic| {
+                    let name = x.try_cast::<String>()?;
+                    let mut ubo = edge.load();
+                    ubo.color_mode = name.parse::<EdgeColorMode>().ok()?;
+                    Some(ubo)
+                }
+            },
+        );
+
+        let adjust = settings.color_adjustment().clone();
+
+        add_nested_cast!(adjust.clone(), brightness, f32);
+        add_nested_cast!(adjust.clone(), contrast, f32);
+        add_nested_cast!(adjust.clone(), gamma, f32);
 
         let e1 = edge.clone();
         let e2 = edge.clone();
@@ -297,7 +619,7 @@ impl Console<'static> {
 
         let window_defs = Arc::new(Mutex::new(vec![]));
 
-        Self {
+        let mut console = Self {
             input_line: String::new(),
 
             input_history_ix: None,
@@ -306,6 +628,12 @@ impl Console<'static> {
             output_offset: 0,
             output_history,
 
+            search: None,
+
+            visible_lines: settings.console_lines().load(),
+            resizing: false,
+            sized_lines: settings.console_lines().load(),
+
             scope,
 
             request_focus: false,
@@ -317,6 +645,11 @@ impl Console<'static> {
             get_set: Arc::new(get_set),
 
             remote_handles: Default::default(),
+            running_scripts: reactor.running_scripts.clone(),
+            remote_addr: None,
+            remote_engine: None,
+
+            recording: None,
 
             result_tx,
             result_rx,
@@ -325,6 +658,9 @@ impl Console<'static> {
             // graph: graph.graph.clone(),
             // path_positions: graph.path_positions.clone(),
             modules: Arc::new(Mutex::new(Vec::new())),
+            module_sources: Arc::new(Mutex::new(Vec::new())),
+
+            graph_hash: Arc::new(Mutex::new(None)),
 
             key_code_map,
 
@@ -333,8 +669,52 @@ impl Console<'static> {
 
             window_defs,
 
+            path_walk: Arc::new(Mutex::new(None)),
+
+            working_dir: Arc::new(Mutex::new(
+                std::env::current_dir().unwrap_or_default(),
+            )),
+
             future_tx,
+        };
+
+        // step through the path started with `walk_path_start`
+        for &(key, delta) in [
+            (winit::event::VirtualKeyCode::Period, 1),
+            (winit::event::VirtualKeyCode::Comma, -1),
+        ]
+        .iter()
+        {
+            let path_walk = console.path_walk.clone();
+            let app_tx = console.channels.app_tx.clone();
+
+            let command = move || {
+                if let Some(walk) = path_walk.lock().as_mut() {
+                    walk.step(&app_tx, delta);
+                }
+            };
+
+            console
+                .channels
+                .binds_tx
+                .send((key, Some(Box::new(command))))
+                .unwrap();
         }
+
+        let scripts_dir = console.settings.scripts_dir().lock().clone();
+        if let Some(dir) = scripts_dir {
+            if let Err(err) = console.import_dir(&dir) {
+                let msg = format!(
+                    " >>> error reading scripts directory {}: {}",
+                    dir.display(),
+                    err
+                );
+                console.append_output(&msg);
+                log::warn!("{}", msg);
+            }
+        }
+
+        console
     }
 
     /// Create a subconsole that shares state with the main console
@@ -351,6 +731,12 @@ impl Console<'static> {
             // path_positions: self.path_positions.clone(),
             result_tx: self.result_tx.clone(),
 
+            module_sources: self.module_sources.clone(),
+            graph_hash: self.graph_hash.clone(),
+
+            path_walk: self.path_walk.clone(),
+            working_dir: self.working_dir.clone(),
+
             overlay_list: self.overlay_list.clone(),
             rayon_pool: self.rayon_pool.clone(),
 
@@ -358,6 +744,12 @@ impl Console<'static> {
         }
     }
 
+    /// Resolves a path against the console's working directory, if
+    /// it's relative
+    fn resolve_path(&self, path: &str) -> String {
+        resolve_console_path(&self.working_dir, path)
+    }
+
     pub fn append_output(&mut self, output: &str) {
         self.output_history.extend(output.lines().map(String::from));
     }
@@ -373,6 +765,20 @@ impl Console<'static> {
         overlays.extend(names.iter().map(|&(a, b, s)| (a, b, s.to_string())));
     }
 
+    /// Makes scripts use the new graph after the graph is reloaded;
+    /// running intervals and overlay animations are stopped, as they
+    /// were started against the old graph
+    pub fn set_graph(&mut self, graph: &Arc<GraphQuery>) {
+        self.graph = graph.clone();
+        *self.graph_hash.lock() = None;
+        *self.path_walk.lock() = None;
+        self.remote_handles.clear();
+
+        if let Some(remote_engine) = &self.remote_engine {
+            *remote_engine.lock() = Arc::new(self.create_engine());
+        }
+    }
+
     fn create_scope() -> rhai::Scope<'static> {
         let scope = rhai::Scope::new();
         scope
@@ -462,6 +868,40 @@ impl Console<'static> {
             },
         );
 
+        // moves a main view mouse action ("pan" or "select") to
+        // another button ("left", "right", or "middle")
+        let pan_button = self.settings.pan_button().clone();
+        let select_button = self.settings.select_button().clone();
+        engine.register_result_fn(
+            "bind_mouse",
+            move |action: &str, button: &str| {
+                use winit::event::MouseButton;
+
+                let button = match button {
+                    "left" => MouseButton::Left,
+                    "right" => MouseButton::Right,
+                    "middle" => MouseButton::Middle,
+                    _ => {
+                        return Err(
+                            format!("Unknown mouse button: {}", button).into()
+                        )
+                    }
+                };
+
+                match action {
+                    "pan" => pan_button.store(button),
+                    "select" => select_button.store(button),
+                    _ => {
+                        return Err(
+                            format!("Unknown mouse action: {}", action).into()
+                        )
+                    }
+                }
+
+                Ok(())
+            },
+        );
+
         let rayon_pool = self.rayon_pool.clone();
         let graph = self.graph.clone();
         let config = ScriptConfig {
@@ -470,63 +910,189 @@ impl Console<'static> {
         };
 
         let overlay_tx = self.channels.new_overlay_tx.clone();
+        let overlay_progress = self.shared_state.overlay_progress.clone();
         let shared = self.shared();
         let modules = self.modules.clone();
         engine.register_fn(
             "create_overlay_from_fn",
             move |name: &str, fn_name: rhai::Dynamic| {
                 if let Some(fn_name) = fn_name.try_cast::<String>() {
-                    let mut scope = Self::create_scope();
-
-                    scope
-                        .push("graph", graph.graph.clone())
-                        .push("path_pos", graph.path_positions.clone());
+                    let result = Self::overlay_data_from_fn(
+                        &shared,
+                        &modules,
+                        &rayon_pool,
+                        &graph,
+                        &config,
+                        &overlay_progress,
+                        &fn_name,
+                    );
 
-                    let mut engine = shared.create_engine();
-                    {
-                        let modules = modules.lock();
-                        for module in modules.iter() {
-                            engine.register_global_module(module.clone());
+                    match result {
+                        Ok(data) => {
+                            let msg = OverlayCreatorMsg::NewOverlay {
+                                name: name.to_string(),
+                                data,
+                                legend: None,
+                            };
+                            overlay_tx.send(msg).unwrap();
+                            log::info!("overlay data success");
+                        }
+                        Err(err) => {
+                            log::warn!("overlay failure: {:?}", err);
                         }
                     }
+                }
+            },
+        );
 
-                    let script =
-                        format!("\nfn node_color(i) {{\n{}(i);\n}}", fn_name);
-                    log::debug!("script: {}", script);
-
-                    let node_color_ast =
-                        engine.compile_into_self_contained(&scope, &script);
-
-                    match node_color_ast {
-                        Ok(node_color_ast) => {
-                            let result = overlay_colors_tgt_ast(
-                                &rayon_pool,
-                                &config,
-                                &graph,
-                                &engine,
-                                scope,
-                                node_color_ast,
-                            );
-
-                            match result {
-                                Ok(data) => {
-                                    let msg = OverlayCreatorMsg::NewOverlay {
-                                        name: name.to_string(),
-                                        data,
-                                    };
-                                    overlay_tx.send(msg).unwrap();
-                                    log::info!("overlay data success");
-                                }
-                                Err(_err) => {
-                                    log::warn!("overlay failure");
-                                }
-                            }
-                        }
-                        Err(_err) => {
-                            log::warn!("ast failure");
-                        }
+        // like `create_overlay_from_fn`, but blocks until the overlay
+        // has been created, and returns its handle, so that it can be
+        // passed to `set_active_overlay`
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let config = ScriptConfig {
+            default_color: rgb::RGBA::new(0.3, 0.3, 0.3, 0.3),
+            target: ScriptTarget::Nodes,
+        };
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        let overlay_progress = self.shared_state.overlay_progress.clone();
+        let shared = self.shared();
+        let modules = self.modules.clone();
+        engine.register_result_fn(
+            "create_overlay_from_fn_sync",
+            move |name: &str,
+                  fn_name: &str|
+                  -> std::result::Result<
+                (usize, OverlayKind),
+                Box<EvalAltResult>,
+            > {
+                let data = Self::overlay_data_from_fn(
+                    &shared,
+                    &modules,
+                    &rayon_pool,
+                    &graph,
+                    &config,
+                    &overlay_progress,
+                    fn_name,
+                )?;
+
+                let (created_tx, created_rx) =
+                    futures::channel::oneshot::channel::<(usize, OverlayKind)>(
+                    );
+
+                let msg = OverlayCreatorMsg::NewOverlayNotify {
+                    name: name.to_string(),
+                    data,
+                    created_tx,
+                };
+                overlay_tx.send(msg).unwrap();
+
+                futures::executor::block_on(created_rx)
+                    .map_err(|_| "Overlay could not be created".into())
+            },
+        );
+
+        // Returns the indices of the records in the collection for
+        // which the function `fn_name` returns true, e.g. to pass to
+        // `create_label_set`
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let shared = self.shared();
+        let modules = self.modules.clone();
+        engine.register_result_fn(
+            "filter_records",
+            move |records: &mut Arc<Gff3Records>, fn_name: &str| {
+                Self::filter_records_impl(
+                    &shared,
+                    &modules,
+                    &rayon_pool,
+                    &graph,
+                    records.as_ref(),
+                    fn_name,
+                )
+            },
+        );
+
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let shared = self.shared();
+        let modules = self.modules.clone();
+        engine.register_result_fn(
+            "filter_records",
+            move |records: &mut Arc<BedRecords>, fn_name: &str| {
+                Self::filter_records_impl(
+                    &shared,
+                    &modules,
+                    &rayon_pool,
+                    &graph,
+                    records.as_ref(),
+                    fn_name,
+                )
+            },
+        );
+
+        // selects the nodes whose colors in the active overlay the
+        // function `fn_name` returns true for, and returns the number
+        // of selected nodes
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let shared = self.shared();
+        let modules = self.modules.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "select_where_overlay",
+            move |fn_name: &str| -> std::result::Result<i64, Box<EvalAltResult>> {
+                let (tx, rx) =
+                    crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+                let msg = AppMsg::RequestData {
+                    key: "overlay_colors".to_string(),
+                    index: "".to_string(),
+                    sender: tx,
+                };
+
+                app_msg_tx.send(msg).unwrap();
+
+                let result =
+                    std::thread::spawn(move || rx.recv().unwrap()).join();
+                let colors =
+                    Self::error_helper::<Vec<rgb::RGBA<f32>>>(&result)?;
+
+                let mut scope = Self::create_scope();
+
+                scope
+                    .push("graph", graph.graph.clone())
+                    .push("path_pos", graph.path_positions.clone());
+
+                let mut engine = shared.create_engine();
+                {
+                    let modules = modules.lock();
+                    for module in modules.iter() {
+                        engine.register_global_module(module.clone());
                     }
                 }
+
+                let script =
+                    format!("\nfn keep(color) {{\n{}(color)\n}}", fn_name);
+
+                let keep_ast =
+                    engine.compile_into_self_contained(&scope, &script)?;
+
+                let nodes = filter_colors_ast(
+                    &rayon_pool,
+                    &engine,
+                    scope,
+                    keep_ast,
+                    &colors,
+                )?;
+
+                let count = nodes.len() as i64;
+
+                let nodes = nodes.into_iter().collect::<FxHashSet<_>>();
+                let msg = AppMsg::Selection(Select::Many { nodes, clear: true });
+                app_msg_tx.send(msg).unwrap();
+
+                Ok(count)
             },
         );
 
@@ -543,44 +1109,184 @@ impl Console<'static> {
         engine
     }
 
-    fn add_gui_dsl_fns(&self, engine: &mut rhai::Engine) {
-        // create a new window with the provided title, and return the index of the window
-        //
-        // NB: there's no way yet to remove or hide windows created here
-        let window_defs = self.window_defs.clone();
-        engine.register_fn("new_window", move |title: &str| {
-            let mut win_defs = window_defs.lock();
+    /// Computes the overlay data for the node color function
+    /// `fn_name`, using the loaded modules. If the overlay cache is
+    /// enabled, results are cached on disk, keyed by the graph and the
+    /// source of the loaded modules, and loaded from the cache instead
+    /// of recomputed when possible
+    fn overlay_data_from_fn(
+        shared: &ConsoleShared,
+        modules: &Mutex<Vec<Arc<rhai::Module>>>,
+        rayon_pool: &rayon::ThreadPool,
+        graph: &GraphQuery,
+        config: &ScriptConfig,
+        overlay_progress: &AtomicCell<Option<f32>>,
+        fn_name: &str,
+    ) -> std::result::Result<OverlayData, Box<EvalAltResult>> {
+        let cache_dir = shared.settings.overlay_cache_dir().lock().clone();
+
+        let cache_key = cache_dir.map(|dir| {
+            use std::hash::Hasher;
+
+            let mut hasher = overlays::cache::StableHasher::default();
+
+            hasher.write(fn_name.as_bytes());
+
+            let c = config.default_color;
+            for v in [c.r, c.g, c.b, c.a].iter() {
+                hasher.write(&v.to_bits().to_le_bytes());
+            }
 
-            let ix = win_defs.len();
-            let window = ConsoleGuiDsl::new(
-                title,
-                egui::Id::new(format!("{}-win_def_dsl-{}", title, ix,)),
-            );
+            // the sources are length-prefixed so that moving text
+            // between modules changes the hash
+            for source in shared.module_sources.lock().iter() {
+                hasher.write(&(source.len() as u64).to_le_bytes());
+                hasher.write(source.as_bytes());
+            }
 
-            win_defs.push(window);
+            let script_hash = hasher.finish();
 
-            ix as i64
+            let graph_hash =
+                *shared.graph_hash.lock().get_or_insert_with(|| {
+                    overlays::cache::graph_hash(rayon_pool, graph)
+                });
+
+            (dir, graph_hash, script_hash)
         });
 
-        // add a label to the window with the provided index
-        let window_defs = self.window_defs.clone();
-        engine.register_fn("add_label", move |ix: i64, text: &str| {
-            let mut win_defs = window_defs.lock();
+        if let Some((dir, graph_hash, script_hash)) = cache_key.as_ref() {
+            let cached = overlays::cache::load(dir, *graph_hash, *script_hash);
 
-            if let Some(window) = win_defs.get_mut(ix as usize) {
-                window.elements.push(ConsoleGuiElem::Label {
-                    text: text.to_string(),
-                });
+            if let Some(data) = cached {
+                log::info!("loaded overlay for {} from cache", fn_name);
+                return Ok(data);
             }
-        });
+        }
 
-        let window_defs = self.window_defs.clone();
-        engine.register_fn(
-            "add_button",
-            move |ix: i64, text: &str, callback_id: &str| {
-                let mut win_defs = window_defs.lock();
+        let mut scope = Self::create_scope();
 
-                if let Some(window) = win_defs.get_mut(ix as usize) {
+        scope
+            .push("graph", graph.graph.clone())
+            .push("path_pos", graph.path_positions.clone());
+
+        let mut engine = shared.create_engine();
+        {
+            let modules = modules.lock();
+            for module in modules.iter() {
+                engine.register_global_module(module.clone());
+            }
+        }
+
+        let script = format!("\nfn node_color(i) {{\n{}(i);\n}}", fn_name);
+        log::debug!("script: {}", script);
+
+        let node_color_ast =
+            engine.compile_into_self_contained(&scope, &script)?;
+
+        let result = overlay_colors_tgt_ast(
+            rayon_pool,
+            config,
+            graph,
+            &engine,
+            scope,
+            node_color_ast,
+            Some(overlay_progress),
+        );
+
+        match (&result, cache_key) {
+            (Ok(data), Some((dir, graph_hash, script_hash))) => {
+                if let Err(err) =
+                    overlays::cache::store(&dir, graph_hash, script_hash, data)
+                {
+                    log::warn!("error writing overlay cache: {}", err);
+                }
+            }
+            (Ok(_), None) => (),
+            (Err(_), _) => overlay_progress.store(None),
+        }
+
+        result
+    }
+
+    fn filter_records_impl<C>(
+        shared: &ConsoleShared,
+        modules: &Mutex<Vec<Arc<rhai::Module>>>,
+        rayon_pool: &rayon::ThreadPool,
+        graph: &GraphQuery,
+        records: &C,
+        fn_name: &str,
+    ) -> std::result::Result<Vec<rhai::Dynamic>, Box<EvalAltResult>>
+    where
+        C: AnnotationCollection,
+        C::Record: Clone + Send + Sync + 'static,
+    {
+        let mut scope = Self::create_scope();
+
+        scope
+            .push("graph", graph.graph.clone())
+            .push("path_pos", graph.path_positions.clone());
+
+        let mut engine = shared.create_engine();
+        {
+            let modules = modules.lock();
+            for module in modules.iter() {
+                engine.register_global_module(module.clone());
+            }
+        }
+
+        let script = format!("\nfn keep(record) {{\n{}(record)\n}}", fn_name);
+
+        let keep_ast = engine.compile_into_self_contained(&scope, &script)?;
+
+        let indices =
+            filter_records_ast(rayon_pool, &engine, scope, keep_ast, records)?;
+
+        let indices = indices
+            .into_iter()
+            .map(|ix| rhai::Dynamic::from(ix as i64))
+            .collect();
+
+        Ok(indices)
+    }
+
+    fn add_gui_dsl_fns(&self, engine: &mut rhai::Engine) {
+        // create a new window with the provided title, and return the index of the window
+        //
+        // NB: there's no way yet to remove or hide windows created here
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("new_window", move |title: &str| {
+            let mut win_defs = window_defs.lock();
+
+            let ix = win_defs.len();
+            let window = ConsoleGuiDsl::new(
+                title,
+                egui::Id::new(format!("{}-win_def_dsl-{}", title, ix,)),
+            );
+
+            win_defs.push(window);
+
+            ix as i64
+        });
+
+        // add a label to the window with the provided index
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("add_label", move |ix: i64, text: &str| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(window) = win_defs.get_mut(ix as usize) {
+                window.elements.push(ConsoleGuiElem::Label {
+                    text: text.to_string(),
+                });
+            }
+        });
+
+        let window_defs = self.window_defs.clone();
+        engine.register_fn(
+            "add_button",
+            move |ix: i64, text: &str, callback_id: &str| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = win_defs.get_mut(ix as usize) {
                     window.elements.push(ConsoleGuiElem::Button {
                         text: text.to_string(),
                         callback_id: callback_id.to_string(),
@@ -619,6 +1325,63 @@ impl Console<'static> {
             },
         );
 
+        // add a line plot of the data with the provided ID to the
+        // window; the data is set with `set_plot_data`
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("add_plot", move |ix: i64, data_id: &str| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(window) = win_defs.get_mut(ix as usize) {
+                window.elements.push(ConsoleGuiElem::Plot {
+                    data_id: data_id.to_string(),
+                    scatter: false,
+                });
+            }
+        });
+
+        // like `add_plot`, but draws the data as points
+        let window_defs = self.window_defs.clone();
+        engine.register_fn(
+            "add_scatter_plot",
+            move |ix: i64, data_id: &str| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = win_defs.get_mut(ix as usize) {
+                    window.elements.push(ConsoleGuiElem::Plot {
+                        data_id: data_id.to_string(),
+                        scatter: true,
+                    });
+                }
+            },
+        );
+
+        // set the data shown by the plots with the provided ID; either
+        // an array of numbers, or an array of points or `[x, y]` pairs
+        let window_defs = self.window_defs.clone();
+        engine.register_result_fn(
+            "set_plot_data",
+            move |ix: i64,
+                  data_id: &str,
+                  data: rhai::Array|
+                  -> std::result::Result<(), Box<EvalAltResult>> {
+                let mut win_defs = window_defs.lock();
+
+                let window = win_defs
+                    .get_mut(ix as usize)
+                    .ok_or_else(|| "Window does not exist".to_string())?;
+
+                let data =
+                    ConsoleGuiDsl::parse_plot_data(data).ok_or_else(|| {
+                        "Plot data must be numbers, points, or [x, y] pairs"
+                            .to_string()
+                    })?;
+
+                window.plot_data.insert(data_id.to_string(), data);
+
+                Ok(())
+            },
+        );
+
         // `fn_name` here has the same limitations as seen in create_engine above
         let window_defs = self.window_defs.clone();
         let shared = self.shared();
@@ -729,13 +1492,28 @@ impl Console<'static> {
         reactor: &mut Reactor,
         handle_name: &str,
         path: &str,
+        period: std::time::Duration,
     ) -> Result<()> {
         let handle_name = handle_name.to_string();
 
-        let engine = self.create_engine();
+        let mut engine = self.create_engine();
+
+        let cancelled = Arc::new(AtomicCell::new(false));
+
+        {
+            let cancelled = cancelled.clone();
+            engine.on_progress(move |_| {
+                if cancelled.load() {
+                    Some(rhai::Dynamic::UNIT)
+                } else {
+                    None
+                }
+            });
+        }
 
         let start = std::time::Instant::now();
 
+        let file = path.to_string();
         let path = PathBuf::from(path);
         let ast = engine.compile_file(path)?;
 
@@ -745,8 +1523,14 @@ impl Console<'static> {
             scope
         };
 
+        let is_cancelled = cancelled.clone();
+
         let handle = reactor.spawn_interval(
             move || {
+                if is_cancelled.load() {
+                    return;
+                }
+
                 scope.set_value(
                     "time_since_start",
                     start.elapsed().as_secs_f32(),
@@ -755,16 +1539,295 @@ impl Console<'static> {
                 let _result: std::result::Result<(), _> =
                     engine.eval_ast_with_scope(&mut scope, &ast);
             },
-            std::time::Duration::from_millis(30),
+            period,
         )?;
 
-        self.remote_handles.insert(handle_name, handle);
+        let interval = ScriptInterval {
+            source: file,
+            period,
+            cancelled,
+            _handle: handle,
+        };
+
+        // replacing an existing interval with the same name drops,
+        // and thus stops, the old one
+        self.remote_handles.insert(handle_name, interval);
 
         Ok(())
     }
 
+    /// Recomputes the overlay `fn_name` every `period`, replacing the
+    /// overlay with the new colors each time. The node color function
+    /// is compiled once, and can animate the overlay using
+    /// `animation_time()`, the time in seconds since the animation
+    /// started.
+    ///
+    /// Like the script intervals, the animation is stopped using
+    /// `:end_interval` with `handle_name`.
+    fn animate_overlay(
+        &mut self,
+        reactor: &mut Reactor,
+        handle_name: &str,
+        fn_name: &str,
+        period: std::time::Duration,
+    ) -> Result<()> {
+        let handle_name = handle_name.to_string();
+
+        let mut scope = Self::create_scope();
+        scope
+            .push("graph", self.graph.graph.clone())
+            .push("path_pos", self.graph.path_positions.clone());
+
+        let mut engine = self.create_engine();
+
+        let cancelled = Arc::new(AtomicCell::new(false));
+
+        {
+            let cancelled = cancelled.clone();
+            engine.on_progress(move |_| {
+                if cancelled.load() {
+                    Some(rhai::Dynamic::UNIT)
+                } else {
+                    None
+                }
+            });
+        }
+
+        let start = std::time::Instant::now();
+
+        let time = Arc::new(AtomicCell::new(0.0f32));
+        {
+            let time = time.clone();
+            engine.register_fn("animation_time", move || time.load());
+        }
+
+        let script = format!("\nfn node_color(i) {{\n{}(i);\n}}", fn_name);
+        let node_color_ast =
+            engine.compile_into_self_contained(&scope, &script)?;
+
+        let config = ScriptConfig {
+            default_color: rgb::RGBA::new(0.3, 0.3, 0.3, 0.3),
+            target: ScriptTarget::Nodes,
+        };
+
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+
+        let overlay_name = fn_name.to_string();
+        let is_cancelled = cancelled.clone();
+
+        let handle = reactor.spawn_interval(
+            move || {
+                if is_cancelled.load() {
+                    return;
+                }
+
+                time.store(start.elapsed().as_secs_f32());
+
+                let result = overlay_colors_tgt_ast(
+                    &rayon_pool,
+                    &config,
+                    &graph,
+                    &engine,
+                    scope.clone(),
+                    node_color_ast.clone(),
+                    None,
+                );
+
+                match result {
+                    Ok(data) => {
+                        let msg = OverlayCreatorMsg::NewOverlay {
+                            name: overlay_name.clone(),
+                            data,
+                            legend: None,
+                        };
+                        overlay_tx.send(msg).unwrap();
+                    }
+                    Err(err) => {
+                        log::warn!("overlay animation error: {:?}", err);
+                    }
+                }
+            },
+            period,
+        )?;
+
+        let interval = ScriptInterval {
+            source: format!("overlay {}", fn_name),
+            period,
+            cancelled,
+            _handle: handle,
+        };
+
+        self.remote_handles.insert(handle_name, interval);
+
+        Ok(())
+    }
+
+    /// Signals the scripts evaluated from the console that are still
+    /// running to abort, returning the number of scripts signaled
+    pub fn abort_scripts(&mut self) -> usize {
+        let count = self.running_scripts.cancel_all();
+
+        if count > 0 {
+            let msg = format!(" >>> aborting {} running scripts", count);
+            self.append_output(&msg);
+        } else {
+            self.append_output(" >>> no running scripts");
+        }
+
+        count
+    }
+
+    pub fn running_script_count(&self) -> usize {
+        self.running_scripts.running_count()
+    }
+
     fn stop_interval(&mut self, handle_name: &str) {
-        self.remote_handles.remove(handle_name);
+        if self.remote_handles.remove(handle_name).is_none() {
+            let msg = format!(" >>> no interval named {}", handle_name);
+            self.append_output(&msg);
+        }
+    }
+
+    fn list_intervals(&mut self) {
+        if self.remote_handles.is_empty() {
+            self.append_output(" >>> no active intervals");
+            return;
+        }
+
+        let mut intervals = self
+            .remote_handles
+            .iter()
+            .map(|(name, interval)| {
+                format!(
+                    " >>> {}: {} every {} ms",
+                    name,
+                    interval.source,
+                    interval.period.as_millis()
+                )
+            })
+            .collect::<Vec<_>>();
+
+        intervals.sort();
+
+        for line in intervals {
+            self.append_output(&line);
+        }
+    }
+
+    /// Compiles `fn_name` as a node color function, and runs it over
+    /// all nodes `iterations` times on the rayon pool, discarding the
+    /// output. The per-node timings are printed to the console when
+    /// done.
+    ///
+    /// Stops early if a single pass takes longer than
+    /// `BENCHMARK_PASS_LIMIT`.
+    fn benchmark_overlay(
+        &mut self,
+        reactor: &mut Reactor,
+        fn_name: &str,
+        iterations: usize,
+    ) -> Result<()> {
+        const BENCHMARK_PASS_LIMIT: std::time::Duration =
+            std::time::Duration::from_secs(2);
+
+        let mut scope = Self::create_scope();
+        scope
+            .push("graph", self.graph.graph.clone())
+            .push("path_pos", self.graph.path_positions.clone());
+
+        let engine = self.create_engine();
+
+        let script = format!("\nfn node_color(i) {{\n{}(i);\n}}", fn_name);
+        let node_color_ast =
+            engine.compile_into_self_contained(&scope, &script)?;
+
+        let config = ScriptConfig {
+            default_color: rgb::RGBA::new(0.3, 0.3, 0.3, 0.3),
+            target: ScriptTarget::Nodes,
+        };
+
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let result_tx = self.result_tx.clone();
+
+        let node_count = graph.node_count().max(1);
+        let fn_name = fn_name.to_string();
+
+        self.append_output(&format!(
+            " >>> benchmarking '{}' over {} nodes, {} iterations",
+            fn_name, node_count, iterations
+        ));
+
+        reactor.spawn_forget(async move {
+            let mut pass_times: Vec<std::time::Duration> =
+                Vec::with_capacity(iterations);
+
+            for _ in 0..iterations {
+                let start = std::time::Instant::now();
+
+                let result = overlay_colors_tgt_ast(
+                    &rayon_pool,
+                    &config,
+                    &graph,
+                    &engine,
+                    scope.clone(),
+                    node_color_ast.clone(),
+                    None,
+                );
+
+                let elapsed = start.elapsed();
+
+                if let Err(err) = result {
+                    let _ = result_tx.send(Err(err));
+                    return;
+                }
+
+                pass_times.push(elapsed);
+
+                if elapsed > BENCHMARK_PASS_LIMIT {
+                    let msg = format!(
+                        " >>> benchmark pass took {:.2} s, stopping early",
+                        elapsed.as_secs_f64()
+                    );
+                    let _ = result_tx.send(Ok(msg.into()));
+                    break;
+                }
+            }
+
+            if pass_times.is_empty() {
+                return;
+            }
+
+            let per_node_us = |dur: &std::time::Duration| {
+                (dur.as_secs_f64() * 1_000_000.0) / node_count as f64
+            };
+
+            let total: f64 = pass_times.iter().map(per_node_us).sum();
+            let mean = total / pass_times.len() as f64;
+
+            let min = pass_times
+                .iter()
+                .map(per_node_us)
+                .fold(std::f64::MAX, f64::min);
+            let max = pass_times
+                .iter()
+                .map(per_node_us)
+                .fold(std::f64::MIN, f64::max);
+
+            let msg = format!(
+                " >>> '{}': {} passes, per node mean {:.3} us, min {:.3} us, max {:.3} us",
+                fn_name,
+                pass_times.len(),
+                mean,
+                min,
+                max
+            );
+            let _ = result_tx.send(Ok(msg.into()));
+        })?;
+
+        Ok(())
     }
 
     // NB: edit this to add new console commands that do *not* use the Rhai engine
@@ -773,7 +1836,53 @@ impl Console<'static> {
         reactor: &mut Reactor,
         input: &str,
     ) -> Result<bool> {
-        if input.starts_with(":clear") {
+        if input.starts_with(":help") {
+            // list the commands and functions, or describe one function
+            let fn_name = input[":help".len()..].trim();
+            let fn_name = Some(fn_name).filter(|name| !name.is_empty());
+
+            self.print_help(fn_name);
+
+            return Ok(true);
+        } else if input.starts_with(":clear_overlay_cache") {
+            // delete all overlays cached by `create_overlay_from_fn`
+            let cache_dir = self.settings.overlay_cache_dir().lock().clone();
+
+            let msg = match cache_dir.map(|dir| overlays::cache::clear(&dir)) {
+                Some(Ok(count)) => {
+                    format!(" >>> removed {} cached overlays", count)
+                }
+                Some(Err(err)) => {
+                    format!(" >>> error clearing cache: {}", err)
+                }
+                None => " >>> the overlay cache is disabled".to_string(),
+            };
+            self.output_history.push(msg);
+
+            return Ok(true);
+        } else if input.starts_with(":overlay_cache") {
+            // set the directory overlays are cached in, or disable
+            // the cache with "off"
+            let arg = input[":overlay_cache".len()..].trim();
+
+            let msg = if arg.is_empty() {
+                match self.settings.overlay_cache_dir().lock().as_ref() {
+                    Some(dir) => format!(" >>> {}", dir.display()),
+                    None => " >>> the overlay cache is disabled".to_string(),
+                }
+            } else if arg == "off" {
+                *self.settings.overlay_cache_dir().lock() = None;
+                " >>> disabled the overlay cache".to_string()
+            } else {
+                let dir = PathBuf::from(self.resolve_path(arg));
+                let msg = format!(" >>> caching overlays in {}", dir.display());
+                *self.settings.overlay_cache_dir().lock() = Some(dir);
+                msg
+            };
+            self.output_history.push(msg);
+
+            return Ok(true);
+        } else if input.starts_with(":clear") {
             // Clears the output history visible in the console GUI
 
             self.output_history.clear();
@@ -789,12 +1898,13 @@ impl Console<'static> {
             {
                 let mut modules = self.modules.lock();
                 modules.clear();
+                self.module_sources.lock().clear();
             }
 
             return Ok(true);
         } else if input.starts_with(":exec ") {
             // Execute the provided script, without importing any functions from it
-            let file_path = &self.input_line[6..].to_string();
+            let file_path = &self.resolve_path(self.input_line[6..].trim());
             let result = self.eval_file(reactor, true, &file_path);
 
             if let Err(err) = result {
@@ -808,7 +1918,7 @@ impl Console<'static> {
         } else if input.starts_with(":import ") {
             // Import the provided script module
             log::debug!("importing file");
-            let file_path = &self.input_line[8..].to_string();
+            let file_path = &self.resolve_path(self.input_line[8..].trim());
             let result = self.import_file(&file_path);
 
             if let Err(err) = result {
@@ -827,26 +1937,367 @@ impl Console<'static> {
 
             return Ok(true);
         } else if input.starts_with(":start_interval ") {
-            // run the provided script every 30ms
-            // the handle can be used with `:end_interval` to stop it
+            // run the provided script every <ms> milliseconds,
+            // defaulting to 30ms; the handle can be used with
+            // `:end_interval` to stop it
+            let mut fields = self.input_line.split_ascii_whitespace();
+
+            fields.next();
+            let file_name = fields.next().map(|file| self.resolve_path(file));
+            let handle_name = fields.next().map(String::from);
+            let period = match fields.next() {
+                Some(ms) => ms.parse::<u64>().ok(),
+                None => Some(30),
+            };
+
+            if let (Some(file), Some(handle), Some(ms)) =
+                (file_name, handle_name, period)
+            {
+                let period = std::time::Duration::from_millis(ms.max(1));
+                self.eval_file_interval(reactor, &handle, &file, period)?;
+            } else {
+                self.append_output(
+                    " >>> usage: :start_interval <file> <handle> [ms]",
+                );
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":animate_overlay ") {
+            // recompute the overlay from the node color function
+            // every <ms> milliseconds, defaulting to 100ms; stopped
+            // with `:end_interval`
             let mut fields = self.input_line.split_ascii_whitespace();
 
             fields.next();
-            let file_name = fields.next();
-            let handle_name = fields.next();
+            let fn_name = fields.next().map(String::from);
+            let handle_name = fields.next().map(String::from);
+            let period = match fields.next() {
+                Some(ms) => ms.parse::<u64>().ok(),
+                None => Some(100),
+            };
 
-            if let (Some(file), Some(handle)) = (file_name, handle_name) {
-                let file = file.to_string();
-                let handle = handle.to_string();
-                self.eval_file_interval(reactor, &handle, &file)?;
+            if let (Some(fn_name), Some(handle), Some(ms)) =
+                (fn_name, handle_name, period)
+            {
+                let period = std::time::Duration::from_millis(ms.max(1));
+                self.animate_overlay(reactor, &handle, &fn_name, period)?;
+            } else {
+                self.append_output(
+                    " >>> usage: :animate_overlay <fn_name> <handle> [ms]",
+                );
             }
 
             return Ok(true);
         } else if input.starts_with(":end_interval ") {
             // see `:start_interval`
-            let handle = &self.input_line[":end_interval ".len()..].to_string();
+            let handle =
+                self.input_line[":end_interval ".len()..].trim().to_string();
             self.stop_interval(&handle);
 
+            return Ok(true);
+        } else if input.starts_with(":list_intervals") {
+            // print the handles of all running intervals
+            self.list_intervals();
+
+            return Ok(true);
+        } else if input.starts_with(":pwd") {
+            // print the directory relative paths are resolved against
+            let msg = format!(" >>> {}", self.working_dir.lock().display());
+            self.append_output(&msg);
+
+            return Ok(true);
+        } else if input.starts_with(":cd ") {
+            // change the directory relative script and annotation
+            // file paths are resolved against
+            let dir = self.resolve_path(self.input_line[":cd ".len()..].trim());
+
+            match std::fs::canonicalize(&dir) {
+                Ok(dir) if dir.is_dir() => {
+                    let msg = format!(" >>> {}", dir.display());
+                    *self.working_dir.lock() = dir;
+                    self.append_output(&msg);
+                }
+                Ok(_) => {
+                    let msg = format!(" >>> not a directory: {}", dir);
+                    self.append_output(&msg);
+                }
+                Err(err) => {
+                    let msg =
+                        format!(" >>> error changing to {}: {}", dir, err);
+                    self.append_output(&msg);
+                }
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":abort") {
+            // stop the scripts that are still being evaluated
+            self.abort_scripts();
+
+            return Ok(true);
+        } else if input.starts_with(":save_session ") {
+            // write the view, active overlay, selection, annotation
+            // collections, and label sets to a JSON file
+            let path =
+                self.input_line[":save_session ".len()..].trim().to_string();
+
+            let shared = self.shared();
+            let result_tx = self.result_tx.clone();
+
+            reactor.spawn_forget(async move {
+                let msg = match shared.save_session(&path) {
+                    Ok(()) => format!(" >>> saved session to {}", path),
+                    Err(err) => format!(" >>> error saving session: {}", err),
+                };
+                let _ = result_tx.send(Ok(msg.into()));
+            })?;
+
+            return Ok(true);
+        } else if input.starts_with(":save_selection ") {
+            // write the selected node IDs to a file, one per line
+            let path = self.resolve_path(
+                self.input_line[":save_selection ".len()..].trim(),
+            );
+
+            let shared = self.shared();
+            let result_tx = self.result_tx.clone();
+
+            reactor.spawn_forget(async move {
+                let msg = match shared.save_selection(&path) {
+                    Ok(count) => {
+                        format!(" >>> saved {} nodes to {}", count, path)
+                    }
+                    Err(err) => format!(" >>> error saving selection: {}", err),
+                };
+                let _ = result_tx.send(Ok(msg.into()));
+            })?;
+
+            return Ok(true);
+        } else if input.starts_with(":load_selection ") {
+            // select the nodes in a file written by `:save_selection`
+            let path = self.resolve_path(
+                self.input_line[":load_selection ".len()..].trim(),
+            );
+
+            let msg = match self.shared().load_selection(&path) {
+                Ok((loaded, 0)) => format!(" >>> selected {} nodes", loaded),
+                Ok((loaded, dropped)) => format!(
+                    " >>> selected {} nodes, skipped {} not in the graph",
+                    loaded, dropped
+                ),
+                Err(err) => format!(" >>> error loading selection: {}", err),
+            };
+            self.append_output(&msg);
+
+            return Ok(true);
+        } else if input.starts_with(":load_session ") {
+            // restore a session written by `:save_session`; missing
+            // files and overlays are reported as warnings
+            let path =
+                self.input_line[":load_session ".len()..].trim().to_string();
+
+            let shared = self.shared();
+            let result_tx = self.result_tx.clone();
+
+            reactor.spawn_forget(async move {
+                let msg = match shared.load_session(&path) {
+                    Ok(warnings) => {
+                        for warning in warnings {
+                            log::warn!("load_session: {}", warning);
+                            let msg = format!(" >>> warning: {}", warning);
+                            let _ = result_tx.send(Ok(msg.into()));
+                        }
+                        format!(" >>> loaded session from {}", path)
+                    }
+                    Err(err) => format!(" >>> error loading session: {}", err),
+                };
+                let _ = result_tx.send(Ok(msg.into()));
+            })?;
+
+            return Ok(true);
+        } else if input.starts_with(":graph_report ") {
+            // write node, edge, and path counts, node length and
+            // degree statistics, to a text file
+            let path =
+                self.input_line[":graph_report ".len()..].trim().to_string();
+
+            let graph = self.graph.clone();
+            let rayon_pool = reactor.rayon_pool.clone();
+            let result_tx = self.result_tx.clone();
+
+            reactor.spawn_forget(async move {
+                let report = graph.graph_report(&rayon_pool);
+
+                let result = std::fs::File::create(&path).and_then(|file| {
+                    use std::io::Write;
+
+                    let mut out = std::io::BufWriter::new(file);
+                    report.write(&mut out)?;
+                    out.flush()
+                });
+
+                let msg = match result {
+                    Ok(()) => format!(" >>> wrote graph report to {}", path),
+                    Err(err) => {
+                        format!(" >>> error writing graph report: {}", err)
+                    }
+                };
+                let _ = result_tx.send(Ok(msg.into()));
+            })?;
+
+            return Ok(true);
+        } else if input.starts_with(":reload_graph ") {
+            // replace the loaded graph with another GFA file, using
+            // the layout file if the GFA lacks layout tags
+            let mut fields = self.input_line.split_ascii_whitespace();
+
+            fields.next();
+            let gfa = fields.next().map(String::from);
+            let layout = fields.next().map(String::from);
+
+            if let Some(gfa) = gfa {
+                self.append_output(&format!(
+                    " >>> reloading graph from {}",
+                    gfa
+                ));
+                let msg = AppMsg::ReloadGraph { gfa, layout };
+                self.channels.app_tx.send(msg)?;
+            } else {
+                self.append_output(" >>> usage: :reload_graph <gfa> [layout]");
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":load_overlay_graph ") {
+            // load another GFA file and draw it on top of the main
+            // graph, e.g. to compare assemblies
+            let mut fields = self.input_line.split_ascii_whitespace();
+
+            fields.next();
+            let gfa = fields.next().map(String::from);
+            let layout = fields.next().map(String::from);
+
+            if let Some(gfa) = gfa {
+                self.append_output(&format!(
+                    " >>> loading overlaid graph from {}",
+                    gfa
+                ));
+                let msg = AppMsg::LoadOverlayGraph { gfa, layout };
+                self.channels.app_tx.send(msg)?;
+            } else {
+                self.append_output(
+                    " >>> usage: :load_overlay_graph <gfa> [layout]",
+                );
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":toggle_overlay_graph ") {
+            // show or hide an overlaid graph, by the order it was loaded in
+            let index = self.input_line[":toggle_overlay_graph ".len()..]
+                .trim()
+                .parse::<usize>();
+
+            if let Ok(index) = index {
+                let msg = AppMsg::ToggleOverlayGraph(index);
+                self.channels.app_tx.send(msg)?;
+            } else {
+                self.append_output(" >>> usage: :toggle_overlay_graph <index>");
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":overlay_graph_offset ") {
+            // move an overlaid graph relative to the main graph
+            let fields = self.input_line.split_ascii_whitespace().skip(1);
+            let fields = fields.collect::<Vec<_>>();
+
+            let parsed = match fields.as_slice() {
+                [index, x, y] => index
+                    .parse::<usize>()
+                    .ok()
+                    .zip(x.parse::<f32>().ok())
+                    .zip(y.parse::<f32>().ok()),
+                _ => None,
+            };
+
+            if let Some(((index, x), y)) = parsed {
+                let offset = Point::new(x, y);
+                let msg = AppMsg::SetOverlayGraphOffset { index, offset };
+                self.channels.app_tx.send(msg)?;
+            } else {
+                self.append_output(
+                    " >>> usage: :overlay_graph_offset <index> <x> <y>",
+                );
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":copy_view") {
+            // copy the current view to the clipboard, as a line that
+            // restores it when evaluated in the console
+            let view = self.shared_state.view();
+            let line = format!(
+                "set_view_origin(Point({:?}, {:?})); set_scale({:?});",
+                view.center.x, view.center.y, view.scale
+            );
+
+            let copied = ClipboardProvider::new().and_then(
+                |mut clipboard: ClipboardContext| {
+                    clipboard.set_contents(line.clone())
+                },
+            );
+
+            let msg = match copied {
+                Ok(_) => format!(" >>> copied: {}", line),
+                Err(err) => format!(" >>> error copying view: {}", err),
+            };
+            self.append_output(&msg);
+
+            return Ok(true);
+        } else if input.starts_with(":listen ") {
+            // accept scripts over TCP on localhost, see `Console::listen`
+            let port =
+                self.input_line[":listen ".len()..].trim().parse::<u16>();
+
+            match port {
+                Ok(port) => {
+                    if let Err(err) = self.listen(port) {
+                        let msg = format!(" >>> error listening: {}", err);
+                        self.append_output(&msg);
+                    }
+                }
+                Err(_) => self.append_output(" >>> usage: :listen <port>"),
+            }
+
+            return Ok(true);
+        } else if input.starts_with(":record_script ") {
+            // append every following input line to the given file,
+            // until `:stop_record`
+            let path = self.input_line[":record_script ".len()..]
+                .trim()
+                .to_string();
+            self.start_recording(&path);
+
+            return Ok(true);
+        } else if input.starts_with(":stop_record") {
+            // see `:record_script`
+            self.stop_recording();
+
+            return Ok(true);
+        } else if input.starts_with(":benchmark_overlay ") {
+            // time repeated runs of an overlay script without
+            // creating the overlay
+            let mut fields = self.input_line.split_ascii_whitespace();
+
+            fields.next();
+            let fn_name = fields.next().map(String::from);
+            let iterations =
+                fields.next().and_then(|s| s.parse::<usize>().ok());
+
+            if let (Some(fn_name), Some(iterations)) = (fn_name, iterations) {
+                self.benchmark_overlay(reactor, &fn_name, iterations)?;
+            } else {
+                self.append_output(
+                    " >>> usage: :benchmark_overlay <fn_name> <iterations>",
+                );
+            }
+
             return Ok(true);
         }
 
@@ -893,10 +2344,80 @@ impl Console<'static> {
         Ok(())
     }
 
+    fn start_recording(&mut self, path: &str) {
+        if let Some((old_path, _)) = &self.recording {
+            let msg = format!(" >>> already recording to {}", old_path);
+            self.append_output(&msg);
+            return;
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path);
+
+        match file {
+            Ok(file) => {
+                self.recording = Some((path.to_string(), file));
+                self.append_output(&format!(" >>> recording to {}", path));
+            }
+            Err(err) => {
+                let msg = format!(" >>> error recording to {}: {}", path, err);
+                self.append_output(&msg);
+            }
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some((path, _)) = self.recording.take() {
+            self.append_output(&format!(" >>> stopped recording to {}", path));
+        } else {
+            self.append_output(" >>> not recording");
+        }
+    }
+
+    /// Appends an input line to the file being recorded to, if any.
+    ///
+    /// The file is meant to be run with `:exec`, so statements are
+    /// terminated with `;` if needed, and console commands, which
+    /// `:exec` can't run, are written as comments.
+    fn record_input(&mut self, line: &str) {
+        use std::io::Write;
+
+        let trimmed = line.trim();
+
+        if trimmed.is_empty()
+            || trimmed.starts_with(":record")
+            || trimmed.starts_with(":stop_record")
+        {
+            return;
+        }
+
+        let (path, file) = match self.recording.as_mut() {
+            Some(recording) => recording,
+            None => return,
+        };
+
+        let result = if trimmed.starts_with(':') {
+            writeln!(file, "// {}", trimmed)
+        } else if trimmed.ends_with(';') || trimmed.ends_with('}') {
+            writeln!(file, "{}", line.trim_end())
+        } else {
+            writeln!(file, "{};", line.trim_end())
+        };
+
+        if let Err(err) = result {
+            let msg = format!(" >>> error recording to {}: {}", path, err);
+            self.recording = None;
+            self.append_output(&msg);
+        }
+    }
+
     pub fn import_file(&mut self, file: &str) -> Result<()> {
         let engine = self.create_engine();
 
-        let ast = engine.compile_file(file.into())?;
+        let source = std::fs::read_to_string(file)?;
+        let ast = engine.compile(&source)?;
         let module =
             rhai::Module::eval_ast_as_new(rhai::Scope::new(), &ast, &engine)?;
 
@@ -909,27 +2430,94 @@ impl Console<'static> {
         {
             let mut modules = self.modules.lock();
             modules.push(Arc::new(module));
+            self.module_sources.lock().push(source);
         }
 
         Ok(())
     }
 
+    /// Imports every `.rhai` file in `dir` using `import_file`, in
+    /// file name order. A file that fails to import is reported in
+    /// the console output, and doesn't stop the rest from loading.
+    pub fn import_dir(&mut self, dir: &std::path::Path) -> Result<()> {
+        let mut files = std::fs::read_dir(dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let is_rhai = path.extension().map_or(false, |e| e == "rhai");
+                (path.is_file() && is_rhai).then(|| path)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let mut loaded = 0;
+
+        for path in files.iter() {
+            let file = path.to_string_lossy();
+
+            match self.import_file(&file) {
+                Ok(()) => loaded += 1,
+                Err(err) => {
+                    let msg = format!(
+                        " >>> error importing file {}: {:?}",
+                        file, err
+                    );
+                    self.append_output(&msg);
+
+                    log::warn!("importing file '{}' failed: {:?}", file, err);
+                }
+            }
+        }
+
+        let msg = format!(
+            " >>> loaded {} of {} modules from '{}'",
+            loaded,
+            files.len(),
+            dir.display()
+        );
+        self.append_output(&msg);
+
+        Ok(())
+    }
+
     pub fn eval(&mut self, reactor: &mut Reactor, _print: bool) -> Result<()> {
-        let engine = self.create_engine();
+        let mut engine = self.create_engine();
+
+        let (script_id, cancelled) = self.running_scripts.start();
+
+        engine.on_progress(move |_| {
+            if cancelled.load() {
+                Some(rhai::Dynamic::UNIT)
+            } else {
+                None
+            }
+        });
 
         let result_tx = self.result_tx.clone();
 
         let input = self.input_line.to_string();
 
         let scope = self.scope.clone();
+        let running_scripts = self.running_scripts.clone();
 
-        let handle = reactor.spawn(async move {
+        let spawned = reactor.spawn(async move {
             let mut scope = scope.lock();
 
             let result =
                 engine.eval_with_scope::<rhai::Dynamic>(&mut scope, &input);
+            running_scripts.finish(script_id);
             let _ = result_tx.send(result);
-        })?;
+        });
+
+        // the script is registered before it's spawned, since it may
+        // finish before `spawn` returns, so it's unregistered here if
+        // it couldn't be spawned
+        let handle = match spawned {
+            Ok(handle) => handle,
+            Err(err) => {
+                self.running_scripts.finish(script_id);
+                return Err(err);
+            }
+        };
 
         handle.forget();
 
@@ -958,151 +2546,255 @@ impl Console<'static> {
             return;
         }
 
-        egui::Window::new(Self::ID)
-            .resizable(false)
-            .auto_sized()
+        let console_lines = self.settings.console_lines().load().max(1);
+
+        let row_height = ctx.fonts()[egui::TextStyle::Monospace].row_height()
+            + ctx.style().spacing.item_spacing.y;
+
+        let input_height = |rows: usize, spacing: &egui::style::Spacing| {
+            rows as f32 * row_height + 2.0 * spacing.button_padding.y
+        };
+
+        // the extra half row keeps the line count stable when the
+        // height is derived from it
+        let lines_height = (console_lines as f32 + 0.5) * row_height
+            + input_height(1, &ctx.style().spacing);
+
+        let mut window = egui::Window::new(Self::ID)
+            .id(egui::Id::new(Self::ID))
+            .resizable(true)
+            .default_height(lines_height)
             .title_bar(false)
             .collapsible(false)
             .enabled(is_down)
-            .anchor(egui::Align2::CENTER_TOP, Point::new(0.0, 0.0))
-            .show(ctx, |ui| {
-                ui.set_width(ctx.input().screen_rect().width());
+            .anchor(egui::Align2::CENTER_TOP, Point::new(0.0, 0.0));
+
+        // `console_lines` was set from a script, so the window is
+        // pinned to the new height for this frame, after which the
+        // resize state keeps it
+        if console_lines != self.sized_lines && !self.resizing {
+            self.sized_lines = console_lines;
+            window = window.resize(|resize| {
+                resize
+                    .min_size(egui::vec2(0.0, lines_height))
+                    .max_size(egui::vec2(f32::INFINITY, lines_height))
+            });
+        }
+
+        window.show(ctx, |ui| {
+            ui.set_width(ctx.input().screen_rect().width());
+
+            let scope_locked = self.scope.is_locked();
+
+            let input_rows = self.input_line.lines().count().max(1);
+            let output_height = ui.available_height()
+                - input_height(input_rows, ui.spacing());
+            let lines_fit =
+                ((output_height / row_height).floor() as usize).max(1);
+
+            // only store the new line count once the user is done
+            // resizing the window
+            if ui.input().pointer.any_down() {
+                if lines_fit != self.visible_lines {
+                    self.resizing = true;
+                }
+            } else if self.resizing {
+                self.resizing = false;
+                self.settings.console_lines().store(lines_fit);
+                self.sized_lines = lines_fit;
+            }
+
+            self.visible_lines = lines_fit;
+
+            let mut output_resp: Option<egui::Response> = None;
+
+            let mut add_resp = |resp: egui::Response| {
+                if let Some(union) = output_resp.as_mut() {
+                    *union = union.union(resp);
+                } else {
+                    output_resp = Some(resp);
+                }
+            };
+
+            if let Some(search) = self.search.as_ref() {
+                let matches = self.search_matches(&search.query);
 
-                let scope_locked = self.scope.is_locked();
+                // the first row shows the search status
+                let rows = lines_fit.saturating_sub(1).max(1);
 
-                let mut output_lines = Vec::with_capacity(20);
+                let end = matches.len().saturating_sub(self.output_offset);
+                let start = end.saturating_sub(rows);
 
-                for output_line in
-                    self.output_history.iter().rev().skip(self.output_offset)
+                let status = if matches.is_empty() {
+                    format!("/{} -- no matches", search.query)
+                } else {
+                    format!(
+                        "/{} -- match {} of {} (n/N + Enter to step, / to exit)",
+                        search.query,
+                        matches.len() - search.current,
+                        matches.len()
+                    )
+                };
+                add_resp(ui.add(egui::Label::new(status).monospace().weak()));
+
+                for (ix, line) in matches[start..end].iter().enumerate() {
+                    let is_current =
+                        matches.len() - 1 - (start + ix) == search.current;
+
+                    let resp = ui
+                        .horizontal(|ui| {
+                            highlighted_line(
+                                ui,
+                                line,
+                                &search.query,
+                                is_current,
+                            )
+                        })
+                        .response;
+                    add_resp(resp);
+                }
+            } else {
+                let mut output_lines = Vec::with_capacity(lines_fit);
+
+                for output_line in self
+                    .output_history
+                    .iter()
+                    .rev()
+                    .skip(self.output_offset)
                 {
-                    if output_lines.len() >= 20 {
+                    if output_lines.len() >= lines_fit {
                         break;
                     }
 
                     let split_lines = output_line.lines().rev();
 
                     for line in split_lines {
-                        if output_lines.len() >= 20 {
+                        if output_lines.len() >= lines_fit {
                             break;
                         }
 
-                        output_lines.push(egui::Label::new(line).monospace());
+                        output_lines
+                            .push(egui::Label::new(line).monospace());
                     }
                 }
 
                 output_lines.reverse();
 
-                let mut output_resp: Option<egui::Response> = None;
-
                 for label in output_lines {
-                    let resp = ui.add(label);
-                    if let Some(union) = output_resp.as_mut() {
-                        *union = union.union(resp);
-                    } else {
-                        output_resp = Some(resp);
-                    }
+                    add_resp(ui.add(label));
                 }
+            }
 
-                if let Some(resp) = output_resp {
-                    let mut rect = resp.rect;
-                    rect.set_width(ui.available_width());
-
-                    let interact = ui.interact(
-                        rect,
-                        egui::Id::new("console_lines"),
-                        egui::Sense::hover(),
-                    );
-                    if interact.hovered() {
-                        let scroll = ui.input().scroll_delta.y;
-
-                        let mag = scroll.abs();
-                        let delta = ((mag / 4.0) as usize).max(1);
+            if let Some(resp) = output_resp {
+                let mut rect = resp.rect;
+                rect.set_width(ui.available_width());
 
-                        let mut delta = delta as isize;
-                        if scroll < 0.0 {
-                            delta *= -1;
-                        }
+                let interact = ui.interact(
+                    rect,
+                    egui::Id::new("console_lines"),
+                    egui::Sense::hover(),
+                );
+                if interact.hovered() {
+                    let scroll = ui.input().scroll_delta.y;
 
-                        if mag > 1.0 {
-                            self.scrollback(delta);
-                        }
-                    }
-                }
+                    let mag = scroll.abs();
+                    let delta = ((mag / 4.0) as usize).max(1);
 
-                let old_input = self.input_line.clone();
-
-                let input = {
-                    let line_count = self.input_line.lines().count().max(1);
-
-                    if scope_locked {
-                        let mut empty = "> Executing...".to_string();
-                        ui.add(
-                            egui::TextEdit::multiline(&mut empty)
-                                .id(egui::Id::new(Self::ID_TEXT))
-                                .desired_rows(line_count)
-                                .code_editor()
-                                .lock_focus(true)
-                                .enabled(false)
-                                .desired_width(ui.available_width()),
-                        )
-                    } else {
-                        ui.add(
-                            egui::TextEdit::multiline(&mut self.input_line)
-                                .id(egui::Id::new(Self::ID_TEXT))
-                                .desired_rows(line_count)
-                                .code_editor()
-                                .lock_focus(true)
-                                .enabled(true)
-                                .desired_width(ui.available_width()),
-                        )
+                    let mut delta = delta as isize;
+                    if scroll < 0.0 {
+                        delta *= -1;
                     }
-                };
 
-                // hack to keep input
-                if self.request_focus && !scope_locked {
-                    if input.has_focus() {
-                        self.request_focus = false;
+                    if mag > 1.0 {
+                        self.scrollback(delta);
                     }
-                    input.request_focus();
                 }
+            }
 
-                if ui.input().key_pressed(egui::Key::ArrowUp) {
-                    self.step_history(true);
+            let old_input = self.input_line.clone();
+
+            let input = {
+                let line_count = self.input_line.lines().count().max(1);
+
+                if scope_locked {
+                    let mut empty = "> Executing...".to_string();
+                    ui.add(
+                        egui::TextEdit::multiline(&mut empty)
+                            .id(egui::Id::new(Self::ID_TEXT))
+                            .desired_rows(line_count)
+                            .code_editor()
+                            .lock_focus(true)
+                            .enabled(false)
+                            .desired_width(ui.available_width()),
+                    )
+                } else {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.input_line)
+                            .id(egui::Id::new(Self::ID_TEXT))
+                            .desired_rows(line_count)
+                            .code_editor()
+                            .lock_focus(true)
+                            .enabled(true)
+                            .desired_width(ui.available_width()),
+                    )
                 }
+            };
 
-                if ui.input().key_pressed(egui::Key::ArrowDown) {
-                    self.step_history(false);
+            // hack to keep input
+            if self.request_focus && !scope_locked {
+                if input.has_focus() {
+                    self.request_focus = false;
                 }
+                input.request_focus();
+            }
 
-                if ui.input().key_pressed(egui::Key::Enter) && !scope_locked {
-                    if ui.input().modifiers.shift {
-                        // insert newline;
-                    } else {
-                        // evaluate input
-                        self.input_line = old_input;
-                        log::debug!("console input line: {}", self.input_line);
-
-                        self.input_history.push(self.input_line.clone());
-                        self.append_output(&format!("> {}", self.input_line));
+            if ui.input().key_pressed(egui::Key::ArrowUp) {
+                self.step_history(true);
+            }
 
-                        self.eval_input(reactor, true).unwrap();
+            if ui.input().key_pressed(egui::Key::ArrowDown) {
+                self.step_history(false);
+            }
 
-                        let mut line =
-                            String::with_capacity(self.input_line.capacity());
-                        std::mem::swap(&mut self.input_line, &mut line);
+            if ui.input().key_pressed(egui::Key::Enter) && !scope_locked {
+                if ui.input().modifiers.shift {
+                    // insert newline;
+                } else {
+                    // evaluate input
+                    self.input_line = old_input;
+                    log::debug!("console input line: {}", self.input_line);
 
+                    if self.search_input() {
                         self.input_line.clear();
-
                         self.input_history_ix.take();
+                        self.request_focus = true;
+                        return;
                     }
 
-                    // input.request_focus() has to be called the
-                    // frame *after* this piece of code is ran, hence
-                    // the bool etc.
-                    // input.request_focus();
-                    self.request_focus = true;
+                    self.input_history.push(self.input_line.clone());
+                    self.append_output(&format!("> {}", self.input_line));
+
+                    let line = self.input_line.clone();
+                    self.record_input(&line);
+
+                    self.eval_input(reactor, true).unwrap();
+
+                    let mut line =
+                        String::with_capacity(self.input_line.capacity());
+                    std::mem::swap(&mut self.input_line, &mut line);
+
+                    self.input_line.clear();
+
+                    self.input_history_ix.take();
                 }
-            });
+
+                // input.request_focus() has to be called the
+                // frame *after* this piece of code is ran, hence
+                // the bool etc.
+                // input.request_focus();
+                self.request_focus = true;
+            }
+        });
     }
 
     fn step_history(&mut self, backward: bool) {
@@ -1139,6 +2831,75 @@ impl Console<'static> {
         }
     }
 
+    /// Handles the input line if it's a search command, returning
+    /// `true` if it was.
+    ///
+    /// `/<query>` shows only the output lines containing the query,
+    /// with the matches highlighted; while searching, `n` and `N`
+    /// select the previous and next match, scrolling to it. `/` on
+    /// its own, or any other input, ends the search.
+    fn search_input(&mut self) -> bool {
+        let input = self.input_line.trim();
+
+        if let Some(query) = self.search.as_ref().map(|s| s.query.clone()) {
+            let match_count = self.search_matches(&query).len();
+            let rows = self.visible_lines.saturating_sub(1).max(1);
+
+            let search = self.search.as_mut().unwrap();
+
+            let step = match input {
+                "n" => Some(true),
+                "N" => Some(false),
+                _ => None,
+            };
+
+            if let Some(older) = step {
+                if match_count > 0 {
+                    search.current = if older {
+                        (search.current + 1).min(match_count - 1)
+                    } else {
+                        search.current.saturating_sub(1)
+                    };
+
+                    // scroll so that the current match is visible
+                    if search.current < self.output_offset {
+                        self.output_offset = search.current;
+                    } else if search.current >= self.output_offset + rows {
+                        self.output_offset = search.current + 1 - rows;
+                    }
+                }
+                return true;
+            }
+
+            self.search = None;
+            self.output_offset = 0;
+
+            if input == "/" {
+                return true;
+            }
+        }
+
+        if input.starts_with('/') && !input.starts_with("//") {
+            self.search = Some(OutputSearch {
+                query: input[1..].to_string(),
+                current: 0,
+            });
+            self.output_offset = 0;
+            return true;
+        }
+
+        false
+    }
+
+    /// The output lines that contain `query`, oldest first
+    fn search_matches(&self, query: &str) -> Vec<&str> {
+        self.output_history
+            .iter()
+            .flat_map(|output| output.lines())
+            .filter(|line| line.contains(query))
+            .collect()
+    }
+
     fn scrollback(&mut self, delta: isize) {
         let reverse = delta < 0;
         let delta = delta.abs() as usize;
@@ -1147,8 +2908,13 @@ impl Console<'static> {
             self.output_offset =
                 self.output_offset.checked_sub(delta).unwrap_or(0);
         } else {
+            let line_count = match self.search.as_ref() {
+                Some(search) => self.search_matches(&search.query).len(),
+                None => self.output_history.len(),
+            };
+
             let max_count =
-                self.output_history.len().checked_sub(20).unwrap_or(0);
+                line_count.checked_sub(self.visible_lines).unwrap_or(0);
 
             self.output_offset = (self.output_offset + delta).min(max_count);
         }
@@ -1199,6 +2965,15 @@ impl GetSetTruth {
         self.setters.insert(name.to_string(), Box::new(setter) as _);
     }
 
+    /// Adds a key that can be read with `get`, but not set
+    pub fn add_getter(
+        &mut self,
+        name: &str,
+        get: impl Fn() -> rhai::Dynamic + Send + Sync + 'static,
+    ) {
+        self.getters.insert(name.to_string(), Box::new(get) as _);
+    }
+
     pub fn add_dynamic<T>(
         &mut self,
         name: &str,
@@ -1335,12 +3110,67 @@ impl ConsoleShared {
             app_msg_tx.send(msg).unwrap();
         });
 
+        // selects every node with an ID in the inclusive range
+        let graph = self.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("select_range", move |start: i64, end: i64| {
+            let start = NodeId::from(start.max(1) as u64);
+            let end = NodeId::from(end.max(1) as u64);
+
+            let selection = graph.node_range_selection(start, end);
+
+            let msg = AppMsg::Selection(Select::Many {
+                nodes: selection.nodes,
+                clear: true,
+            });
+            app_msg_tx.send(msg).unwrap();
+        });
+
         let app_msg_tx = self.channels.app_tx.clone();
         engine.register_fn("pan_to_active_selection", move || {
             let msg = AppMsg::GotoSelection;
             app_msg_tx.send(msg).unwrap();
         });
 
+        // keeps the view centered on the selection as it changes,
+        // until disabled or the view is panned manually
+        let follow = self.shared_state.follow_selection.clone();
+        engine.register_fn("follow_selection", move |enabled: bool| {
+            follow.store(enabled);
+        });
+
+        // pans to and zooms the view so the entire selection fits
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("fit_selection", move || {
+            app_msg_tx.send(AppMsg::FitSelection).unwrap();
+        });
+
+        // pans to and zooms the view so the entire graph fits
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("fit_graph", move || {
+            app_msg_tx.send(AppMsg::FitGraph).unwrap();
+        });
+
+        // writes the visible part of the graph to an SVG file, with
+        // the current overlay colors; returns the number of nodes
+        // written
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn("export_svg", move |path: &str| {
+            let (tx, rx) = crossbeam::channel::bounded::<Result<usize>>(1);
+
+            let msg = AppMsg::ExportSvg {
+                path: path.to_string(),
+                sender: tx,
+            };
+            app_msg_tx.send(msg).unwrap();
+
+            match rx.recv() {
+                Ok(Ok(count)) => Ok(count as i64),
+                Ok(Err(err)) => Err(format!("{}", err).into()),
+                Err(_) => Err("Error exporting SVG".into()),
+            }
+        });
+
         let graph = self.graph.graph.clone();
         engine.register_fn(
             "path_selection",
@@ -1373,14 +3203,438 @@ impl ConsoleShared {
             }
         });
 
+        // keeps only the nodes of the selection that are on the path
+        let graph = self.graph.graph.clone();
+        engine.register_fn(
+            "selection_on_path",
+            move |selection: NodeSelection, path: PathId| {
+                selection.on_path(&graph, path)
+            },
+        );
+
+        // variant of the above that takes a path name instead of ID
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "selection_on_path",
+            move |selection: NodeSelection, path_name: &str| {
+                if let Some(path) = graph.get_path_id(path_name.as_bytes()) {
+                    Ok(selection.on_path(&graph, path))
+                } else {
+                    Err("The provided path does not exist".into())
+                }
+            },
+        );
+
+        // start walking along a path, one step at a time; selects
+        // and centers on the path's first node. the walk is advanced
+        // with `walk_path_next` and `walk_path_prev`, or the period
+        // and comma keys
+        let graph = self.graph.graph.clone();
+        let path_walk = self.path_walk.clone();
+        let app_tx = self.channels.app_tx.clone();
+        engine.register_result_fn("walk_path_start", move |path_name: &str| {
+            let path = graph
+                .get_path_id(path_name.as_bytes())
+                .ok_or("The provided path does not exist")?;
+
+            let steps = graph
+                .path_steps(path)
+                .map(|steps| {
+                    steps.map(|step| step.handle().id()).collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            let mut walk = PathWalk { steps, index: 0 };
+            let node = walk.step(&app_tx, 0).ok_or("The path is empty")?;
+
+            *path_walk.lock() = Some(walk);
+
+            Ok(node)
+        });
+
+        // these return the node at the new step, or () if no path is
+        // being walked
+        for &(name, delta) in
+            [("walk_path_next", 1), ("walk_path_prev", -1)].iter()
+        {
+            let path_walk = self.path_walk.clone();
+            let app_tx = self.channels.app_tx.clone();
+            engine.register_fn(name, move || {
+                path_walk
+                    .lock()
+                    .as_mut()
+                    .and_then(|walk| walk.step(&app_tx, delta))
+                    .map(rhai::Dynamic::from)
+                    .unwrap_or(rhai::Dynamic::UNIT)
+            });
+        }
+
+        // the nodes the path visits in the reverse orientation, e.g.
+        // to highlight inversions
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "path_reverse_steps",
+            move |path_name: &str| {
+                if let Some(path) = graph.get_path_id(path_name.as_bytes()) {
+                    let mut selection = NodeSelection::default();
+                    if let Some(steps) = graph.path_steps(path) {
+                        for step in steps {
+                            let handle = step.handle();
+                            if handle.is_reverse() {
+                                selection.add_one(false, handle.id());
+                            }
+                        }
+                    }
+                    Ok(selection)
+                } else {
+                    Err("The provided path does not exist".into())
+                }
+            },
+        );
+
+        // shows tick marks with base pair offsets along the path's
+        // layout, or hides them
+        let graph = self.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "show_path_ruler",
+            move |path_name: &str, show: bool| {
+                let path = graph
+                    .graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or("The provided path does not exist")?;
+
+                let ruler = if show {
+                    let (tx, rx) =
+                        crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+                    let msg = AppMsg::RequestData {
+                        key: "node_positions".to_string(),
+                        index: "".to_string(),
+                        sender: tx,
+                    };
+
+                    app_msg_tx.send(msg).unwrap();
+
+                    let result =
+                        std::thread::spawn(move || rx.recv().unwrap()).join();
+                    let nodes = Self::error_helper::<Vec<Node>>(&result)?;
+
+                    let ruler = PathRuler::new(&graph, &nodes, path)
+                        .ok_or("The path has no layout length")?;
+                    Some(ruler)
+                } else {
+                    None
+                };
+
+                let msg = AppMsg::SetPathRuler {
+                    name: path_name.to_string(),
+                    ruler,
+                };
+                app_msg_tx.send(msg).unwrap();
+
+                Ok(())
+            },
+        );
+
+        // the color used for the path by everything that colors by
+        // path, such as edge coloring
+        let graph = self.graph.graph.clone();
+        let path_colors = self.settings.path_colors().clone();
+        engine.register_result_fn("path_color", move |path: PathId| {
+            let name = graph
+                .get_path_name_vec(path)
+                .ok_or("The provided path does not exist")?;
+            Ok(path_colors.color(&name))
+        });
+
+        let path_colors = self.settings.path_colors().clone();
+        engine.register_fn("path_color", move |path_name: &str| {
+            path_colors.color(path_name.as_bytes())
+        });
+
+        // overrides the color of a path; the edge colors and path
+        // ribbons are updated on the next frame
+        let graph = self.graph.graph.clone();
+        let path_colors = self.settings.path_colors().clone();
+        engine.register_result_fn(
+            "set_path_color",
+            move |path_name: &str, color: rgb::RGB<f32>| {
+                if graph.get_path_id(path_name.as_bytes()).is_none() {
+                    return Err("The provided path does not exist".into());
+                }
+                path_colors.set_override(path_name.as_bytes(), Some(color));
+                Ok(())
+            },
+        );
+
+        let path_colors = self.settings.path_colors().clone();
+        engine.register_fn("clear_path_color", move |path_name: &str| {
+            path_colors.set_override(path_name.as_bytes(), None);
+        });
+
+        // the names of all paths in the graph, in path ID order
+        let graph = self.graph.graph.clone();
+        engine.register_fn("path_names", move || {
+            let mut path_ids = graph.path_ids().collect::<Vec<_>>();
+            path_ids.sort();
+
+            path_ids
+                .into_iter()
+                .filter_map(|path| {
+                    let name = graph.get_path_name_vec(path)?;
+                    Some(rhai::Dynamic::from(format!("{}", name.as_bstr())))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // the number of steps in the path
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "path_node_count",
+            move |path: rhai::Dynamic| -> std::result::Result<
+                i64,
+                Box<EvalAltResult>,
+            > {
+                let path = dynamic_path_id(&graph, path)?;
+                Ok(graph.path_len(path).unwrap_or(0) as i64)
+            },
+        );
+
+        // Jaccard similarity of the node sets of two paths; the
+        // paths can be given as path IDs or names
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "path_overlap",
+            move |path_a: rhai::Dynamic,
+                  path_b: rhai::Dynamic|
+                  -> std::result::Result<f32, Box<EvalAltResult>> {
+                let path_a = dynamic_path_id(&graph, path_a)?;
+                let path_b = dynamic_path_id(&graph, path_b)?;
+
+                let a = path_node_set(&graph, path_a);
+                let b = path_node_set(&graph, path_b);
+
+                Ok(jaccard_similarity(&a, &b))
+            },
+        );
+
+        // the pairwise Jaccard similarities of all paths in the
+        // array, as a flat row-major array of length `paths.len()^2`
+        let graph = self.graph.graph.clone();
+        let rayon_pool = self.rayon_pool.clone();
+        engine.register_result_fn(
+            "path_overlap_matrix",
+            move |paths: Vec<rhai::Dynamic>| -> std::result::Result<
+                Vec<rhai::Dynamic>,
+                Box<EvalAltResult>,
+            > {
+                use rayon::prelude::*;
+
+                let paths = paths
+                    .into_iter()
+                    .map(|path| dynamic_path_id(&graph, path))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+
+                let n = paths.len();
+
+                let matrix = rayon_pool.install(|| {
+                    let node_sets = paths
+                        .par_iter()
+                        .map(|&path| path_node_set(&graph, path))
+                        .collect::<Vec<_>>();
+
+                    (0..n * n)
+                        .into_par_iter()
+                        .map(|ix| {
+                            let (row, col) = (ix / n, ix % n);
+                            jaccard_similarity(&node_sets[row], &node_sets[col])
+                        })
+                        .collect::<Vec<f32>>()
+                });
+
+                Ok(matrix.into_iter().map(rhai::Dynamic::from).collect())
+            },
+        );
+
+        // per-node values that persist between scripts, e.g. to be
+        // read by an overlay function; `get_node_data` returns `()`
+        // if the key isn't set for the node
+        let node_data = self.shared_state.node_data.clone();
+        engine.register_fn(
+            "set_node_data",
+            move |node: NodeId, key: &str, value: rhai::Dynamic| {
+                let mut data = node_data.write();
+                data.insert((node, key.to_string()), value);
+            },
+        );
+
+        let node_data = self.shared_state.node_data.clone();
+        engine.register_fn(
+            "set_node_data",
+            move |node: i64, key: &str, value: rhai::Dynamic| {
+                let node = NodeId::from(node as u64);
+                let mut data = node_data.write();
+                data.insert((node, key.to_string()), value);
+            },
+        );
+
+        let node_data = self.shared_state.node_data.clone();
+        engine.register_fn("get_node_data", move |node: NodeId, key: &str| {
+            let data = node_data.read();
+            data.get(&(node, key.to_string()))
+                .cloned()
+                .unwrap_or(rhai::Dynamic::UNIT)
+        });
+
+        let node_data = self.shared_state.node_data.clone();
+        engine.register_fn("get_node_data", move |node: i64, key: &str| {
+            let node = NodeId::from(node as u64);
+            let data = node_data.read();
+            data.get(&(node, key.to_string()))
+                .cloned()
+                .unwrap_or(rhai::Dynamic::UNIT)
+        });
+
+        // removes the value for `key` from every node
+        let node_data = self.shared_state.node_data.clone();
+        engine.register_fn("clear_node_data", move |key: &str| {
+            let mut data = node_data.write();
+            data.retain(|(_, k), _| k != key);
+        });
+
+        // the neighbors of the forward handle of a node, as an array
+        // of `[neighbor_id, is_reverse]` pairs; `neighbors_in` and
+        // `neighbors_out` only follow the edges on the left and right
+        // side of the node, respectively
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "neighbors",
+            move |node: rhai::Dynamic| -> std::result::Result<
+                rhai::Array,
+                Box<EvalAltResult>,
+            > {
+                let node = dynamic_node_id(&graph, node)?;
+                let dirs = [Direction::Left, Direction::Right];
+                Ok(node_neighbors(&graph, node, &dirs))
+            },
+        );
+
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "neighbors_in",
+            move |node: rhai::Dynamic| -> std::result::Result<
+                rhai::Array,
+                Box<EvalAltResult>,
+            > {
+                let node = dynamic_node_id(&graph, node)?;
+                Ok(node_neighbors(&graph, node, &[Direction::Left]))
+            },
+        );
+
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "neighbors_out",
+            move |node: rhai::Dynamic| -> std::result::Result<
+                rhai::Array,
+                Box<EvalAltResult>,
+            > {
+                let node = dynamic_node_id(&graph, node)?;
+                Ok(node_neighbors(&graph, node, &[Direction::Right]))
+            },
+        );
+
+        // the IDs of the paths that pass through a node
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "node_paths",
+            move |node: rhai::Dynamic| -> std::result::Result<
+                rhai::Array,
+                Box<EvalAltResult>,
+            > {
+                let node = dynamic_node_id(&graph.graph, node)?;
+                Ok(graph
+                    .node_paths(node)
+                    .into_iter()
+                    .map(rhai::Dynamic::from)
+                    .collect())
+            },
+        );
+
+        // whether a path, given by name or ID, passes through a node
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "node_on_path",
+            move |node: rhai::Dynamic,
+                  path_name: &str|
+                  -> std::result::Result<bool, Box<EvalAltResult>> {
+                let node = dynamic_node_id(&graph.graph, node)?;
+                let path = graph
+                    .graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or(format!("Path not found: {}", path_name))?;
+                Ok(graph.node_on_path(node, path))
+            },
+        );
+
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "node_on_path",
+            move |node: rhai::Dynamic,
+                  path: PathId|
+                  -> std::result::Result<bool, Box<EvalAltResult>> {
+                let node = dynamic_node_id(&graph.graph, node)?;
+                Ok(graph.node_on_path(node, path))
+            },
+        );
+
         let arc = self.shared_state.hover_node.clone();
         engine.register_fn("get_hover_node", move || arc.load());
 
+        // a map with the `id`, `len`, `degree`, and `coverage` (the
+        // number of path steps) of the hovered node, or unit if no
+        // node is hovered
+        let arc = self.shared_state.hover_node.clone();
+        let graph = self.graph.graph.clone();
+        engine.register_fn("hover_node_info", move || {
+            let node = match arc.load() {
+                Some(node) if graph.has_node(node) => node,
+                _ => return rhai::Dynamic::UNIT,
+            };
+
+            let handle = Handle::pack(node, false);
+
+            let degree = graph.degree(handle, Direction::Left)
+                + graph.degree(handle, Direction::Right);
+
+            let coverage = graph
+                .steps_on_handle(handle)
+                .map(|steps| steps.count())
+                .unwrap_or(0);
+
+            let mut info = rhai::Map::new();
+            info.insert("id".into(), rhai::Dynamic::from(node));
+            info.insert("len".into(), (graph.node_len(handle) as i64).into());
+            info.insert("degree".into(), (degree as i64).into());
+            info.insert("coverage".into(), (coverage as i64).into());
+
+            rhai::Dynamic::from(info)
+        });
+
         let app_msg_tx = self.channels.app_tx.clone();
         engine.register_fn("toggle_dark_mode", move || {
             app_msg_tx.send(crate::app::AppMsg::ToggleDarkMode).unwrap();
         });
 
+        // stops updating the view info and stats panels, so their
+        // values can be read while the view moves
+        let gui_tx = self.channels.gui_tx.clone();
+        engine.register_fn("freeze_stats", move |freeze: bool| {
+            gui_tx
+                .send(crate::gui::GuiMsg::FreezeStats(Some(freeze)))
+                .unwrap();
+        });
+
         // Actually add the `get` and `set` functions, see Console::new as well
         let get_set = self.get_set.clone();
         engine.register_result_fn("get", move |name: &str| {
@@ -1419,8 +3673,8 @@ impl ConsoleShared {
 
         let handle = exported_module!(crate::script::plugins::handle_plugin);
 
-        // TODO it's probably a bad idea to have this without a way to
-        // cancel/abort running scripts
+        // a script can only be aborted (see `:abort`) between
+        // function calls, so a long sleep delays aborting it
         engine.register_fn("sleep", |ms: i64| {
             let dur = std::time::Duration::from_millis(ms as u64);
             std::thread::sleep(dur);
@@ -1563,6 +3817,18 @@ impl ConsoleShared {
             }
         });
 
+        // pins an overlay to be drawn under the active one, so that
+        // a translucent active overlay is composited on top of it;
+        // `()` removes the base overlay
+        let overlay_state = self.shared_state.overlay_state.clone();
+        engine.register_fn("set_base_overlay", move |v: rhai::Dynamic| {
+            if let Ok(_) = v.as_unit() {
+                overlay_state.set_base_overlay(None);
+            } else if let Some(overlay) = v.try_cast::<usize>() {
+                overlay_state.set_base_overlay(Some(overlay));
+            }
+        });
+
         let overlay_list: Arc<_> = self.overlay_list.clone();
         // let overlay_map: Arc<HashMap<String, (usize, OverlayKind)>> =
         engine.register_fn("get_overlays", move || {
@@ -1584,6 +3850,213 @@ impl ConsoleShared {
             "overlay_id",
             move |overlay: (usize, OverlayKind, String)| (overlay.0, overlay.1),
         );
+
+        // colors each node by how many times it's visited by any
+        // path, counting repeated visits by the same path
+        let graph = self.graph.graph.clone();
+        let rayon_pool = self.rayon_pool.clone();
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_fn("overlay_coverage", move || {
+            let (data, min, max) =
+                rayon_pool.install(|| node_coverage_values(&graph));
+
+            let msg = OverlayCreatorMsg::NewOverlay {
+                name: "Coverage".to_string(),
+                data: OverlayData::Value(data),
+                legend: Some(OverlayLegend::Range {
+                    min: min as f32,
+                    max: max as f32,
+                }),
+            };
+            overlay_tx.send(msg).unwrap();
+        });
+
+        // paint nodes by hand; the colors are written directly into
+        // the "Manual" overlay, which is created if needed
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_fn(
+            "set_node_color",
+            move |node: NodeId, color: rgb::RGBA<f32>| {
+                let msg = OverlayCreatorMsg::SetNodeColors {
+                    name: MANUAL_OVERLAY_NAME.to_string(),
+                    colors: vec![(node, color)],
+                };
+                overlay_tx.send(msg).unwrap();
+            },
+        );
+
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_fn(
+            "set_node_color",
+            move |node: i64, color: rgb::RGBA<f32>| {
+                let msg = OverlayCreatorMsg::SetNodeColors {
+                    name: MANUAL_OVERLAY_NAME.to_string(),
+                    colors: vec![(NodeId::from(node as u64), color)],
+                };
+                overlay_tx.send(msg).unwrap();
+            },
+        );
+
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_fn(
+            "set_nodes_color",
+            move |selection: NodeSelection, color: rgb::RGBA<f32>| {
+                let msg = OverlayCreatorMsg::SetNodeColors {
+                    name: MANUAL_OVERLAY_NAME.to_string(),
+                    colors: selection
+                        .nodes
+                        .into_iter()
+                        .map(|node| (node, color))
+                        .collect(),
+                };
+                overlay_tx.send(msg).unwrap();
+            },
+        );
+
+        // colors the nodes covered by the CDS records in a GFF3
+        // collection by their reading frame along the given path
+        let graph = self.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_result_fn(
+            "overlay_reading_frame",
+            move |collection: &str,
+                  path_name: &str|
+                  -> std::result::Result<(), Box<EvalAltResult>> {
+                let (tx, rx) =
+                    crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+                let msg = AppMsg::RequestData {
+                    key: "annotation_file".to_string(),
+                    index: collection.to_string(),
+                    sender: tx,
+                };
+                app_msg_tx.send(msg).unwrap();
+
+                let records = rx
+                    .recv()
+                    .unwrap()
+                    .map_err(|err| err.to_string())?
+                    .try_cast::<Arc<Gff3Records>>()
+                    .ok_or("Reading frames require a GFF3 collection")?;
+
+                let path_id = graph
+                    .graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or_else(|| format!("Path not found: {}", path_name))?;
+
+                let data =
+                    reading_frame_colors(&graph, &records, path_id, path_name)
+                        .ok_or("Could not get path positions")?;
+
+                let legend = READING_FRAME_COLORS
+                    .iter()
+                    .enumerate()
+                    .map(|(frame, &color)| (format!("Frame {}", frame), color))
+                    .collect();
+
+                let msg = OverlayCreatorMsg::NewOverlay {
+                    name: format!("Reading frame ({})", collection),
+                    data: OverlayData::RGB(data),
+                    legend: Some(OverlayLegend::Categories(legend)),
+                };
+                overlay_tx.send(msg).unwrap();
+
+                Ok(())
+            },
+        );
+
+        // registers a colormap that interpolates linearly between the
+        // colors in the array, for use with `overlay_from_column` and
+        // `colormap_color`; it can't be used as the gradient of value
+        // overlays, which is limited to the builtin gradients
+        let colormaps = self.shared_state.colormaps.clone();
+        engine.register_result_fn(
+            "register_colormap",
+            move |name: &str, colors: rhai::Array| {
+                let colors = colors
+                    .into_iter()
+                    .map(|color| {
+                        if let Some(rgb) =
+                            color.clone().try_cast::<rgb::RGB<f32>>()
+                        {
+                            Some(rgb::RGBA::new(rgb.r, rgb.g, rgb.b, 1.0))
+                        } else {
+                            color.try_cast::<rgb::RGBA<f32>>()
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or("Colormap colors must be RGB or RGBA colors")?;
+
+                let colormap = overlays::Colormap::new(colors)
+                    .ok_or("A colormap needs at least one color")?;
+                colormaps.insert(name, colormap);
+
+                Ok(())
+            },
+        );
+
+        // samples a registered colormap, or builtin gradient, at `t`
+        // in [0, 1]
+        let colormaps = self.shared_state.colormaps.clone();
+        engine.register_result_fn(
+            "colormap_color",
+            move |name: &str,
+                  t: f32|
+                  -> std::result::Result<_, Box<EvalAltResult>> {
+                let colormap = colormaps
+                    .get(name)
+                    .ok_or_else(|| format!("Colormap not found: {}", name))?;
+                Ok(colormap.sample(t))
+            },
+        );
+
+        // colors the nodes covered by the records by the numeric
+        // value of the column, normalized over the records and mapped
+        // through the colormap
+        let graph = self.graph.clone();
+        let colormaps = self.shared_state.colormaps.clone();
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_result_fn(
+            "overlay_from_column",
+            move |records: &mut Arc<Gff3Records>,
+                  column: Gff3Column,
+                  path: PathId,
+                  colormap: &str| {
+                let msg = column_value_overlay(
+                    &graph,
+                    records.as_ref(),
+                    &column,
+                    path,
+                    &colormaps,
+                    colormap,
+                )?;
+                overlay_tx.send(msg).unwrap();
+                Ok(())
+            },
+        );
+
+        let graph = self.graph.clone();
+        let colormaps = self.shared_state.colormaps.clone();
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_result_fn(
+            "overlay_from_column",
+            move |records: &mut Arc<BedRecords>,
+                  column: BedColumn,
+                  path: PathId,
+                  colormap: &str| {
+                let msg = column_value_overlay(
+                    &graph,
+                    records.as_ref(),
+                    &column,
+                    path,
+                    &colormaps,
+                    colormap,
+                )?;
+                overlay_tx.send(msg).unwrap();
+                Ok(())
+            },
+        );
     }
 
     fn add_view_fns(&self, engine: &mut Engine) {
@@ -1600,15 +4073,17 @@ impl ConsoleShared {
             |v, c| v.center = c,
         );
 
-        // NB: these are not regular console get/sets because the view
-        // system will likely be reworked soon, to make it a queue --
-        // and remove direct mutable access by other systems
-        // (but for now, load/store is enough)
+        // NB: these are not regular console get/sets because changes
+        // to the view are queued (see `ViewUpdate`), and applied by
+        // the main view once per frame; `get_view` returns the view
+        // as of the last frame
         let view = self.shared_state.view.clone();
         engine.register_fn("get_view", move || view.load());
 
-        let view = self.shared_state.view.clone();
-        engine.register_fn("set_view", move |v: View| view.store(v));
+        let view_tx = self.channels.view_update_tx.clone();
+        engine.register_fn("set_view", move |v: View| {
+            view_tx.send(ViewUpdate::SetFull(v)).unwrap();
+        });
 
         let app_msg_tx = self.channels.app_tx.clone();
         engine.register_fn("goto_node", move |node: NodeId| {
@@ -1627,18 +4102,78 @@ impl ConsoleShared {
             app_msg_tx.send(msg).unwrap();
         });
 
-        let view = self.shared_state.view.clone();
+        // like goto_node, but also zooms so that the node takes up a
+        // quarter of the screen
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("zoom_to_node", move |node: NodeId| {
+            app_msg_tx.send(AppMsg::ZoomToNode(node)).unwrap();
+
+            let msg = AppMsg::Selection(Select::One { node, clear: true });
+            app_msg_tx.send(msg).unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("zoom_to_node", move |node: i64| {
+            let node = NodeId::from(node as u64);
+            app_msg_tx.send(AppMsg::ZoomToNode(node)).unwrap();
+
+            let msg = AppMsg::Selection(Select::One { node, clear: true });
+            app_msg_tx.send(msg).unwrap();
+        });
+
+        // jumps to (and selects) a node picked uniformly at random,
+        // returning its ID; the seeded version is reproducible
+        let graph = self.graph.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn("goto_random_node", move || {
+            let mut rng = rand::thread_rng();
+            let node = random_node(&graph, &mut rng)
+                .ok_or("The graph has no nodes")?;
+            app_msg_tx.send(AppMsg::GotoNode(node)).unwrap();
+
+            let msg = AppMsg::Selection(Select::One { node, clear: true });
+            app_msg_tx.send(msg).unwrap();
+            Ok(node)
+        });
+
+        let graph = self.graph.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn("goto_random_node", move |seed: i64| {
+            use rand::SeedableRng;
+
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed as u64);
+            let node = random_node(&graph, &mut rng)
+                .ok_or("The graph has no nodes")?;
+            app_msg_tx.send(AppMsg::GotoNode(node)).unwrap();
+
+            let msg = AppMsg::Selection(Select::One { node, clear: true });
+            app_msg_tx.send(msg).unwrap();
+            Ok(node)
+        });
+
+        let view_tx = self.channels.view_update_tx.clone();
         engine.register_fn("set_view_origin", move |p: Point| {
-            let mut v = view.load();
-            v.center = p;
-            view.store(v);
+            view_tx.send(ViewUpdate::SetCenter(p)).unwrap();
         });
 
-        let view = self.shared_state.view.clone();
+        let view_tx = self.channels.view_update_tx.clone();
         engine.register_fn("set_scale", move |s: f32| {
-            let mut v = view.load();
-            v.scale = s;
-            view.store(v);
+            view_tx.send(ViewUpdate::SetScale(s)).unwrap();
+        });
+
+        // moves the view by a delta in world units
+        let view_tx = self.channels.view_update_tx.clone();
+        engine.register_fn("pan_by", move |dx: f32, dy: f32| {
+            view_tx.send(ViewUpdate::Nudge(Point::new(dx, dy))).unwrap();
+        });
+
+        // moves the view by a delta in pixels, at the current scale
+        let view = self.shared_state.view.clone();
+        let view_tx = self.channels.view_update_tx.clone();
+        engine.register_fn("pan_by_screen", move |dx: f32, dy: f32| {
+            let scale = view.load().scale;
+            let delta = Point::new(dx * scale, dy * scale);
+            view_tx.send(ViewUpdate::Nudge(delta)).unwrap();
         });
 
         let mouse = self.shared_state.mouse_pos.clone();
@@ -1651,6 +4186,154 @@ impl ConsoleShared {
             let dims = screen_dims.load();
             view.screen_point_to_world(dims, screen)
         });
+
+        // the main graph node at a screen position, according to the
+        // node ID buffer, or unit if there's no node there, or if it's
+        // a node of an overlaid graph
+        let app_msg_tx = self.channels.app_tx.clone();
+        let node_id_at = move |x: f32, y: f32| {
+            let (tx, rx) =
+                crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+            let msg = AppMsg::RequestData {
+                key: "node_id_at".to_string(),
+                index: format!("{},{}", x, y),
+                sender: tx,
+            };
+
+            app_msg_tx.send(msg).unwrap();
+
+            let result = std::thread::spawn(move || rx.recv().unwrap()).join();
+            Self::error_helper::<rhai::Dynamic>(&result)
+        };
+
+        let node_at = node_id_at.clone();
+        engine.register_result_fn("node_id_at", move |x: f32, y: f32| {
+            node_at(x, y)
+        });
+
+        let node_at = node_id_at;
+        engine.register_result_fn("node_id_at", move |x: i64, y: i64| {
+            node_at(x as f32, y as f32)
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        let node_layout = move |node: NodeId| {
+            let (tx, rx) =
+                crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+            let msg = AppMsg::RequestData {
+                key: "node_position".to_string(),
+                index: node.0.to_string(),
+                sender: tx,
+            };
+
+            app_msg_tx.send(msg).unwrap();
+
+            let result = std::thread::spawn(move || rx.recv().unwrap()).join();
+            Self::error_helper::<Node>(&result)
+        };
+
+        // the layout position of a node's center
+        let layout = node_layout.clone();
+        engine.register_result_fn("node_position", move |node: NodeId| {
+            Ok(layout(node)?.center())
+        });
+
+        let layout = node_layout.clone();
+        engine.register_result_fn("node_position", move |node: i64| {
+            Ok(layout(NodeId::from(node as u64))?.center())
+        });
+
+        // the layout positions of a node's endpoints, as [p0, p1]
+        let layout = node_layout.clone();
+        engine.register_result_fn("node_endpoints", move |node: NodeId| {
+            let node = layout(node)?;
+            let ends: rhai::Array = vec![
+                rhai::Dynamic::from(node.p0),
+                rhai::Dynamic::from(node.p1),
+            ];
+            Ok(ends)
+        });
+
+        let layout = node_layout;
+        engine.register_result_fn("node_endpoints", move |node: i64| {
+            let node = layout(NodeId::from(node as u64))?;
+            let ends: rhai::Array = vec![
+                rhai::Dynamic::from(node.p0),
+                rhai::Dynamic::from(node.p1),
+            ];
+            Ok(ends)
+        });
+
+        let mouse = self.shared_state.mouse_pos.clone();
+        let view = self.shared_state.view.clone();
+        let screen_dims = self.shared_state.screen_dims.clone();
+
+        let graph = self.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+
+        // returns the base offset of the step on the path whose node
+        // is closest to the cursor
+        engine.register_result_fn(
+            "cursor_path_position",
+            move |path_name: &str| -> std::result::Result<
+                i64,
+                Box<EvalAltResult>,
+            > {
+                let path = graph
+                    .graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or("The provided path does not exist")?;
+
+                let cursor = {
+                    let screen = mouse.load();
+                    let view = view.load();
+                    let dims = screen_dims.load();
+                    view.screen_point_to_world(dims, screen)
+                };
+
+                let (tx, rx) =
+                    crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+                let msg = AppMsg::RequestData {
+                    key: "node_positions".to_string(),
+                    index: "".to_string(),
+                    sender: tx,
+                };
+
+                app_msg_tx.send(msg).unwrap();
+
+                let result =
+                    std::thread::spawn(move || rx.recv().unwrap()).join();
+                let nodes = Self::error_helper::<Vec<Node>>(&result)?;
+
+                let steps = graph.graph.path_steps(path).ok_or("Empty path")?;
+
+                let mut closest: Option<(StepPtr, f32)> = None;
+
+                for step in steps {
+                    let node_ix = (step.handle().id().0 - 1) as usize;
+
+                    if let Some(node) = nodes.get(node_ix) {
+                        let dist = node.distance_to(cursor);
+
+                        if closest.map(|(_, d)| dist < d).unwrap_or(true) {
+                            closest = Some((step.0, dist));
+                        }
+                    }
+                }
+
+                let (step_ptr, _) = closest.ok_or("Empty path")?;
+
+                let pos = graph
+                    .path_positions
+                    .path_step_position(path, step_ptr)
+                    .ok_or("Step not found in path position index")?;
+
+                Ok(pos as i64)
+            },
+        );
     }
 
     fn error_helper<T: Clone + 'static>(
@@ -1720,6 +4403,24 @@ impl ConsoleShared {
             coll.len() as i64
         });
 
+        // returns the sorted names of all attributes that are used in
+        // the GFF3 collection
+        engine.register_fn(
+            "gff3_attribute_keys",
+            |coll: &mut Arc<Gff3Records>| {
+                let mut keys = coll
+                    .attribute_keys
+                    .iter()
+                    .map(|key| format!("{}", key.as_bstr()))
+                    .collect::<Vec<_>>();
+                keys.sort();
+
+                keys.into_iter()
+                    .map(rhai::Dynamic::from)
+                    .collect::<Vec<_>>()
+            },
+        );
+
         engine.register_fn("gff3_column", |key: &str| match key {
             "SeqId" => Gff3Column::SeqId,
             "Source" => Gff3Column::Source,
@@ -1816,6 +4517,32 @@ impl ConsoleShared {
             },
         );
 
+        // map a record sequence ID in a collection to a path, used by
+        // `create_label_set` when no path is given
+        let app_msg_tx = self.channels.app_tx.clone();
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn(
+            "set_collection_seqid_path",
+            move |name: &str, seq_id: &str, path_name: &str| {
+                let path_id = graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or_else(|| format!("Path not found: {}", path_name))?;
+
+                let msg: AppMsg = AppMsg::SetData {
+                    key: "annotation_seq_id_path".to_string(),
+                    index: name.to_string(),
+                    value: rhai::Dynamic::from((
+                        seq_id.to_string(),
+                        Some(path_id),
+                    )),
+                };
+
+                app_msg_tx.send(msg).unwrap();
+
+                Ok(())
+            },
+        );
+
         let app_msg_tx = self.channels.app_tx.clone();
         engine.register_result_fn(
             "get_collection_ref_path",
@@ -1870,60 +4597,132 @@ impl ConsoleShared {
                 .map(rhai::Dynamic::from)
                 .collect::<Vec<_>>();
 
-            result
+            result
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("list_label_sets", move || {
+            let key = "label_set_names".to_string();
+            let index = "".to_string();
+
+            let (tx, rx) =
+                crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+            let msg: AppMsg = AppMsg::RequestData {
+                key,
+                index,
+                sender: tx,
+            };
+
+            app_msg_tx.send(msg).unwrap();
+
+            let result = std::thread::spawn(move || rx.recv().unwrap()).join();
+            let result = Self::error_helper::<Vec<String>>(&result).unwrap();
+
+            result
+                .into_iter()
+                .map(rhai::Dynamic::from)
+                .collect::<Vec<_>>()
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("remove_label_set", move |name: &str| {
+            let name = name.to_string();
+            app_msg_tx.send(AppMsg::RemoveNodeLabels { name }).unwrap();
+        });
+
+        // labels set from scripts all go in one label set, which can
+        // be toggled with `set_label_set_visible("script_labels", ..)`
+        let graph = self.graph.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "set_node_label",
+            move |node: rhai::Dynamic,
+                  text: &str|
+                  -> std::result::Result<(), Box<EvalAltResult>> {
+                let node = dynamic_node_id(&graph, node)?;
+                let text = text.to_string();
+                app_msg_tx
+                    .send(AppMsg::SetNodeLabel { node, text })
+                    .unwrap();
+                Ok(())
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("clear_node_labels", move || {
+            app_msg_tx.send(AppMsg::ClearNodeLabels).unwrap();
         });
 
+        // toggles whether a label set is drawn, without removing it
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn(
+            "set_label_set_visible",
+            move |name: &str, visible: bool| {
+                let msg: AppMsg = AppMsg::SetData {
+                    key: "label_set_visible".to_string(),
+                    index: name.to_string(),
+                    value: rhai::Dynamic::from(visible),
+                };
+
+                app_msg_tx.send(msg).unwrap();
+            },
+        );
+
         let app_msg_tx = self.channels.app_tx.clone();
         let result_tx = self.result_tx.clone();
-        engine.register_result_fn("load_collection", move |path: &str| {
-            let file = PathBuf::from(path);
-
-            let ext = file.extension().and_then(|ext| ext.to_str()).map_or(
-                Err("Missing file extension".into())
-                    as std::result::Result<_, Box<EvalAltResult>>,
-                |ext| Ok(ext),
-            )?;
-
-            if ext == "gff3" {
-                let records = Gff3Records::parse_gff3_file(&file);
-                match records {
-                    Ok(records) => {
-                        app_msg_tx
-                            .send(AppMsg::AddGff3Records(records))
-                            .unwrap();
-
-                        result_tx
-                            .send(Ok(rhai::Dynamic::from("Loaded GFF3 file")))
-                            .unwrap();
-
-                        return Ok(());
-                    }
-                    Err(_err) => {
-                        return Err("Error parsing GFF3 file".into());
-                    }
-                }
-            } else if ext == "bed" {
-                let records = BedRecords::parse_bed_file(&file);
-                match records {
-                    Ok(records) => {
-                        app_msg_tx
-                            .send(AppMsg::AddBedRecords(records))
-                            .unwrap();
-
-                        result_tx
-                            .send(Ok(rhai::Dynamic::from("Loaded BED file")))
-                            .unwrap();
-
-                        return Ok(());
-                    }
-                    Err(_err) => {
-                        return Err("Error parsing BED file".into());
-                    }
+        let working_dir = self.working_dir.clone();
+        engine.register_result_fn(
+            "load_collection",
+            move |path: &str| -> std::result::Result<(), Box<EvalAltResult>> {
+                let path = resolve_console_path(&working_dir, path);
+                let msg = load_collection_file(&app_msg_tx, &path)?;
+                result_tx.send(Ok(rhai::Dynamic::from(msg))).unwrap();
+                Ok(())
+            },
+        );
+
+        // loads a generic TSV or CSV file as an annotation collection,
+        // given the (0-based) columns holding the record coordinates;
+        // the other columns are named by the header line
+        let app_msg_tx = self.channels.app_tx.clone();
+        let result_tx = self.result_tx.clone();
+        let working_dir = self.working_dir.clone();
+        engine.register_result_fn(
+            "load_collection_tsv",
+            move |path: &str,
+                  seq_id_col: i64,
+                  start_col: i64,
+                  end_col: i64|
+                  -> std::result::Result<(), Box<EvalAltResult>> {
+                let path = resolve_console_path(&working_dir, path);
+
+                let cols = [seq_id_col, start_col, end_col];
+                if cols.iter().any(|&col| col < 0) {
+                    return Err("Column indices must be non-negative".into());
                 }
-            } else {
-                return Err("Invalid file extension".into());
-            }
-        });
+
+                let records = BedRecords::parse_delimited_file(
+                    &path,
+                    seq_id_col as usize,
+                    start_col as usize,
+                    end_col as usize,
+                )
+                .map_err(|err| -> Box<EvalAltResult> {
+                    format!("Error parsing {}: {}", path, err).into()
+                })?;
+
+                let msg = format!(
+                    "Loaded {} records from {}",
+                    records.len(),
+                    records.file_name()
+                );
+
+                app_msg_tx.send(AppMsg::AddBedRecords(records)).unwrap();
+                result_tx.send(Ok(rhai::Dynamic::from(msg))).unwrap();
+                Ok(())
+            },
+        );
 
         // this one's messy, there should be a better system in place
         // for requesting data like this
@@ -1960,6 +4759,153 @@ impl ConsoleShared {
             Err("Error retrieving data".into())
         });
 
+        // returns the indices of the records whose `column` matches
+        // `value`, either exactly or as a substring, optionally
+        // ignoring case
+        fn find_records_impl<C, K>(
+            records: &C,
+            column: &K,
+            value: &str,
+            contains: bool,
+            ignore_case: bool,
+        ) -> Vec<rhai::Dynamic>
+        where
+            C: AnnotationCollection<ColumnKey = K>,
+            K: ColumnKey,
+        {
+            let value = if ignore_case {
+                value.to_lowercase()
+            } else {
+                value.to_string()
+            };
+            let value = value.as_bytes();
+
+            records
+                .records()
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, record)| {
+                    let found =
+                        record.get_all(column).into_iter().any(|field| {
+                            let field = if ignore_case {
+                                field.to_lowercase()
+                            } else {
+                                field.to_vec()
+                            };
+
+                            if contains {
+                                field.contains_str(value)
+                            } else {
+                                field == value
+                            }
+                        });
+
+                    found.then(|| rhai::Dynamic::from(ix as i64))
+                })
+                .collect()
+        }
+
+        engine.register_fn(
+            "find_records",
+            |records: &mut Arc<Gff3Records>,
+             column: Gff3Column,
+             value: &str| {
+                find_records_impl(
+                    records.as_ref(),
+                    &column,
+                    value,
+                    false,
+                    false,
+                )
+            },
+        );
+
+        engine.register_fn(
+            "find_records",
+            |records: &mut Arc<Gff3Records>,
+             column: Gff3Column,
+             value: &str,
+             contains: bool,
+             ignore_case: bool| {
+                find_records_impl(
+                    records.as_ref(),
+                    &column,
+                    value,
+                    contains,
+                    ignore_case,
+                )
+            },
+        );
+
+        engine.register_fn(
+            "find_records",
+            |records: &mut Arc<BedRecords>, column: BedColumn, value: &str| {
+                find_records_impl(
+                    records.as_ref(),
+                    &column,
+                    value,
+                    false,
+                    false,
+                )
+            },
+        );
+
+        engine.register_fn(
+            "find_records",
+            |records: &mut Arc<BedRecords>,
+             column: BedColumn,
+             value: &str,
+             contains: bool,
+             ignore_case: bool| {
+                find_records_impl(
+                    records.as_ref(),
+                    &column,
+                    value,
+                    contains,
+                    ignore_case,
+                )
+            },
+        );
+
+        // a map from each value in the column to the number of times
+        // it appears in the collection; records with several values
+        // in the column count towards each of them
+        fn record_counts_impl<C, K>(records: &C, column: &K) -> rhai::Map
+        where
+            C: AnnotationCollection<ColumnKey = K>,
+            K: ColumnKey,
+        {
+            let mut counts: FxHashMap<&[u8], i64> = FxHashMap::default();
+
+            for record in records.records() {
+                for field in record.get_all(column) {
+                    *counts.entry(field).or_default() += 1;
+                }
+            }
+
+            counts
+                .into_iter()
+                .map(|(value, count)| {
+                    let key = format!("{}", value.as_bstr());
+                    (key.into(), rhai::Dynamic::from(count))
+                })
+                .collect()
+        }
+
+        engine.register_fn(
+            "record_counts",
+            |records: &mut Arc<Gff3Records>, column: Gff3Column| {
+                record_counts_impl(records.as_ref(), &column)
+            },
+        );
+
+        engine.register_fn(
+            "record_counts",
+            |records: &mut Arc<BedRecords>, column: BedColumn| {
+                record_counts_impl(records.as_ref(), &column)
+            },
+        );
+
         fn create_label_set_impl<C, K>(
             app_msg_tx: &crossbeam::channel::Sender<AppMsg>,
             graph: &Arc<GraphQuery>,
@@ -2010,6 +4956,113 @@ impl ConsoleShared {
             }
         }
 
+        fn create_label_set_by_seq_id_impl<C, K>(
+            app_msg_tx: &crossbeam::channel::Sender<AppMsg>,
+            graph: &Arc<GraphQuery>,
+
+            annots: &mut Arc<C>,
+            record_indices: Vec<rhai::Dynamic>,
+            column: K,
+            label_set_name: &str,
+        ) -> std::result::Result<(), Box<EvalAltResult>>
+        where
+            C: AnnotationCollection<ColumnKey = K> + Send + Sync + 'static,
+            K: ColumnKey,
+        {
+            use crate::gui::windows::annotations::calculate_annotation_set_by_seq_id;
+
+            let record_indices = record_indices
+                .into_iter()
+                .filter_map(|i| {
+                    let i = i.as_int().ok()?;
+
+                    Some(i as usize)
+                })
+                .collect::<Vec<_>>();
+
+            let (tx, rx) =
+                crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+            let msg: AppMsg = AppMsg::RequestData {
+                key: "annotation_seq_id_paths".to_string(),
+                index: annots.file_name().to_string(),
+                sender: tx,
+            };
+
+            app_msg_tx.send(msg).unwrap();
+
+            let result = std::thread::spawn(move || rx.recv().unwrap()).join();
+            let seq_id_paths = ConsoleShared::error_helper::<
+                HashMap<String, PathId>,
+            >(&result)?;
+
+            if seq_id_paths.is_empty() {
+                return Err(format!(
+                    "No sequence ID paths set for collection {}",
+                    annots.file_name()
+                )
+                .into());
+            }
+
+            let label_set = calculate_annotation_set_by_seq_id(
+                graph,
+                annots.as_ref(),
+                &record_indices,
+                &seq_id_paths,
+                &column,
+                label_set_name,
+            )
+            .ok_or("No records could be placed on a path")?;
+
+            let name = label_set_name.to_string();
+
+            app_msg_tx
+                .send(AppMsg::NewNodeLabels { name, label_set })
+                .unwrap();
+
+            Ok(())
+        }
+
+        // these versions place each record on the path its sequence
+        // ID is mapped to, see `set_collection_seqid_path`
+        let app_msg_tx = self.channels.app_tx.clone();
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "create_label_set",
+            move |annots: &mut Arc<Gff3Records>,
+                  record_indices: Vec<rhai::Dynamic>,
+                  column: Gff3Column,
+                  label_set_name: &str| {
+                create_label_set_by_seq_id_impl(
+                    &app_msg_tx,
+                    &graph,
+                    annots,
+                    record_indices,
+                    column,
+                    label_set_name,
+                )
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "create_label_set",
+            move |annots: &mut Arc<BedRecords>,
+                  record_indices: Vec<rhai::Dynamic>,
+                  column: BedColumn,
+                  label_set_name: &str| {
+                create_label_set_by_seq_id_impl(
+                    &app_msg_tx,
+                    &graph,
+                    annots,
+                    record_indices,
+                    column,
+                    label_set_name,
+                )
+            },
+        );
+
         let app_msg_tx = self.channels.app_tx.clone();
         let graph = self.graph.clone();
         engine.register_fn(
@@ -2054,6 +5107,399 @@ impl ConsoleShared {
     }
 }
 
+/// Resolves `path` against the console working directory (see `:cd`),
+/// unless it's absolute
+fn resolve_console_path(working_dir: &Mutex<PathBuf>, path: &str) -> String {
+    working_dir.lock().join(path).to_string_lossy().to_string()
+}
+
+/// Parses the GFF3 or BED file at `path` and sends the records to
+/// the App, returning a message describing what was loaded
+fn load_collection_file(
+    app_msg_tx: &crossbeam::channel::Sender<AppMsg>,
+    path: &str,
+) -> std::result::Result<&'static str, Box<EvalAltResult>> {
+    let file = PathBuf::from(path);
+
+    let ext = file.extension().and_then(|ext| ext.to_str()).map_or(
+        Err("Missing file extension".into())
+            as std::result::Result<_, Box<EvalAltResult>>,
+        |ext| Ok(ext),
+    )?;
+
+    if ext == "gff3" {
+        let records = Gff3Records::parse_gff3_file(&file);
+        match records {
+            Ok(records) => {
+                app_msg_tx.send(AppMsg::AddGff3Records(records)).unwrap();
+                Ok("Loaded GFF3 file")
+            }
+            Err(_err) => Err("Error parsing GFF3 file".into()),
+        }
+    } else if ext == "bed" {
+        let records = BedRecords::parse_bed_file(&file);
+        match records {
+            Ok(records) => {
+                app_msg_tx.send(AppMsg::AddBedRecords(records)).unwrap();
+                Ok("Loaded BED file")
+            }
+            Err(_err) => Err("Error parsing BED file".into()),
+        }
+    } else {
+        Err("Invalid file extension".into())
+    }
+}
+
+/// The number of path steps on each node, normalized to [0, 1], in
+/// node ID order, and the minimum and maximum step counts
+fn node_coverage_values(graph: &PackedGraph) -> (Vec<f32>, usize, usize) {
+    use rayon::prelude::*;
+
+    let counts = (0..graph.node_count())
+        .into_par_iter()
+        .map(|ix| {
+            let handle = Handle::pack(NodeId::from((ix as u64) + 1), false);
+            graph
+                .steps_on_handle(handle)
+                .map(|steps| steps.count())
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let min = counts.iter().copied().min().unwrap_or(0);
+    let max = counts.iter().copied().max().unwrap_or(0);
+
+    let range = (max - min).max(1) as f32;
+
+    let values = counts
+        .into_iter()
+        .map(|count| (count - min) as f32 / range)
+        .collect();
+
+    (values, min, max)
+}
+
+/// Builds an RGB overlay coloring the nodes covered by each record in
+/// `records` on `path` by the record's value in `column`, parsed as a
+/// number. The values are normalized to [0, 1] over all records with
+/// a value, and mapped through the colormap named `colormap_name`;
+/// later records overwrite earlier ones on shared nodes.
+fn column_value_overlay<C>(
+    graph: &GraphQuery,
+    records: &C,
+    column: &C::ColumnKey,
+    path: PathId,
+    colormaps: &overlays::Colormaps,
+    colormap_name: &str,
+) -> std::result::Result<OverlayCreatorMsg, Box<EvalAltResult>>
+where
+    C: AnnotationCollection,
+{
+    let colormap = colormaps
+        .get(colormap_name)
+        .ok_or_else(|| format!("Colormap not found: {}", colormap_name))?;
+
+    let steps = graph
+        .path_pos_steps(path)
+        .ok_or("Could not get path positions")?;
+
+    let offset = graph
+        .graph()
+        .get_path_name_vec(path)
+        .and_then(|name| crate::annotations::path_name_offset(&name));
+
+    let values = records
+        .records()
+        .iter()
+        .filter_map(|record| {
+            let value = record.get_first(column)?;
+            let value = std::str::from_utf8(value).ok()?.parse::<f32>().ok()?;
+            value.is_finite().then(|| (record, value))
+        })
+        .collect::<Vec<_>>();
+
+    if values.is_empty() {
+        return Err(format!("No numeric values in column {}", column).into());
+    }
+
+    let min = values.iter().map(|(_, v)| *v).fold(f32::MAX, f32::min);
+    let max = values.iter().map(|(_, v)| *v).fold(f32::MIN, f32::max);
+    let range = (max - min).max(std::f32::EPSILON);
+
+    let mut data = vec![rgb::RGBA::new(0.3, 0.3, 0.3, 0.3); graph.node_count()];
+
+    for (record, value) in values {
+        let color = colormap.sample((value - min) / range);
+
+        let range = crate::annotations::path_step_range(
+            &steps,
+            offset,
+            record.start(),
+            record.end(),
+        );
+
+        for (handle, _, _) in range.into_iter().flatten() {
+            data[(handle.id().0 - 1) as usize] = color;
+        }
+    }
+
+    // label a few evenly spaced points of the colormap with the value
+    // they correspond to
+    let legend = (0..5)
+        .map(|i| {
+            let t = i as f32 / 4.0;
+            (format!("{}", min + t * (max - min)), colormap.sample(t))
+        })
+        .collect();
+
+    Ok(OverlayCreatorMsg::NewOverlay {
+        name: format!("{} ({})", column, colormap_name),
+        data: OverlayData::RGB(data),
+        legend: Some(OverlayLegend::Categories(legend)),
+    })
+}
+
+/// The colors of the three codon positions in the reading frame
+/// overlay
+const READING_FRAME_COLORS: [rgb::RGBA<f32>; 3] = [
+    rgb::RGBA {
+        r: 0.9,
+        g: 0.2,
+        b: 0.2,
+        a: 1.0,
+    },
+    rgb::RGBA {
+        r: 0.2,
+        g: 0.75,
+        b: 0.2,
+        a: 1.0,
+    },
+    rgb::RGBA {
+        r: 0.2,
+        g: 0.4,
+        b: 0.9,
+        a: 1.0,
+    },
+];
+
+/// Colors each node covered by a CDS record in `records` with one of
+/// three colors, by the codon position of the node's first base
+/// along `path`, using the record's phase and strand. All other nodes
+/// get the default overlay color.
+fn reading_frame_colors(
+    graph: &GraphQuery,
+    records: &Gff3Records,
+    path: PathId,
+    path_name: &str,
+) -> Option<Vec<rgb::RGBA<f32>>> {
+    let offset = crate::annotations::path_name_offset(path_name.as_bytes());
+    let steps = graph.path_pos_steps(path)?;
+
+    let mut colors =
+        vec![rgb::RGBA::new(0.3, 0.3, 0.3, 0.3); graph.node_count()];
+
+    for record in records.records() {
+        if record.get_first(&Gff3Column::Type) != Some(&b"CDS"[..]) {
+            continue;
+        }
+
+        let phase = record
+            .get_first(&Gff3Column::Frame)
+            .and_then(|frame| frame.to_str().ok()?.parse::<i64>().ok());
+
+        let phase = match phase {
+            Some(phase) => phase,
+            None => continue,
+        };
+
+        let range = crate::annotations::path_step_range(
+            &steps,
+            offset,
+            record.start(),
+            record.end(),
+        );
+
+        for (handle, _, pos) in range.into_iter().flatten() {
+            // GFF3 coordinates are 1-based
+            let base = (pos + offset.unwrap_or(0) + 1) as i64;
+
+            let codon_pos = match record.strand() {
+                Strand::Neg => record.end() as i64 - base - phase,
+                _ => base - record.start() as i64 - phase,
+            };
+
+            let ix = (handle.id().0 - 1) as usize;
+            if let Some(color) = colors.get_mut(ix) {
+                *color = READING_FRAME_COLORS[codon_pos.rem_euclid(3) as usize];
+            }
+        }
+    }
+
+    Some(colors)
+}
+
+/// Adds `line` as a row of monospace labels, with each occurrence of
+/// `query` highlighted
+fn highlighted_line(
+    ui: &mut egui::Ui,
+    line: &str,
+    query: &str,
+    is_current: bool,
+) {
+    ui.spacing_mut().item_spacing.x = 0.0;
+
+    let highlight = if is_current {
+        egui::Color32::from_rgb(160, 120, 0)
+    } else {
+        egui::Color32::from_rgb(80, 80, 120)
+    };
+
+    let mut rest = line;
+
+    while !query.is_empty() {
+        let ix = match rest.find(query) {
+            Some(ix) => ix,
+            None => break,
+        };
+
+        if ix > 0 {
+            ui.add(egui::Label::new(&rest[..ix]).monospace());
+        }
+
+        let end = ix + query.len();
+        ui.add(
+            egui::Label::new(&rest[ix..end])
+                .monospace()
+                .background_color(highlight),
+        );
+
+        rest = &rest[end..];
+    }
+
+    if !rest.is_empty() {
+        ui.add(egui::Label::new(rest).monospace());
+    }
+}
+
+/// Quotes and escapes a string for use in JSON output
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn dynamic_path_id(
+    graph: &PackedGraph,
+    path: rhai::Dynamic,
+) -> std::result::Result<PathId, Box<EvalAltResult>> {
+    if let Some(path_id) = path.clone().try_cast::<PathId>() {
+        Ok(path_id)
+    } else if let Some(name) = path.try_cast::<String>() {
+        graph
+            .get_path_id(name.as_bytes())
+            .ok_or_else(|| format!("Path not found: {}", name).into())
+    } else {
+        Err("Expected a path ID or path name".into())
+    }
+}
+
+fn dynamic_node_id(
+    graph: &PackedGraph,
+    node: rhai::Dynamic,
+) -> std::result::Result<NodeId, Box<EvalAltResult>> {
+    let node = if let Some(node) = node.clone().try_cast::<NodeId>() {
+        node
+    } else if let Ok(id) = node.as_int() {
+        NodeId::from(id.max(0) as u64)
+    } else {
+        return Err("Expected a node ID".into());
+    };
+
+    if graph.has_node(node) {
+        Ok(node)
+    } else {
+        Err(format!("Node not found: {}", node.0).into())
+    }
+}
+
+fn node_neighbors(
+    graph: &PackedGraph,
+    node: NodeId,
+    dirs: &[Direction],
+) -> rhai::Array {
+    let handle = Handle::pack(node, false);
+
+    dirs.iter()
+        .flat_map(|&dir| graph.neighbors(handle, dir))
+        .map(|other| {
+            let pair: rhai::Array = vec![
+                rhai::Dynamic::from(other.id()),
+                rhai::Dynamic::from(other.is_reverse()),
+            ];
+            rhai::Dynamic::from(pair)
+        })
+        .collect()
+}
+
+fn path_node_set(graph: &PackedGraph, path: PathId) -> FxHashSet<NodeId> {
+    graph
+        .path_steps(path)
+        .map(|steps| steps.map(|step| step.handle().id()).collect())
+        .unwrap_or_default()
+}
+
+/// Picks a node uniformly at random, by sampling IDs in the graph's
+/// ID range until one exists, which is fast for the usual case of
+/// (nearly) contiguous node IDs; returns `None` if the graph is empty
+fn random_node<R: rand::Rng>(
+    graph: &PackedGraph,
+    rng: &mut R,
+) -> Option<NodeId> {
+    if graph.node_count() == 0 {
+        return None;
+    }
+
+    let min_id = graph.min_node_id().0;
+    let max_id = graph.max_node_id().0;
+
+    for _ in 0..64 {
+        let id = NodeId::from(rng.gen_range(min_id..=max_id));
+        if graph.has_node(id) {
+            return Some(id);
+        }
+    }
+
+    let ix = rng.gen_range(0..graph.node_count());
+    graph.handles().nth(ix).map(|h| h.id())
+}
+
+fn jaccard_similarity(a: &FxHashSet<NodeId>, b: &FxHashSet<NodeId>) -> f32 {
+    let (small, large) = if a.len() < b.len() { (a, b) } else { (b, a) };
+
+    let intersection = small.iter().filter(|n| large.contains(n)).count();
+    let union = a.len() + b.len() - intersection;
+
+    if union == 0 {
+        0.0
+    } else {
+        intersection as f32 / union as f32
+    }
+}
+
 fn virtual_key_code_map() -> HashMap<String, winit::event::VirtualKeyCode> {
     use winit::event::VirtualKeyCode as Key;
 
@@ -2234,6 +5680,7 @@ pub enum ConsoleGuiElem {
     Button { text: String, callback_id: String },
     TextInput { label: String, data_id: String },
     Row { fields: Vec<String> },
+    Plot { data_id: String, scatter: bool },
 }
 
 pub struct ConsoleGuiDsl {
@@ -2243,6 +5690,7 @@ pub struct ConsoleGuiDsl {
     callbacks: HashMap<String, Box<dyn Fn() + Send + Sync + 'static>>,
 
     text_data: HashMap<String, String>,
+    plot_data: HashMap<String, Vec<(f32, f32)>>,
 }
 
 impl ConsoleGuiDsl {
@@ -2254,6 +5702,7 @@ impl ConsoleGuiDsl {
             callbacks: HashMap::default(),
 
             text_data: HashMap::default(),
+            plot_data: HashMap::default(),
         }
     }
 
@@ -2261,6 +5710,34 @@ impl ConsoleGuiDsl {
         self.text_data.get(data_id).map(|s| s.as_str())
     }
 
+    /// Parses plot data from a script, which can be either an array
+    /// of numbers, plotted against their indices, or an array of
+    /// points, or `[x, y]` arrays
+    fn parse_plot_data(data: rhai::Array) -> Option<Vec<(f32, f32)>> {
+        fn number(val: &rhai::Dynamic) -> Option<f32> {
+            val.as_float()
+                .ok()
+                .or_else(|| val.as_int().ok().map(|i| i as f32))
+        }
+
+        data.iter()
+            .enumerate()
+            .map(|(ix, val)| {
+                if let Some(y) = number(val) {
+                    Some((ix as f32, y))
+                } else if let Some(p) = val.clone().try_cast::<Point>() {
+                    Some((p.x, p.y))
+                } else {
+                    let pair = val.clone().try_cast::<rhai::Array>()?;
+                    match pair.as_slice() {
+                        [x, y] => Some((number(x)?, number(y)?)),
+                        _ => None,
+                    }
+                }
+            })
+            .collect()
+    }
+
     pub fn show(&mut self, ctx: &egui::CtxRef) {
         egui::Window::new(&self.window_title)
             .id(self.id)
@@ -2296,6 +5773,36 @@ impl ConsoleGuiDsl {
                         ConsoleGuiElem::Row { fields } => {
                             // TODO
                         }
+                        ConsoleGuiElem::Plot { data_id, scatter } => {
+                            use egui::plot::{
+                                Line, Plot, Points, Value, Values,
+                            };
+
+                            let values = self
+                                .plot_data
+                                .get(data_id.as_str())
+                                .map(|data| {
+                                    data.iter()
+                                        .map(|&(x, y)| {
+                                            Value::new(x as f64, y as f64)
+                                        })
+                                        .collect::<Vec<_>>()
+                                })
+                                .unwrap_or_default();
+
+                            let values = Values::from_values(values);
+
+                            let plot = Plot::new(self.id.with(&*data_id))
+                                .height(200.0);
+
+                            let plot = if *scatter {
+                                plot.points(Points::new(values).radius(2.0))
+                            } else {
+                                plot.line(Line::new(values))
+                            };
+
+                            ui.add(plot);
+                        }
                     }
                 }
             });
@@ -0,0 +1,258 @@
+use super::Console;
+
+/// The `:` console commands, and their usage
+const COMMANDS: &[(&str, &str)] = &[
+    (
+        ":help [fn]",
+        "list commands and functions, or describe a function",
+    ),
+    (":clear", "clear the console output"),
+    (":clear_overlay_cache", "delete all cached overlays"),
+    (
+        ":overlay_cache [<dir>|off]",
+        "cache script overlays in a directory, or disable the cache",
+    ),
+    (":reset", "clear the history, scope, and imported modules"),
+    (":exec <file>", "evaluate a script file"),
+    (":import <file>", "import a script file as a module"),
+    (
+        ":start_interval <file> <handle> [ms]",
+        "run a script repeatedly",
+    ),
+    (
+        ":animate_overlay <fn> <handle> [ms]",
+        "recompute an overlay repeatedly",
+    ),
+    (":end_interval <handle>", "stop an interval"),
+    (":list_intervals", "list the running intervals"),
+    (":pwd", "print the console working directory"),
+    (":cd <dir>", "change the console working directory"),
+    (":abort", "stop the scripts that are still running"),
+    (":save_session <file>", "save the session to a JSON file"),
+    (":load_session <file>", "restore a saved session"),
+    (
+        ":save_selection <file>",
+        "write the selected node IDs to a file",
+    ),
+    (":load_selection <file>", "select the node IDs in a file"),
+    (":graph_report <file>", "write graph statistics to a file"),
+    (":reload_graph <gfa> [layout]", "replace the loaded graph"),
+    (
+        ":load_overlay_graph <gfa> [layout]",
+        "draw another graph on top",
+    ),
+    (
+        ":toggle_overlay_graph <index>",
+        "show or hide an overlaid graph",
+    ),
+    (
+        ":overlay_graph_offset <index> <x> <y>",
+        "move an overlaid graph",
+    ),
+    (":copy_view", "copy the current view to the clipboard"),
+    (":listen <port>", "accept scripts over TCP on localhost"),
+    (
+        ":record_script <file>",
+        "append the following input to a file",
+    ),
+    (":stop_record", "stop recording input"),
+    (
+        ":benchmark_overlay <fn> <iterations>",
+        "time an overlay function",
+    ),
+];
+
+/// Short descriptions of the script functions, shown by `:help <fn>`
+const FUNCTIONS: &[(&str, &str)] = &[
+    ("get", "get(name): the value of a setting"),
+    ("set", "set(name, value): change a setting"),
+    (
+        "get_var",
+        "get_var(name): a variable shared between scripts",
+    ),
+    ("set_var", "set_var(name, value): set a shared variable"),
+    ("log", "log(value): print a value to the console"),
+    ("sleep", "sleep(ms): pause the script"),
+    ("get_graph", "get_graph(): the loaded graph"),
+    ("get_view", "get_view(): the current view"),
+    ("set_view", "set_view(view): jump to a view"),
+    (
+        "set_view_origin",
+        "set_view_origin(point): center the view on a point",
+    ),
+    ("set_scale", "set_scale(scale): set the view scale"),
+    ("pan_by", "pan_by(dx, dy): pan the view, in world units"),
+    (
+        "goto_node",
+        "goto_node(node): center the view on a node, and select it",
+    ),
+    (
+        "zoom_to_node",
+        "zoom_to_node(node): like goto_node, but also zooms to fit the node",
+    ),
+    (
+        "goto_random_node",
+        "goto_random_node([seed]): jump to a random node",
+    ),
+    ("node_position", "node_position(node): the center of a node"),
+    (
+        "node_endpoints",
+        "node_endpoints(node): the endpoints of a node",
+    ),
+    ("get_selection", "get_selection(): the selected nodes"),
+    (
+        "set_selection",
+        "set_selection(nodes): replace the selection",
+    ),
+    (
+        "fit_selection",
+        "fit_selection(): zoom to fit the selection",
+    ),
+    ("fit_graph", "fit_graph(): zoom to fit the entire graph"),
+    (
+        "path_selection",
+        "path_selection(path): the nodes on a path",
+    ),
+    (
+        "node_id_at",
+        "node_id_at(x, y): the node at a screen position, from the node ID \
+         buffer",
+    ),
+    (
+        "get_hover_node",
+        "get_hover_node(): the node under the cursor",
+    ),
+    (
+        "hover_node_info",
+        "hover_node_info(): details of the hovered node",
+    ),
+    ("path_names", "path_names(): the names of all paths"),
+    ("path_color", "path_color(path): the color used for a path"),
+    (
+        "set_path_color",
+        "set_path_color(path_name, color): override it",
+    ),
+    (
+        "get_overlays",
+        "get_overlays(): the names and IDs of all overlays",
+    ),
+    (
+        "set_active_overlay",
+        "set_active_overlay(id): show an overlay",
+    ),
+    (
+        "set_base_overlay",
+        "set_base_overlay(id): draw an overlay under the active one",
+    ),
+    (
+        "overlay_coverage",
+        "overlay_coverage(): color nodes by path coverage",
+    ),
+    (
+        "overlay_from_column",
+        "overlay_from_column(records, column, path, colormap): color nodes \
+         by an annotation column",
+    ),
+    (
+        "register_colormap",
+        "register_colormap(name, colors): add a colormap for \
+         overlay_from_column (not selectable as the overlay gradient)",
+    ),
+    (
+        "load_collection",
+        "load_collection(path): load a GFF3 or BED annotation file",
+    ),
+    (
+        "load_collection_tsv",
+        "load_collection_tsv(path, seq_id_col, start_col, end_col): load a \
+         TSV or CSV annotation file",
+    ),
+    (
+        "get_collection",
+        "get_collection(name): a loaded annotation file",
+    ),
+    (
+        "record_counts",
+        "record_counts(collection, column): the number of records with \
+         each value in a column",
+    ),
+    (
+        "list_collections",
+        "list_collections(): the loaded annotation files",
+    ),
+    (
+        "list_label_sets",
+        "list_label_sets(): the names of all label sets",
+    ),
+    (
+        "remove_label_set",
+        "remove_label_set(name): remove a label set",
+    ),
+    (
+        "show_path_ruler",
+        "show_path_ruler(path_name, show): draw a bp ruler",
+    ),
+    (
+        "freeze_stats",
+        "freeze_stats(freeze): freeze the debug statistics",
+    ),
+    ("export_svg", "export_svg(path): export the view as an SVG"),
+];
+
+impl Console<'static> {
+    /// Prints the `:` commands and the names of the registered
+    /// script functions, or, given a function name, its description
+    /// and signatures
+    pub(super) fn print_help(&mut self, fn_name: Option<&str>) {
+        let signatures = self.create_engine().gen_fn_signatures(false);
+
+        match fn_name {
+            None => {
+                self.append_output(" >>> commands:");
+                for (command, desc) in COMMANDS {
+                    let line = format!("  {:<40} {}", command, desc);
+                    self.append_output(&line);
+                }
+
+                let mut names = signatures
+                    .iter()
+                    .filter_map(|sig| sig.split('(').next())
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                names.sort();
+                names.dedup();
+
+                self.append_output(" >>> functions (:help <fn> for details):");
+                for line in names.chunks(6) {
+                    self.append_output(&format!("  {}", line.join(", ")));
+                }
+            }
+            Some(fn_name) => {
+                let desc = FUNCTIONS
+                    .iter()
+                    .find(|(name, _)| *name == fn_name)
+                    .map(|(_, desc)| *desc);
+
+                let prefix = format!("{}(", fn_name);
+                let matching = signatures
+                    .iter()
+                    .filter(|sig| sig.starts_with(&prefix))
+                    .collect::<Vec<_>>();
+
+                if desc.is_none() && matching.is_empty() {
+                    let msg = format!(" >>> no function named {}", fn_name);
+                    self.append_output(&msg);
+                    return;
+                }
+
+                if let Some(desc) = desc {
+                    self.append_output(&format!(" >>> {}", desc));
+                }
+
+                for sig in matching {
+                    self.append_output(&format!("  {}", sig));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+
+use handlegraph::handle::NodeId;
+
+use super::{json_str, Console, ScriptEvalResult};
+
+impl Console<'static> {
+    /// Starts listening for remote commands on `127.0.0.1:<port>`.
+    ///
+    /// Each connection gets its own Rhai scope, and is handled on its
+    /// own thread. Every line received is evaluated as a script with
+    /// the console engine, and answered with a single line of JSON,
+    /// either `{"result": ...}` or `{"error": "..."}`.
+    ///
+    /// Only one listener can be active at a time, and it runs until
+    /// gfaestus exits. The engine is rebuilt when a new graph is
+    /// loaded, and each command uses the engine current at the time.
+    pub fn listen(&mut self, port: u16) -> Result<()> {
+        if let Some(addr) = self.remote_addr {
+            return Err(anyhow!("Already listening on {}", addr));
+        }
+
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let listener = TcpListener::bind(addr)?;
+
+        let engine = Arc::new(Mutex::new(Arc::new(self.create_engine())));
+        self.remote_engine = Some(engine.clone());
+
+        let result_tx = self.result_tx.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        log::warn!("remote connection failed: {:?}", err);
+                        continue;
+                    }
+                };
+
+                if let Ok(peer) = stream.peer_addr() {
+                    let msg = format!(" >>> remote connection from {}", peer);
+                    let _ = result_tx.send(Ok(msg.into()));
+                }
+
+                let engine = engine.clone();
+                std::thread::spawn(move || {
+                    if let Err(err) = handle_connection(&engine, stream) {
+                        log::warn!("remote connection error: {:?}", err);
+                    }
+                });
+            }
+        });
+
+        self.remote_addr = Some(addr);
+        self.append_output(&format!(" >>> listening on {}", addr));
+
+        Ok(())
+    }
+}
+
+fn handle_connection(
+    engine: &Mutex<Arc<rhai::Engine>>,
+    stream: TcpStream,
+) -> Result<()> {
+    let mut scope = Console::create_scope();
+
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        log::debug!("remote command: {}", line);
+
+        let engine = engine.lock().clone();
+
+        let result: ScriptEvalResult =
+            engine.eval_with_scope::<rhai::Dynamic>(&mut scope, &line);
+
+        let response = match result {
+            Ok(value) => format!("{{\"result\": {}}}", dynamic_json(&value)),
+            Err(err) => {
+                format!("{{\"error\": {}}}", json_str(&err.to_string()))
+            }
+        };
+
+        writeln!(writer, "{}", response)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Converts a script result to JSON; values that have no JSON
+/// equivalent are returned as their debug string
+fn dynamic_json(value: &rhai::Dynamic) -> String {
+    if value.is::<()>() {
+        "null".to_string()
+    } else if let Ok(b) = value.as_bool() {
+        b.to_string()
+    } else if let Ok(i) = value.as_int() {
+        i.to_string()
+    } else if let Ok(f) = value.as_float() {
+        if f.is_finite() {
+            f.to_string()
+        } else {
+            "null".to_string()
+        }
+    } else if let Some(node) = value.clone().try_cast::<NodeId>() {
+        node.0.to_string()
+    } else if let Ok(s) = value.clone().as_string() {
+        json_str(&s)
+    } else if let Some(array) = value.clone().try_cast::<rhai::Array>() {
+        let values = array.iter().map(dynamic_json).collect::<Vec<_>>();
+        format!("[{}]", values.join(", "))
+    } else if let Some(map) = value.clone().try_cast::<rhai::Map>() {
+        let fields = map
+            .iter()
+            .map(|(k, v)| format!("{}: {}", json_str(k), dynamic_json(v)))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", fields.join(", "))
+    } else {
+        json_str(&format!("{:?}", value))
+    }
+}
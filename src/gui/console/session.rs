@@ -0,0 +1,477 @@
+use std::sync::Arc;
+
+use handlegraph::{handle::NodeId, handlegraph::*, pathhandlegraph::*};
+
+use anyhow::{anyhow, Result};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    annotations::{
+        AnnotationCollection, AnnotationLabelSet, AnnotationRecord, BedRecords,
+        ColumnKey, Gff3Records,
+    },
+    app::{mainview::ViewUpdate, AppMsg, Select},
+    geometry::{Point, Rect},
+    view::View,
+};
+
+use super::{json_str, load_collection_file, ConsoleShared};
+
+impl ConsoleShared {
+    /// Writes the current view, active overlay, selection, the paths
+    /// of all loaded annotation collections, and all label sets to
+    /// `path`, as JSON.
+    ///
+    /// Blocks on requests to the App, so must not be called from the
+    /// main thread.
+    pub fn save_session(&self, path: &str) -> Result<()> {
+        let view = self.shared_state.view.load();
+
+        let overlay =
+            self.shared_state.overlay_state.current_overlay().and_then(
+                |overlay_id| {
+                    let overlays = self.overlay_list.lock();
+                    overlays
+                        .iter()
+                        .find(|(id, _, _)| *id == overlay_id)
+                        .map(|(_, _, name)| name.to_string())
+                },
+            );
+
+        let mut selection = {
+            let (tx, rx) =
+                crossbeam::channel::bounded::<(Rect, FxHashSet<NodeId>)>(1);
+            self.channels.app_tx.send(AppMsg::RequestSelection(tx))?;
+
+            let (_rect, nodes) = rx.recv()?;
+            nodes.into_iter().map(|node| node.0).collect::<Vec<_>>()
+        };
+        selection.sort();
+
+        let collections: Vec<String> =
+            self.request_app_data("annotation_paths")?;
+        let label_sets: Vec<AnnotationLabelSet> =
+            self.request_app_data("label_sets")?;
+
+        let mut json = String::new();
+
+        json.push_str("{\n");
+        json.push_str(&format!(
+            "  \"view\": {{ \"x\": {}, \"y\": {}, \"scale\": {} }},\n",
+            view.center.x, view.center.y, view.scale
+        ));
+
+        let overlay = overlay.as_deref().map_or("null".to_string(), json_str);
+        json.push_str(&format!("  \"overlay\": {},\n", overlay));
+
+        let selection = selection
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        json.push_str(&format!(
+            "  \"selection\": [{}],\n",
+            selection.join(", ")
+        ));
+
+        let collections = collections
+            .iter()
+            .map(|path| json_str(path))
+            .collect::<Vec<_>>();
+        json.push_str(&format!(
+            "  \"collections\": [{}],\n",
+            collections.join(", ")
+        ));
+
+        let label_sets =
+            label_sets.iter().map(label_set_json).collect::<Vec<_>>();
+        json.push_str(&format!(
+            "  \"label_sets\": [\n{}\n  ]\n",
+            label_sets.join(",\n")
+        ));
+
+        json.push_str("}\n");
+
+        std::fs::write(path, json)?;
+
+        Ok(())
+    }
+
+    /// Restores a session saved with `save_session`, loading the
+    /// annotation collections first so that the label sets can be
+    /// recreated.
+    ///
+    /// Anything in the session that can't be found -- missing
+    /// annotation files, overlays, paths, or columns -- is skipped,
+    /// and reported in the returned list of warnings.
+    pub fn load_session(&self, path: &str) -> Result<Vec<String>> {
+        let json = std::fs::read_to_string(path)?;
+
+        let engine = rhai::Engine::new_raw();
+        let session = engine
+            .parse_json(&json, true)
+            .map_err(|err| anyhow!("Error parsing session file: {}", err))?;
+
+        let mut warnings = Vec::new();
+
+        let collections = session
+            .get("collections")
+            .and_then(|c| c.clone().try_cast::<rhai::Array>())
+            .unwrap_or_default();
+
+        for collection in collections {
+            let file = match collection.as_string() {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+
+            if !std::path::Path::new(&file).exists() {
+                warnings.push(format!("annotation file not found: {}", file));
+                continue;
+            }
+
+            if let Err(err) = load_collection_file(&self.channels.app_tx, &file)
+            {
+                warnings.push(format!("{}: {}", file, err));
+            }
+        }
+
+        let label_sets = session
+            .get("label_sets")
+            .and_then(|l| l.clone().try_cast::<rhai::Array>())
+            .unwrap_or_default();
+
+        for label_set in label_sets {
+            if let Some(label_set) = label_set.try_cast::<rhai::Map>() {
+                if let Err(err) = self.restore_label_set(&label_set) {
+                    warnings.push(err.to_string());
+                }
+            }
+        }
+
+        if let Some(selection) = session
+            .get("selection")
+            .and_then(|s| s.clone().try_cast::<rhai::Array>())
+        {
+            let graph = &self.graph.graph;
+
+            let nodes = selection
+                .into_iter()
+                .filter_map(|id| {
+                    let id = NodeId::from(id.as_int().ok()? as u64);
+                    graph.has_node(id).then(|| id)
+                })
+                .collect::<FxHashSet<_>>();
+
+            let msg = AppMsg::Selection(Select::Many { nodes, clear: true });
+            self.channels.app_tx.send(msg)?;
+        }
+
+        if let Some(view) = session
+            .get("view")
+            .and_then(|v| v.clone().try_cast::<rhai::Map>())
+        {
+            let field = |name: &str| view.get(name).and_then(dynamic_f32);
+
+            if let (Some(x), Some(y), Some(scale)) =
+                (field("x"), field("y"), field("scale"))
+            {
+                let view = View {
+                    center: Point::new(x, y),
+                    scale,
+                };
+                self.channels
+                    .view_update_tx
+                    .send(ViewUpdate::SetFull(view))?;
+            } else {
+                warnings.push("invalid view in session file".to_string());
+            }
+        }
+
+        if let Some(overlay) = session.get("overlay") {
+            if let Ok(name) = overlay.clone().as_string() {
+                let overlay_id = {
+                    let overlays = self.overlay_list.lock();
+                    overlays
+                        .iter()
+                        .find(|(_, _, n)| n == &name)
+                        .map(|(id, _, _)| *id)
+                };
+
+                if let Some(id) = overlay_id {
+                    let overlay_state = &self.shared_state.overlay_state;
+                    overlay_state.set_current_overlay(Some(id));
+                } else {
+                    warnings.push(format!("overlay not found: {}", name));
+                }
+            }
+        }
+
+        Ok(warnings)
+    }
+
+    /// Writes the IDs of the selected nodes to `path`, one per line,
+    /// in ID order, returning the number of nodes written.
+    ///
+    /// Blocks on a request to the App, so must not be called from the
+    /// main thread.
+    pub fn save_selection(&self, path: &str) -> Result<usize> {
+        let (tx, rx) =
+            crossbeam::channel::bounded::<(Rect, FxHashSet<NodeId>)>(1);
+        self.channels.app_tx.send(AppMsg::RequestSelection(tx))?;
+
+        let (_rect, nodes) = rx.recv()?;
+
+        let mut ids = nodes.into_iter().map(|node| node.0).collect::<Vec<_>>();
+        ids.sort();
+
+        let mut out = String::new();
+        for id in ids.iter() {
+            out.push_str(&format!("{}\n", id));
+        }
+
+        std::fs::write(path, out)?;
+
+        Ok(ids.len())
+    }
+
+    /// Replaces the selection with the node IDs in the file at
+    /// `path`, as written by `save_selection`. IDs of nodes that
+    /// aren't in the graph are skipped.
+    ///
+    /// Returns the number of nodes selected, and the number skipped.
+    pub fn load_selection(&self, path: &str) -> Result<(usize, usize)> {
+        let text = std::fs::read_to_string(path)?;
+
+        let graph = &self.graph.graph;
+
+        let mut nodes = FxHashSet::default();
+        let mut dropped = 0;
+
+        for (line_ix, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let id = line.parse::<u64>().map_err(|_| {
+                anyhow!("invalid node ID on line {}: {}", line_ix + 1, line)
+            })?;
+
+            let node = NodeId::from(id);
+            if graph.has_node(node) {
+                nodes.insert(node);
+            } else {
+                dropped += 1;
+            }
+        }
+
+        let loaded = nodes.len();
+
+        let msg = AppMsg::Selection(Select::Many { nodes, clear: true });
+        self.channels.app_tx.send(msg)?;
+
+        Ok((loaded, dropped))
+    }
+
+    fn restore_label_set(&self, label_set: &rhai::Map) -> Result<()> {
+        let get_str = |key: &str| -> Result<String> {
+            label_set
+                .get(key)
+                .and_then(|v| v.clone().as_string().ok())
+                .ok_or_else(|| anyhow!("label set missing field: {}", key))
+        };
+
+        let name = get_str("name")?;
+        let collection = get_str("collection")?;
+        let column = get_str("column")?;
+        let path_name = get_str("path")?;
+
+        let visible = label_set
+            .get("visible")
+            .and_then(|v| v.as_bool().ok())
+            .unwrap_or(true);
+
+        let path_id = self
+            .graph
+            .graph
+            .get_path_id(path_name.as_bytes())
+            .ok_or_else(|| anyhow!("path not found: {}", path_name))?;
+
+        let mut label_strings: Vec<String> = Vec::new();
+        let mut string_ixs: FxHashMap<String, usize> = FxHashMap::default();
+        let mut labels: FxHashMap<NodeId, Vec<usize>> = FxHashMap::default();
+
+        let entries = label_set
+            .get("labels")
+            .and_then(|l| l.clone().try_cast::<rhai::Array>())
+            .unwrap_or_default();
+
+        for entry in entries {
+            let entry = match entry.try_cast::<rhai::Array>() {
+                Some(entry) if entry.len() == 2 => entry,
+                _ => continue,
+            };
+
+            let (node, text) =
+                match (entry[0].as_int(), entry[1].clone().as_string()) {
+                    (Ok(node), Ok(text)) => (NodeId::from(node as u64), text),
+                    _ => continue,
+                };
+
+            let ix = *string_ixs.entry(text.clone()).or_insert_with(|| {
+                label_strings.push(text);
+                label_strings.len() - 1
+            });
+
+            labels.entry(node).or_default().push(ix);
+        }
+
+        let records = self.request_app_data_dyn("annotation_file", &collection);
+
+        let label_set = match records {
+            Ok(records) => {
+                if let Some(gff3) =
+                    records.clone().try_cast::<Arc<Gff3Records>>()
+                {
+                    label_set_from_parts(
+                        gff3.as_ref(),
+                        path_id,
+                        &path_name,
+                        &column,
+                        &name,
+                        label_strings,
+                        labels,
+                    )
+                } else if let Some(bed) = records.try_cast::<Arc<BedRecords>>()
+                {
+                    label_set_from_parts(
+                        bed.as_ref(),
+                        path_id,
+                        &path_name,
+                        &column,
+                        &name,
+                        label_strings,
+                        labels,
+                    )
+                } else {
+                    None
+                }
+            }
+            Err(_) => {
+                return Err(anyhow!(
+                    "label set {}: collection not loaded: {}",
+                    name,
+                    collection
+                ))
+            }
+        };
+
+        let label_set = label_set.ok_or_else(|| {
+            anyhow!("label set {}: column not found: {}", name, column)
+        })?;
+
+        self.channels.app_tx.send(AppMsg::NewNodeLabels {
+            name: name.clone(),
+            label_set,
+        })?;
+
+        if !visible {
+            self.channels.app_tx.send(AppMsg::SetData {
+                key: "label_set_visible".to_string(),
+                index: name,
+                value: rhai::Dynamic::from(false),
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn request_app_data_dyn(
+        &self,
+        key: &str,
+        index: &str,
+    ) -> Result<rhai::Dynamic> {
+        let (tx, rx) = crossbeam::channel::bounded::<Result<rhai::Dynamic>>(1);
+
+        let msg = AppMsg::RequestData {
+            key: key.to_string(),
+            index: index.to_string(),
+            sender: tx,
+        };
+
+        self.channels.app_tx.send(msg)?;
+
+        rx.recv()?
+    }
+
+    fn request_app_data<T: Clone + 'static>(&self, key: &str) -> Result<T> {
+        self.request_app_data_dyn(key, "")?
+            .try_cast::<T>()
+            .ok_or_else(|| anyhow!("Received incorrect type from App"))
+    }
+}
+
+fn label_set_from_parts<C, R, K>(
+    collection: &C,
+    path_id: PathId,
+    path_name: &str,
+    column: &str,
+    name: &str,
+    label_strings: Vec<String>,
+    labels: FxHashMap<NodeId, Vec<usize>>,
+) -> Option<AnnotationLabelSet>
+where
+    C: AnnotationCollection<ColumnKey = K, Record = R>,
+    R: AnnotationRecord<ColumnKey = K>,
+    K: ColumnKey,
+{
+    let column = collection
+        .all_columns()
+        .into_iter()
+        .find(|c| c.to_string() == column)?;
+
+    Some(AnnotationLabelSet::new(
+        collection,
+        path_id,
+        path_name.as_bytes(),
+        &column,
+        name,
+        label_strings,
+        labels,
+    ))
+}
+
+fn label_set_json(label_set: &AnnotationLabelSet) -> String {
+    let strings = label_set.label_strings();
+
+    let mut labels = label_set
+        .labels()
+        .iter()
+        .flat_map(|(node, ixs)| {
+            ixs.iter().map(move |&ix| (node.0, &strings[ix]))
+        })
+        .collect::<Vec<_>>();
+    labels.sort();
+
+    let labels = labels
+        .into_iter()
+        .map(|(node, text)| format!("[{}, {}]", node, json_str(text)))
+        .collect::<Vec<_>>();
+
+    format!(
+        "    {{ \"name\": {}, \"collection\": {}, \"column\": {}, \"path\": {}, \"visible\": {}, \"labels\": [{}] }}",
+        json_str(&label_set.label_set_name),
+        json_str(&label_set.annotation_name),
+        json_str(&label_set.column_str),
+        json_str(&label_set.path_name),
+        label_set.is_visible(),
+        labels.join(", ")
+    )
+}
+
+fn dynamic_f32(val: &rhai::Dynamic) -> Option<f32> {
+    val.as_float()
+        .ok()
+        .or_else(|| val.as_int().ok().map(|v| v as f32))
+}
@@ -146,6 +146,127 @@ impl LabelPos {
     }
 }
 
+/// Labels with higher priority are placed first when resolving
+/// overlaps, and are never hidden by lower priority labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LabelPriority {
+    Annotation,
+    Hover,
+    Selection,
+}
+
+#[derive(Clone)]
+struct LabelCandidate {
+    rect: Rect,
+    galley: std::sync::Arc<egui::epaint::text::Galley>,
+    priority: LabelPriority,
+}
+
+/// Collects all labels to be drawn in a frame, so that overlapping
+/// labels can be nudged out of the way, or hidden, before anything
+/// is drawn.
+#[derive(Default, Clone)]
+pub struct LabelLayout {
+    candidates: Vec<LabelCandidate>,
+}
+
+impl LabelLayout {
+    /// Adds a label centered at the given world point, laid out the
+    /// same way as `draw_text_at_world_point_offset`; labels that are
+    /// far outside the screen are skipped.
+    pub fn add_at_world_point(
+        &mut self,
+        ctx: &egui::CtxRef,
+        view: View,
+        world: Point,
+        screen_offset: Point,
+        text: &str,
+        priority: LabelPriority,
+    ) {
+        let screen_rect = ctx.input().screen_rect();
+
+        let screen_pos = view.world_point_to_screen(world);
+
+        let dims = Point::new(screen_rect.width(), screen_rect.height());
+
+        let mut screen_pos = screen_pos + dims / 2.0;
+        screen_pos += screen_offset;
+
+        if screen_pos.x > -screen_rect.width()
+            && screen_pos.x < 2.0 * screen_rect.width()
+            && screen_pos.y > -screen_rect.height()
+            && screen_pos.y < 2.0 * screen_rect.height()
+        {
+            let galley = ctx
+                .fonts()
+                .layout_no_wrap(egui::TextStyle::Body, text.to_owned());
+
+            let rect = egui::Align2::CENTER_CENTER.anchor_rect(
+                egui::Rect::from_min_size(screen_pos.into(), galley.size),
+            );
+
+            self.candidates.push(LabelCandidate {
+                rect: rect.into(),
+                galley,
+                priority,
+            });
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Places the labels greedily in priority order, trying a few
+    /// nudged positions for each label that overlaps one that's
+    /// already been placed, and hiding it if none of them fit.
+    ///
+    /// Returns the screen rectangles of the labels that were drawn.
+    pub fn draw(mut self, ctx: &egui::CtxRef) -> Vec<Rect> {
+        // the sort is stable, so labels with the same priority are
+        // placed in insertion order
+        self.candidates.sort_by(|a, b| b.priority.cmp(&a.priority));
+
+        let painter = ctx.layer_painter(painter_layer());
+        let color = ctx.style().visuals.text_color();
+
+        let mut placed: Vec<Rect> = Vec::with_capacity(self.candidates.len());
+
+        for candidate in self.candidates {
+            let rect = candidate.rect;
+            let (w, h) = (rect.width(), rect.height());
+
+            let nudges = [
+                Point::ZERO,
+                Point::new(0.0, h),
+                Point::new(0.0, -h),
+                Point::new(w, 0.0),
+                Point::new(-w, 0.0),
+            ];
+
+            let free = nudges.iter().find_map(|&delta| {
+                let nudged = Rect::new(rect.min() + delta, rect.max() + delta);
+                // shrink the rectangle a bit when testing, so that
+                // labels that just touch aren't considered overlapping
+                let test = nudged.resize(0.9);
+                let overlaps = placed.iter().any(|p| p.intersects(test));
+                (!overlaps).then(|| nudged)
+            });
+
+            if let Some(rect) = free {
+                painter.galley(rect.min().into(), candidate.galley, color);
+                placed.push(rect);
+            }
+        }
+
+        placed
+    }
+}
+
 pub fn offset_align(dir: &Point) -> egui::Align2 {
     let norm = *dir / dir.length();
 
@@ -337,6 +458,40 @@ pub fn draw_rect_world(
     painter.rect_stroke(Rect::new(s0, s1).into(), 0.0, stroke);
 }
 
+pub fn draw_line_world(
+    ctx: &egui::CtxRef,
+    view: View,
+    p0: Point,
+    p1: Point,
+    color: Option<rgb::RGBA<f32>>,
+) {
+    let screen_rect = ctx.input().screen_rect();
+
+    let mut s0 = view.world_point_to_screen(p0);
+    let mut s1 = view.world_point_to_screen(p1);
+
+    let offset = Point::new(screen_rect.width(), screen_rect.height()) / 2.0;
+
+    s0 += offset;
+    s1 += offset;
+
+    let painter = ctx.layer_painter(painter_layer());
+
+    let color = color
+        .map(|c| {
+            let r = (c.r * 255.0) as u8;
+            let g = (c.g * 255.0) as u8;
+            let b = (c.b * 255.0) as u8;
+            let a = (c.a * 255.0) as u8;
+            egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+        })
+        .unwrap_or(egui::Color32::from_rgb(128, 128, 128));
+
+    let stroke = egui::Stroke::new(2.0, color);
+
+    painter.line_segment([s0.into(), s1.into()], stroke);
+}
+
 pub fn draw_rect<R: Into<egui::Rect>>(ctx: &egui::CtxRef, rect: R) {
     let painter = ctx.layer_painter(painter_layer());
 
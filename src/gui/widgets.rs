@@ -9,20 +9,76 @@ use handlegraph::{
 };
 use rustc_hash::FxHashMap;
 
-use crate::{app::AppMsg, overlays::OverlayKind};
 use crate::{app::OverlayState, geometry::*};
+use crate::{
+    app::{AppMsg, Select},
+    graph_query::GraphQuery,
+    overlays::OverlayKind,
+    universe::LayoutSource,
+};
 
 pub trait Widget {
     fn id() -> &'static str;
 
+    /// Show the widget anchored to the given corner of the screen,
+    /// moved by `offset`
     fn ui(
         &self,
         ctx: &egui::CtxRef,
-        pos: Point,
+        corner: Corner,
+        offset: Point,
         size: Option<Point>,
     ) -> Option<egui::InnerResponse<Option<()>>>;
 }
 
+/// The screen corners that info widgets, such as the graph stats,
+/// can be pinned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub fn align(&self) -> egui::Align2 {
+        match self {
+            Corner::TopLeft => egui::Align2::LEFT_TOP,
+            Corner::TopRight => egui::Align2::RIGHT_TOP,
+            Corner::BottomLeft => egui::Align2::LEFT_BOTTOM,
+            Corner::BottomRight => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+
+    pub fn is_top(&self) -> bool {
+        matches!(self, Corner::TopLeft | Corner::TopRight)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Corner::TopLeft => "top_left",
+            Corner::TopRight => "top_right",
+            Corner::BottomLeft => "bottom_left",
+            Corner::BottomRight => "bottom_right",
+        }
+    }
+}
+
+impl std::str::FromStr for Corner {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "top_left" => Ok(Corner::TopLeft),
+            "top_right" => Ok(Corner::TopRight),
+            "bottom_left" => Ok(Corner::BottomLeft),
+            "bottom_right" => Ok(Corner::BottomRight),
+            _ => Err(()),
+        }
+    }
+}
+
 pub struct MenuBar {
     overlay_state: OverlayState,
 
@@ -72,6 +128,7 @@ impl MenuBar {
 
         let nodes = &mut open_windows.nodes;
         let paths = &mut open_windows.paths;
+        let selection_stats = &mut open_windows.selection_stats;
 
         let _themes = &mut open_windows.themes;
         let overlays = &mut open_windows.overlays;
@@ -88,6 +145,13 @@ impl MenuBar {
                     if ui.selectable_label(*paths, "Paths").clicked() {
                         *paths = !*paths;
                     }
+
+                    if ui
+                        .selectable_label(*selection_stats, "Selection")
+                        .clicked()
+                    {
+                        *selection_stats = !*selection_stats;
+                    }
                 });
 
                 menu::menu(ui, "Annotations", |ui| {
@@ -160,6 +224,82 @@ impl MenuBar {
     }
 }
 
+/// Text field for jumping to a node by its ID
+#[derive(Debug, Default, Clone)]
+pub struct NodeSearch {
+    input: String,
+
+    // set when the user enters an ID that isn't in the graph, to
+    // briefly highlight the text field
+    error_since: Option<std::time::Instant>,
+}
+
+impl NodeSearch {
+    pub const ID: &'static str = "node_search_box";
+
+    const ERROR_DURATION: std::time::Duration =
+        std::time::Duration::from_millis(500);
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        pos: Point,
+        graph_query: &GraphQuery,
+        app_msg_tx: &Sender<AppMsg>,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if let Some(since) = self.error_since {
+            if since.elapsed() > Self::ERROR_DURATION {
+                self.error_since = None;
+            }
+        }
+
+        egui::Window::new(Self::ID)
+            .title_bar(false)
+            .collapsible(false)
+            .auto_sized()
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Node");
+
+                    let mut text_edit =
+                        egui::TextEdit::singleline(&mut self.input)
+                            .desired_width(100.0);
+
+                    if self.error_since.is_some() {
+                        text_edit = text_edit.text_color(egui::Color32::RED);
+                    }
+
+                    let resp = ui.add(text_edit);
+
+                    if resp.lost_focus()
+                        && ui.input().key_pressed(egui::Key::Enter)
+                    {
+                        let node = self
+                            .input
+                            .trim()
+                            .parse::<u64>()
+                            .ok()
+                            .map(NodeId::from)
+                            .filter(|&id| graph_query.graph.has_node(id));
+
+                        if let Some(node) = node {
+                            app_msg_tx.send(AppMsg::GotoNode(node)).unwrap();
+                            app_msg_tx
+                                .send(AppMsg::Selection(Select::One {
+                                    node,
+                                    clear: true,
+                                }))
+                                .unwrap();
+                        } else {
+                            self.error_since = Some(std::time::Instant::now());
+                        }
+                    }
+                });
+            })
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct NodeInfo {
     node_id: NodeId,
@@ -193,25 +333,17 @@ impl Widget for FrameRate {
     fn ui(
         &self,
         ctx: &egui::CtxRef,
-        pos: Point,
+        corner: Corner,
+        offset: Point,
         _size: Option<Point>,
     ) -> Option<egui::InnerResponse<Option<()>>> {
-        let scr = ctx.input().screen_rect();
-
         let width = 100.0;
 
-        let rect = egui::Rect {
-            min: pos.into(),
-            max: Point {
-                x: scr.max.x,
-                y: pos.y + 100.0,
-            }
-            .into(),
-        };
-
         egui::Window::new(Self::id())
-            .fixed_rect(rect)
             .title_bar(false)
+            .collapsible(false)
+            .auto_sized()
+            .anchor(corner.align(), offset)
             .show(ctx, |ui| {
                 ui.set_min_width(width);
 
@@ -227,6 +359,45 @@ pub struct GraphStats {
     pub edge_count: usize,
     pub path_count: usize,
     pub total_len: usize,
+    pub layout_source: LayoutSource,
+    /// The average number of base pairs per world unit in the
+    /// layout, used by the scale bar
+    pub bp_per_unit: f32,
+    pub memory: MemoryUsage,
+}
+
+/// Approximate GPU memory used by the main view, in bytes
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemoryUsage {
+    pub vertex_buffer: usize,
+    pub overlay_buffers: usize,
+    pub overlay_count: usize,
+    pub node_id_buffer: usize,
+    pub node_id_dims: (u32, u32),
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize {
+        self.vertex_buffer + self.overlay_buffers + self.node_id_buffer
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
 }
 
 impl Widget for GraphStats {
@@ -238,19 +409,56 @@ impl Widget for GraphStats {
     fn ui(
         &self,
         ctx: &egui::CtxRef,
-        pos: Point,
+        corner: Corner,
+        offset: Point,
         _size: Option<Point>,
     ) -> Option<egui::InnerResponse<Option<()>>> {
         egui::Window::new(Self::id())
             .title_bar(false)
             .collapsible(false)
             .auto_sized()
-            .fixed_pos(pos)
+            .anchor(corner.align(), offset)
             .show(ctx, |ui| {
                 ui.label(format!("Nodes: {}", self.node_count));
                 ui.label(format!("Edges: {}", self.edge_count));
                 ui.label(format!("Paths: {}", self.path_count));
                 ui.label(format!("Total length: {}", self.total_len));
+                ui.label(format!("Layout: {}", self.layout_source.as_str()));
+
+                let mem = &self.memory;
+
+                if mem.total() > 0 {
+                    ui.separator();
+
+                    // the per-node cost, which grows with each overlay
+                    let per_node = if self.node_count > 0 {
+                        (mem.vertex_buffer + mem.overlay_buffers)
+                            / self.node_count
+                    } else {
+                        0
+                    };
+
+                    ui.label(format!(
+                        "Memory: {} ({} B/node)",
+                        format_bytes(mem.total()),
+                        per_node
+                    ));
+                    ui.label(format!(
+                        "Vertices: {}",
+                        format_bytes(mem.vertex_buffer)
+                    ));
+                    ui.label(format!(
+                        "Overlays: {} ({})",
+                        format_bytes(mem.overlay_buffers),
+                        mem.overlay_count
+                    ));
+                    ui.label(format!(
+                        "Node IDs: {} ({}x{})",
+                        format_bytes(mem.node_id_buffer),
+                        mem.node_id_dims.0,
+                        mem.node_id_dims.1
+                    ));
+                }
             })
     }
 }
@@ -261,6 +469,9 @@ pub struct GraphStatsMsg {
     pub edge_count: Option<usize>,
     pub path_count: Option<usize>,
     pub total_len: Option<usize>,
+    pub layout_source: Option<LayoutSource>,
+    pub bp_per_unit: Option<f32>,
+    pub memory: Option<MemoryUsage>,
 }
 
 impl GraphStats {
@@ -270,6 +481,106 @@ impl GraphStats {
             edge_count: msg.edge_count.unwrap_or(self.edge_count),
             path_count: msg.path_count.unwrap_or(self.path_count),
             total_len: msg.total_len.unwrap_or(self.total_len),
+            layout_source: msg.layout_source.unwrap_or(self.layout_source),
+            bp_per_unit: msg.bp_per_unit.unwrap_or(self.bp_per_unit),
+            memory: msg.memory.unwrap_or(self.memory),
+        }
+    }
+}
+
+/// A bar labeled with the number of base pairs it spans at the
+/// current zoom level
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScaleBar {
+    /// The number of base pairs per world unit in the layout
+    pub bp_per_unit: f32,
+    /// The view scale, in world units per pixel
+    pub view_scale: f32,
+}
+
+impl ScaleBar {
+    /// The bar is at most this wide, in pixels
+    const MAX_WIDTH: f32 = 150.0;
+
+    /// Returns the bar length in base pairs, rounded down to 1, 2,
+    /// or 5 times a power of ten, and its width in pixels
+    fn bar_length(&self) -> Option<(f32, f32)> {
+        let bp_per_px = self.bp_per_unit * self.view_scale;
+
+        if !bp_per_px.is_finite() || bp_per_px <= 0.0 {
+            return None;
         }
+
+        let bp = round_bp_length(bp_per_px * Self::MAX_WIDTH);
+
+        Some((bp, bp / bp_per_px))
+    }
+}
+
+/// Rounds a length in base pairs down to 1, 2, or 5 times a power of
+/// ten, and at least 1
+pub(crate) fn round_bp_length(max_bp: f32) -> f32 {
+    let magnitude = 10f32.powf(max_bp.log10().floor());
+
+    [5.0, 2.0, 1.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|&bp| bp <= max_bp)
+        .unwrap_or(magnitude)
+        .max(1.0)
+}
+
+impl Widget for ScaleBar {
+    #[inline]
+    fn id() -> &'static str {
+        "scale_bar_box"
+    }
+
+    fn ui(
+        &self,
+        ctx: &egui::CtxRef,
+        corner: Corner,
+        offset: Point,
+        _size: Option<Point>,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let (bp, width) = self.bar_length()?;
+
+        let label = if bp >= 1_000_000.0 {
+            format!("{} Mbp", bp / 1_000_000.0)
+        } else if bp >= 1_000.0 {
+            format!("{} kbp", bp / 1_000.0)
+        } else {
+            format!("{} bp", bp)
+        };
+
+        egui::Window::new(Self::id())
+            .title_bar(false)
+            .collapsible(false)
+            .auto_sized()
+            .anchor(corner.align(), offset)
+            .show(ctx, |ui| {
+                let (rect, _resp) = ui.allocate_exact_size(
+                    egui::vec2(width, 8.0),
+                    egui::Sense::hover(),
+                );
+
+                let stroke = egui::Stroke::new(2.0, ui.visuals().text_color());
+                let painter = ui.painter();
+
+                painter.line_segment(
+                    [rect.left_bottom(), rect.right_bottom()],
+                    stroke,
+                );
+                painter.line_segment(
+                    [rect.left_top(), rect.left_bottom()],
+                    stroke,
+                );
+                painter.line_segment(
+                    [rect.right_top(), rect.right_bottom()],
+                    stroke,
+                );
+
+                ui.label(label);
+            })
     }
 }
@@ -1,19 +1,23 @@
 pub mod annotations;
+pub mod command_palette;
 pub mod file;
 pub mod filters;
 pub mod graph_details;
 pub mod graph_picker;
 pub mod overlays;
 pub mod paths;
+pub mod selection;
 pub mod settings;
 pub mod util;
 
 pub use annotations::*;
+pub use command_palette::*;
 pub use file::*;
 pub use filters::*;
 pub use graph_details::*;
 pub use graph_picker::*;
 pub use overlays::*;
 pub use paths::*;
+pub use selection::*;
 pub use settings::*;
 pub use util::*;
@@ -20,6 +20,7 @@ use handlegraph::{
     mutablehandlegraph::*,
     packed::*,
     packedgraph::index::OneBasedIndex,
+    packedgraph::paths::StepPtr,
     packedgraph::*,
     path_position::*,
     pathhandlegraph::*,
@@ -751,6 +752,7 @@ where
                     .send(OverlayCreatorMsg::NewOverlay {
                         name: input.name,
                         data: overlay_data,
+                        legend: None,
                     })
                     .unwrap();
 
@@ -993,21 +995,14 @@ where
         log::trace!("getting record");
         let record = records.records().get(record_ix)?;
 
-        if let Some(range) = crate::annotations::path_step_range(
+        add_record_label(
             &steps,
             offset,
-            record.start(),
-            record.end(),
-        ) {
-            if let Some(value) = record.get_first(column) {
-                if let Some((mid, _, _)) = range.get(range.len() / 2) {
-                    let index = label_strings.len();
-                    let label = format!("{}", value.as_bstr());
-                    label_strings.push(label);
-                    label_indices.entry(mid.id()).or_default().push(index);
-                }
-            }
-        }
+            record,
+            column,
+            &mut label_strings,
+            &mut label_indices,
+        );
     }
 
     for labels in label_indices.values_mut() {
@@ -1029,3 +1024,118 @@ where
         label_indices,
     ))
 }
+
+/// Like `calculate_annotation_set`, but each record is placed on the
+/// path its sequence ID maps to in `seq_id_paths`, so that a single
+/// label set can cover several paths; records with unmapped sequence
+/// IDs are skipped.
+///
+/// The label set's path is the path of the first record that was
+/// placed.
+pub(crate) fn calculate_annotation_set_by_seq_id<C>(
+    graph: &GraphQuery,
+    records: &C,
+    record_indices: &[usize],
+    seq_id_paths: &HashMap<String, PathId>,
+    column: &C::ColumnKey,
+    label_set_name: &str,
+) -> Option<AnnotationLabelSet>
+where
+    C: AnnotationCollection + Send + Sync + 'static,
+{
+    if record_indices.is_empty() {
+        return None;
+    }
+
+    let mut path_steps: FxHashMap<
+        PathId,
+        (Option<usize>, Vec<(Handle, StepPtr, usize)>),
+    > = FxHashMap::default();
+
+    let mut first_path: Option<PathId> = None;
+
+    let mut label_strings: Vec<String> =
+        Vec::with_capacity(record_indices.len());
+    let mut label_indices: FxHashMap<NodeId, Vec<usize>> = FxHashMap::default();
+
+    for &record_ix in record_indices.iter() {
+        let record = records.records().get(record_ix)?;
+
+        let seq_id = format!("{}", record.seq_id().as_bstr());
+
+        let path_id = if let Some(path_id) = seq_id_paths.get(&seq_id) {
+            *path_id
+        } else {
+            continue;
+        };
+
+        if !path_steps.contains_key(&path_id) {
+            let path_name = graph.graph().get_path_name_vec(path_id)?;
+            let offset = crate::annotations::path_name_offset(&path_name);
+            let steps = graph.path_pos_steps(path_id)?;
+            path_steps.insert(path_id, (offset, steps));
+        }
+
+        let (offset, steps) = path_steps.get(&path_id)?;
+
+        first_path.get_or_insert(path_id);
+
+        add_record_label(
+            steps,
+            *offset,
+            record,
+            column,
+            &mut label_strings,
+            &mut label_indices,
+        );
+    }
+
+    let path_id = first_path?;
+    let path_name = graph.graph().get_path_name_vec(path_id)?;
+
+    for labels in label_indices.values_mut() {
+        labels.sort();
+        labels.dedup();
+        labels.shrink_to_fit();
+    }
+
+    label_strings.shrink_to_fit();
+    label_indices.shrink_to_fit();
+
+    Some(AnnotationLabelSet::new(
+        records,
+        path_id,
+        &path_name,
+        column,
+        label_set_name,
+        label_strings,
+        label_indices,
+    ))
+}
+
+/// Adds the record's label, taken from `column`, at the node in the
+/// middle of the record's range on the path with the given steps
+fn add_record_label<R: AnnotationRecord>(
+    steps: &[(Handle, StepPtr, usize)],
+    offset: Option<usize>,
+    record: &R,
+    column: &R::ColumnKey,
+    label_strings: &mut Vec<String>,
+    label_indices: &mut FxHashMap<NodeId, Vec<usize>>,
+) {
+    if let Some(range) = crate::annotations::path_step_range(
+        steps,
+        offset,
+        record.start(),
+        record.end(),
+    ) {
+        if let Some(value) = record.get_first(column) {
+            if let Some((mid, _, _)) = range.get(range.len() / 2) {
+                let index = label_strings.len();
+                let label = format!("{}", value.as_bstr());
+                label_strings.push(label);
+                label_indices.entry(mid.id()).or_default().push(index);
+            }
+        }
+    }
+}
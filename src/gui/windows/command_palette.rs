@@ -0,0 +1,184 @@
+use crate::app::OverlayState;
+use crate::gui::Windows;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PaletteAction {
+    ResetView,
+    GotoSelection,
+    FitSelection,
+    FitGraph,
+    ClearSelection,
+    ToggleDarkMode,
+    ToggleOverlay,
+    OpenConsole,
+    OpenWindow(Windows),
+}
+
+const ACTIONS: &[(&str, PaletteAction)] = &[
+    ("Reset view", PaletteAction::ResetView),
+    ("Go to selection", PaletteAction::GotoSelection),
+    ("Fit selection", PaletteAction::FitSelection),
+    ("Fit graph", PaletteAction::FitGraph),
+    ("Clear selection", PaletteAction::ClearSelection),
+    ("Toggle dark mode", PaletteAction::ToggleDarkMode),
+    ("Toggle overlay", PaletteAction::ToggleOverlay),
+    ("Open console", PaletteAction::OpenConsole),
+    (
+        "Open settings",
+        PaletteAction::OpenWindow(Windows::Settings),
+    ),
+    ("Open node list", PaletteAction::OpenWindow(Windows::Nodes)),
+    (
+        "Open node details",
+        PaletteAction::OpenWindow(Windows::NodeDetails),
+    ),
+    ("Open path list", PaletteAction::OpenWindow(Windows::Paths)),
+    (
+        "Open selection statistics",
+        PaletteAction::OpenWindow(Windows::SelectionStats),
+    ),
+    (
+        "Open overlay list",
+        PaletteAction::OpenWindow(Windows::Overlays),
+    ),
+    (
+        "Open annotation records",
+        PaletteAction::OpenWindow(Windows::AnnotationRecords),
+    ),
+];
+
+/// A fuzzy-searchable list of high level actions, opened with a
+/// hotkey; the selected action is returned from `ui` and applied by
+/// the `Gui`
+#[derive(Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+
+    // the overlay that was active before `ToggleOverlay` disabled it
+    last_overlay: Option<usize>,
+}
+
+impl CommandPalette {
+    pub const ID: &'static str = "command_palette";
+    pub const ID_TEXT: &'static str = "command_palette_text";
+
+    pub fn toggle(&mut self, ctx: &egui::CtxRef) {
+        self.open = !self.open;
+
+        if self.open {
+            self.query.clear();
+            self.selected = 0;
+            ctx.memory()
+                .request_focus(egui::Id::new(CommandPalette::ID_TEXT));
+        }
+    }
+
+    /// Disables the active overlay, or restores the overlay that was
+    /// disabled last. Returns `false` if there was no overlay to
+    /// restore.
+    pub fn toggle_overlay(&mut self, overlay_state: &OverlayState) -> bool {
+        if let Some(current) = overlay_state.current_overlay() {
+            self.last_overlay = Some(current);
+            overlay_state.set_current_overlay(None);
+            true
+        } else if let Some(last) = self.last_overlay {
+            overlay_state.set_current_overlay(Some(last));
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::CtxRef) -> Option<PaletteAction> {
+        if !self.open {
+            return None;
+        }
+
+        let mut matches = ACTIONS
+            .iter()
+            .filter_map(|&(name, action)| {
+                let score = fuzzy_score(&self.query, name)?;
+                Some((score, name, action))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|&(score, _, _)| score);
+
+        if ctx.input().key_pressed(egui::Key::ArrowDown) {
+            self.selected += 1;
+        }
+        if ctx.input().key_pressed(egui::Key::ArrowUp) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let mut chosen = None;
+
+        let query = &mut self.query;
+        let selected = &mut self.selected;
+
+        egui::Window::new("Command palette")
+            .id(egui::Id::new(CommandPalette::ID))
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::Vec2::new(0.0, 80.0))
+            .show(ctx, |ui| {
+                let text_resp = ui.add(
+                    egui::TextEdit::singleline(query)
+                        .id(egui::Id::new(CommandPalette::ID_TEXT))
+                        .desired_width(400.0),
+                );
+
+                if text_resp.changed() {
+                    *selected = 0;
+                }
+
+                ui.separator();
+
+                if matches.is_empty() {
+                    ui.label("No matching commands");
+                }
+
+                for (ix, &(_, name, action)) in matches.iter().enumerate() {
+                    if ui.selectable_label(ix == *selected, name).clicked() {
+                        chosen = Some(action);
+                    }
+                }
+            });
+
+        if ctx.input().key_pressed(egui::Key::Enter) {
+            chosen = matches.get(self.selected).map(|&(_, _, action)| action);
+        }
+
+        if chosen.is_some() || ctx.input().key_pressed(egui::Key::Escape) {
+            self.open = false;
+        }
+
+        chosen
+    }
+}
+
+/// Scores `text` as a case-insensitive subsequence match of `query`,
+/// lower is better; characters skipped before and between the
+/// matched characters are penalized. Returns `None` if `text` doesn't
+/// match.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut text_chars = text.chars().flat_map(char::to_lowercase).enumerate();
+
+    let mut score = 0;
+    let mut next_ix = 0;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+
+        let (ix, _) = text_chars.find(|&(_, c)| c == q)?;
+        score += ix - next_ix;
+        next_ix = ix + 1;
+    }
+
+    Some(score)
+}
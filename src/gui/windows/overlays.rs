@@ -1,5 +1,6 @@
 use std::io::Read;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use crossbeam::atomic::AtomicCell;
 
@@ -18,7 +19,7 @@ use crate::{
 };
 
 use crate::app::{OverlayCreatorMsg, OverlayState};
-use crate::overlays::OverlayKind;
+use crate::overlays::{OverlayKind, OverlayLegend, Palette};
 
 use super::file::FilePicker;
 
@@ -30,12 +31,18 @@ pub struct OverlayList {
     gradient_picker: GradientPicker,
 
     gradient_picker_open: AtomicCell<bool>,
+    legend_open: AtomicCell<bool>,
+
+    palette: Arc<AtomicCell<Palette>>,
 }
 
 impl OverlayList {
     pub const ID: &'static str = "overlay_list_window";
 
-    pub fn new(overlay_state: OverlayState) -> Self {
+    pub fn new(
+        overlay_state: OverlayState,
+        palette: Arc<AtomicCell<Palette>>,
+    ) -> Self {
         let gradient_picker = GradientPicker::new(overlay_state.clone());
 
         Self {
@@ -45,6 +52,9 @@ impl OverlayList {
             gradient_picker,
 
             gradient_picker_open: AtomicCell::new(false),
+            legend_open: AtomicCell::new(false),
+
+            palette,
         }
     }
 
@@ -84,6 +94,11 @@ impl OverlayList {
                     {
                         self.gradient_picker_open.store(!open_gradient_picker);
                     }
+
+                    let open_legend = self.legend_open.load();
+                    if ui.selectable_label(open_legend, "Legend").clicked() {
+                        self.legend_open.store(!open_legend);
+                    }
                 });
 
                 egui::Grid::new("overlay_list_window_grid").show(
@@ -128,6 +143,91 @@ impl OverlayList {
         self.gradient_picker_open.store(open);
         resp
     }
+
+    pub fn legend_ui(
+        &self,
+        ctx: &egui::CtxRef,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let mut open = self.legend_open.load();
+
+        let current_overlay = self.overlay_state.current_overlay();
+
+        let overlay = current_overlay.and_then(|id| {
+            let (kind, name) = self.overlay_names.get(&id)?;
+            Some((*kind, name, self.overlay_state.legend(id)))
+        });
+
+        let resp = egui::Window::new("Overlay Legend")
+            .id(egui::Id::new("overlay_legend_window"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let (kind, name, legend) = if let Some(overlay) = overlay {
+                    overlay
+                } else {
+                    ui.label("No active overlay");
+                    return;
+                };
+
+                ui.heading(name);
+
+                match (kind, legend) {
+                    (_, Some(OverlayLegend::Categories(categories))) => {
+                        // the swatches are remapped the same way as
+                        // the overlay colors on the nodes
+                        let palette = self.palette.load();
+
+                        egui::Grid::new("overlay_legend_categories").show(
+                            ui,
+                            |ui| {
+                                for (category, color) in categories {
+                                    let (rect, _) = ui.allocate_exact_size(
+                                        egui::vec2(16.0, 16.0),
+                                        egui::Sense::hover(),
+                                    );
+
+                                    let rgb = palette.apply(color.rgb());
+                                    let color =
+                                        egui::Rgba::from_rgba_premultiplied(
+                                            rgb.r, rgb.g, rgb.b, color.a,
+                                        );
+                                    ui.painter().rect_filled(rect, 2.0, color);
+
+                                    ui.label(category);
+                                    ui.end_row();
+                                }
+                            },
+                        );
+                    }
+                    (OverlayKind::Value, legend) => {
+                        let (min, max) = match legend {
+                            Some(OverlayLegend::Range { min, max }) => {
+                                (min, max)
+                            }
+                            _ => (0.0, 1.0),
+                        };
+
+                        let gradient = self.overlay_state.gradient();
+
+                        ui.image(
+                            gradient.texture_id(),
+                            Point { x: 130.0, y: 15.0 },
+                        );
+
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}", min));
+                            ui.separator();
+                            ui.label(format!("{}", max));
+                        });
+                    }
+                    (OverlayKind::RGB, _) => {
+                        ui.label("This overlay has no legend");
+                    }
+                }
+            });
+
+        self.legend_open.store(open);
+        resp
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -226,6 +326,7 @@ impl OverlayCreator {
                             let msg = OverlayCreatorMsg::NewOverlay {
                                 name: input.name,
                                 data,
+                                legend: None,
                             };
                             tx.send(msg).unwrap();
                             Ok(())
@@ -0,0 +1,98 @@
+use handlegraph::{
+    handle::{Handle, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use rustc_hash::FxHashSet;
+
+use crate::geometry::*;
+use crate::graph_query::GraphQuery;
+
+/// Aggregate statistics of the selected nodes, computed when the
+/// selection changes while the window is open, or when the window is
+/// opened after the selection changed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SelectionStats {
+    pub node_count: usize,
+    pub total_len: usize,
+    pub path_count: usize,
+    /// The width and height of the selection bounding box, in world
+    /// units
+    pub bounds_size: Point,
+}
+
+impl SelectionStats {
+    const ID: &'static str = "selection_stats_window";
+
+    pub fn new<'a, I>(
+        graph_query: &GraphQuery,
+        nodes: I,
+        bounds: Option<(Point, Point)>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = &'a NodeId>,
+    {
+        let graph = graph_query.graph();
+
+        let mut node_count = 0;
+        let mut total_len = 0;
+        let mut paths: FxHashSet<PathId> = FxHashSet::default();
+
+        for &node in nodes {
+            let handle = Handle::pack(node, false);
+
+            node_count += 1;
+            total_len += graph.node_len(handle);
+
+            if let Some(steps) = graph.steps_on_handle(handle) {
+                paths.extend(steps.map(|(path, _)| path));
+            }
+        }
+
+        let bounds_size = bounds
+            .map(|(p0, p1)| Rect::new(p0, p1))
+            .map(|rect| Point::new(rect.width(), rect.height()))
+            .unwrap_or(Point::ZERO);
+
+        Self {
+            node_count,
+            total_len,
+            path_count: paths.len(),
+            bounds_size,
+        }
+    }
+
+    pub fn ui(&self, ctx: &egui::CtxRef, open: &mut bool) {
+        egui::Window::new("Selection")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                if self.node_count == 0 {
+                    ui.label("No nodes selected");
+                    return;
+                }
+
+                egui::Grid::new("selection_stats_grid").show(ui, |ui| {
+                    ui.label("Nodes");
+                    ui.label(self.node_count.to_string());
+                    ui.end_row();
+
+                    ui.label("Total length");
+                    ui.label(format!("{} bp", self.total_len));
+                    ui.end_row();
+
+                    ui.label("Paths");
+                    ui.label(self.path_count.to_string());
+                    ui.end_row();
+
+                    ui.label("Bounding box");
+                    ui.label(format!(
+                        "{:.1} x {:.1}",
+                        self.bounds_size.x, self.bounds_size.y
+                    ));
+                    ui.end_row();
+                });
+            });
+    }
+}
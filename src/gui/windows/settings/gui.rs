@@ -1,6 +1,8 @@
 pub struct GuiSettings {
     pub(crate) show_fps: bool,
     pub(crate) show_graph_stats: bool,
+    pub(crate) show_node_search: bool,
+    pub(crate) show_scale_bar: bool,
 }
 
 impl std::default::Default for GuiSettings {
@@ -8,6 +10,8 @@ impl std::default::Default for GuiSettings {
         Self {
             show_fps: false,
             show_graph_stats: false,
+            show_node_search: true,
+            show_scale_bar: false,
         }
     }
 }
@@ -16,5 +20,7 @@ impl GuiSettings {
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ui.checkbox(&mut self.show_fps, "Display FPS");
         ui.checkbox(&mut self.show_graph_stats, "Display graph stats");
+        ui.checkbox(&mut self.show_node_search, "Display node search");
+        ui.checkbox(&mut self.show_scale_bar, "Display scale bar");
     }
 }
@@ -53,6 +53,17 @@ pub struct InputManager {
     main_view: SubsystemInput<MainViewInput>,
     gui: SubsystemInput<GuiInput>,
 
+    // the pan and select buttons the main view bindings were built
+    // with, see `set_main_view_buttons`
+    main_view_buttons: (event::MouseButton, event::MouseButton),
+
+    // while the ruler is active, pan button clicks place ruler points
+    // and aren't passed to the main view; see `set_ruler_mode`
+    ruler_mode: bool,
+    // whether the last pan button press was held back from the main
+    // view, so that its release is as well
+    ruler_click: AtomicCell<bool>,
+
     gui_focus_state: crate::gui::GuiFocusState,
 
     custom_binds: FxHashMap<
@@ -103,6 +114,31 @@ impl InputManager {
         self.custom_binds.insert(key_code, boxed);
     }
 
+    /// Sets whether the ruler is active; if it is, clicks with the
+    /// pan button only place ruler points, without panning or
+    /// selecting in the main view
+    pub fn set_ruler_mode(&mut self, ruler_mode: bool) {
+        self.ruler_mode = ruler_mode;
+    }
+
+    /// Replaces the main view mouse bindings, if the buttons differ
+    /// from the ones currently used for panning and selection; ruler
+    /// points are placed with the pan button
+    pub fn set_main_view_buttons(
+        &mut self,
+        pan_button: event::MouseButton,
+        select_button: event::MouseButton,
+    ) {
+        if self.main_view_buttons == (pan_button, select_button) {
+            return;
+        }
+
+        self.main_view.bindings =
+            MainViewInput::binds_with_buttons(pan_button, select_button);
+        self.app.bindings = AppInput::binds_with_ruler_button(pan_button);
+        self.main_view_buttons = (pan_button, select_button);
+    }
+
     pub fn handle_events(
         &self,
         reactor: &mut Reactor,
@@ -178,7 +214,10 @@ impl InputManager {
                 self.app.bindings.apply(&winit_ev, modifiers, mouse_pos)
             {
                 for input in app_inputs {
-                    if !(input.is_keyboard() && gui_wants_keyboard) {
+                    if (input.is_keyboard() && !gui_wants_keyboard)
+                        || (input.is_mouse() && !mouse_over_gui)
+                        || input.is_mouse_up()
+                    {
                         self.app.tx.send(input).unwrap();
                     }
                 }
@@ -192,10 +231,23 @@ impl InputManager {
                 }
             }
 
+            let ruler_click = match winit_ev {
+                event::WindowEvent::MouseInput { state, button, .. }
+                    if button == self.main_view_buttons.0 =>
+                {
+                    if state == ElementState::Pressed {
+                        self.ruler_click.store(self.ruler_mode);
+                    }
+                    self.ruler_click.load()
+                }
+                _ => false,
+            };
+
             if let Some(main_view_inputs) = self
                 .main_view
                 .bindings
                 .apply(&winit_ev, modifiers, mouse_pos)
+                .filter(|_| !ruler_click)
             {
                 for input in main_view_inputs {
                     if (input.is_keyboard() && !gui_wants_keyboard)
@@ -251,6 +303,14 @@ impl InputManager {
             main_view,
             gui,
 
+            main_view_buttons: (
+                event::MouseButton::Left,
+                event::MouseButton::Right,
+            ),
+
+            ruler_mode: false,
+            ruler_click: AtomicCell::new(false),
+
             gui_focus_state,
 
             custom_binds: FxHashMap::default(),
@@ -7,7 +7,7 @@ use gfaestus::context::{ContextEntry, ContextMenu};
 use gfaestus::quad_tree::QuadTree;
 use gfaestus::reactor::{ModalError, ModalHandler, ModalSuccess, Reactor};
 use gfaestus::vulkan::context::EdgeRendererType;
-use gfaestus::vulkan::draw_system::edges::EdgeRenderer;
+use gfaestus::vulkan::draw_system::edges::{EdgeColorMode, EdgeRenderer};
 use gfaestus::vulkan::texture::{Gradients, Gradients_};
 
 use parking_lot::RwLock;
@@ -20,26 +20,29 @@ use winit::event_loop::ControlFlow;
 #[allow(unused_imports)]
 use winit::window::{Window, WindowBuilder};
 
-use gfaestus::app::{mainview::*, Args, OverlayCreatorMsg, Select};
-use gfaestus::app::{App, AppMsg};
+use gfaestus::app::{
+    mainview::*, Args, OverlayCreatorMsg, OverlayState, Select,
+};
+use gfaestus::app::{bench::Benchmark, window_title, App, AppMsg};
 use gfaestus::geometry::*;
 use gfaestus::graph_query::*;
 use gfaestus::input::*;
 use gfaestus::overlays::*;
 use gfaestus::universe::*;
-use gfaestus::view::View;
+use gfaestus::view::{ScreenDims, View};
 use gfaestus::vulkan::render_pass::Framebuffers;
 
 use gfa_modified::optfields::OptField;
 use std::convert::TryFrom;
 
+use gfaestus::gui::text::LabelLayout;
 use gfaestus::gui::{widgets::*, windows::*, *};
 
 use gfaestus::vulkan::debug;
 
 #[allow(unused_imports)]
 use gfaestus::vulkan::draw_system::{
-    nodes::Overlay, post::PostProcessPipeline,
+    nodes::Overlay, nodes::PathRibbons, post::PostProcessPipeline,
 };
 
 use gfaestus::vulkan::draw_system::selection::{
@@ -50,7 +53,7 @@ use gfaestus::vulkan::compute::{
     ComputeManager, GpuSelection, NodeTranslation,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 use ash::version::DeviceV1_0;
 use ash::{vk, Device};
@@ -79,24 +82,65 @@ use flexi_logger::{Duplicate, FileSpec, Logger, LoggerHandle};
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 
+// uses the layout stored in the GFA tags if every node has one, and
+// otherwise the layout file
 fn universe_from_gfa_layout(
     graph_query: &GraphQuery,
-    layout_path: &str,
+    layout_path: Option<&str>,
 ) -> Result<(Universe<FlatLayout>, GraphStats)> {
     let graph = graph_query.graph();
 
-    let universe = Universe::from_laid_out_graph(&graph, layout_path)?;
+    let tag_universe =
+        Universe::from_gfa_tags(&graph, &graph_query.tag_hash_map);
+
+    let (universe, layout_source) = match (tag_universe, layout_path) {
+        (Some(universe), _) => (universe, LayoutSource::GfaTags),
+        (None, Some(layout_path)) => {
+            let universe = Universe::from_laid_out_graph(&graph, layout_path)?;
+            (universe, LayoutSource::LayoutFile)
+        }
+        (None, None) => {
+            bail!("No layout file given, and the GFA lacks LX/LY layout tags")
+        }
+    };
 
     let stats = GraphStats {
         node_count: graph.node_count(),
         edge_count: graph.edge_count(),
         path_count: graph.path_count(),
         total_len: graph.total_length(),
+        layout_source,
+        bp_per_unit: bp_per_world_unit(graph, universe.layout().nodes()),
+        memory: Default::default(),
     };
 
     Ok((universe, stats))
 }
 
+// the total base pairs divided by the total layout length, over a
+// sample of the nodes, or zero if the nodes have no extent
+fn bp_per_world_unit(graph: &PackedGraph, nodes: &[Node]) -> f32 {
+    const SAMPLE_SIZE: usize = 1000;
+
+    let step = (nodes.len() / SAMPLE_SIZE).max(1);
+
+    let mut total_bp = 0.0;
+    let mut total_dist = 0.0;
+
+    for (ix, node) in nodes.iter().enumerate().step_by(step) {
+        let handle = Handle::pack(NodeId::from((ix as u64) + 1), false);
+
+        total_bp += graph.node_len(handle) as f32;
+        total_dist += node.p0.dist(node.p1);
+    }
+
+    if total_dist > 0.0 {
+        total_bp / total_dist
+    } else {
+        0.0
+    }
+}
+
 fn set_up_logger(args: &Args) -> Result<LoggerHandle> {
     let spec = match (args.trace, args.debug, args.quiet) {
         (true, _, _) => "trace",
@@ -121,8 +165,8 @@ fn main() {
     log::debug!("Logger initalized");
 
     let gfa_file = &args.gfa;
-    let layout_file = &args.layout;
-    log::debug!("using {} and {}", gfa_file, layout_file);
+    let layout_file = args.layout.as_deref();
+    log::debug!("using {} and {:?}", gfa_file, layout_file);
 
     let (mut gfaestus, event_loop, window) = match GfaestusVk::new(&args) {
         Ok(app) => app,
@@ -172,10 +216,14 @@ fn main() {
     info!("Loading GFA");
     let t = std::time::Instant::now();
 
-    let graph_query = Arc::new(GraphQuery::load_gfa(gfa_file).unwrap());
+    let mut graph_query = Arc::new(GraphQuery::load_gfa(gfa_file).unwrap());
 
     let mut app = App::new((100.0, 100.0)).expect("error when creating App");
 
+    app.settings
+        .msaa_samples()
+        .store(gfaestus.msaa_samples.as_raw());
+
     let mut reactor = gfaestus::reactor::Reactor::init(
         thread_pool.clone(),
         rayon_pool,
@@ -183,13 +231,13 @@ fn main() {
         app.channels(),
     );
 
-    let graph_query_worker =
+    let mut graph_query_worker =
         GraphQueryWorker::new(graph_query.clone(), thread_pool.clone());
 
     let (mut universe, stats) =
         universe_from_gfa_layout(&graph_query, layout_file).unwrap();
 
-    let (top_left, bottom_right) = universe.layout().bounding_box();
+    let (mut top_left, mut bottom_right) = universe.layout().bounding_box();
 
     let _center = Point {
         x: top_left.x + (bottom_right.x - top_left.x) / 2.0,
@@ -221,10 +269,10 @@ fn main() {
     )
     .unwrap();
 
-    let gpu_selection =
+    let mut gpu_selection =
         GpuSelection::new(&gfaestus, graph_query.node_count()).unwrap();
 
-    let node_translation =
+    let mut node_translation =
         NodeTranslation::new(&gfaestus, graph_query.node_count()).unwrap();
 
     let mut select_fence_id: Option<usize> = None;
@@ -254,17 +302,20 @@ fn main() {
     )
     .unwrap();
 
-    let tree_bounding_box = {
-        let height = (bottom_right.x - top_left.x) / 4.0;
+    main_view.rebuild_node_tree(universe.layout().nodes());
 
-        let mut tl = top_left;
-        let mut br = bottom_right;
+    let mut tree_bounding_box = label_tree_bounds(top_left, bottom_right);
 
-        tl.y = -height;
-        br.y = height;
+    if let Some(dir) = args.scripts_dir.as_ref() {
+        *app.settings.scripts_dir().lock() = Some(dir.to_owned());
+    }
 
-        Rect::new(tl, br)
-    };
+    // stored as an absolute path, so that the cache doesn't depend on
+    // the working directory
+    if let Some(dir) = args.overlay_cache.as_ref() {
+        let dir = std::env::current_dir().unwrap_or_default().join(dir);
+        *app.settings.overlay_cache_dir().lock() = Some(dir);
+    }
 
     let mut gui = Gui::new(
         &gfaestus,
@@ -276,51 +327,8 @@ fn main() {
     )
     .unwrap();
 
-    // create default overlays
-    {
-        let node_seq_script = "
-fn node_color(id) {
-  let h = handle(id, false);
-  let seq = graph.sequence(h);
-  let hash = hash_bytes(seq);
-  let color = hash_color(hash);
-  color
-}
-";
-
-        let step_count_script = "
-fn node_color(id) {
-  let h = handle(id, false);
-
-  let steps = graph.steps_on_handle(h);
-  let count = 0.0;
-
-  for step in steps {
-    count += 1.0;
-  }
-
-  count
-}
-";
-
-        create_overlay(
-            &gfaestus,
-            &mut main_view,
-            &reactor,
-            "Node Seq Hash",
-            node_seq_script,
-        )
-        .expect("Error creating node seq hash overlay");
-
-        create_overlay(
-            &gfaestus,
-            &mut main_view,
-            &reactor,
-            "Node Step Count",
-            step_count_script,
-        )
-        .expect("Error creating step count overlay");
-    }
+    create_default_overlays(&gfaestus, &mut main_view, &reactor)
+        .expect("Error creating default overlays");
 
     app.shared_state()
         .overlay_state
@@ -329,6 +337,16 @@ fn node_color(id) {
     let mut initial_view: Option<View> = None;
     let mut initialized_view = false;
 
+    // the view and screen size from before the window started being
+    // resized, and the view last computed from them; every resize
+    // scales the view from the start, rather than the previous size,
+    // so that resizing back restores the original view
+    let mut resize_start: Option<(View, ScreenDims, View)> = None;
+
+    // set when the selection changes while the selection statistics
+    // window is closed, so that they're computed once it's opened
+    let mut selection_stats_stale = false;
+
     let new_overlay_rx = app.channels().new_overlay_rx.clone();
 
     let mut modal_handler =
@@ -383,6 +401,9 @@ fn node_color(id) {
         edge_count: Some(stats.edge_count),
         path_count: Some(stats.path_count),
         total_len: Some(stats.total_len),
+        layout_source: Some(stats.layout_source),
+        bp_per_unit: Some(stats.bp_per_unit),
+        memory: None,
     });
 
     main_view
@@ -409,6 +430,9 @@ fn node_color(id) {
         Some(edge_renderer)
     };
 
+    // the path colors the edge colors and ribbons were computed with
+    let mut path_colors_version = app.settings.path_colors().version();
+
     let mut dirty_swapchain = false;
 
     let mut selection_edge =
@@ -417,6 +441,20 @@ fn node_color(id) {
     let mut selection_blur =
         SelectionOutlineBlurPipeline::new(&gfaestus, 1).unwrap();
 
+    let mut post_adjust = PostProcessPipeline::new(
+        &gfaestus,
+        1,
+        gfaestus.render_passes.post_adjust,
+        gfaestus::include_shader!("post/post_adjust.frag.spv"),
+    )
+    .unwrap();
+
+    post_adjust.write_descriptor_set(
+        gfaestus.vk_context().device(),
+        gfaestus.node_attachments.resolve,
+        None,
+    );
+
     let gui_msg_tx = gui.clone_gui_msg_tx();
 
     // let gradients_ = Gradients_::initialize(
@@ -443,6 +481,13 @@ fn node_color(id) {
             .into_iter(),
     );
 
+    // vsync would cap the frame times, so the benchmark always uses
+    // mailbox, or immediate if mailbox isn't supported
+    let mut benchmark = args.bench.map(|frames| {
+        app.settings.present_mode().store(PresentMode::Mailbox);
+        Benchmark::new(frames, args.bench_out.clone())
+    });
+
     const FRAME_HISTORY_LEN: usize = 10;
     let mut frame_time_history = [0.0f32; FRAME_HISTORY_LEN];
     let mut frame = 0;
@@ -483,6 +528,12 @@ fn node_color(id) {
         }
     }
 
+    if let Some(port) = args.listen {
+        if let Err(err) = gui.console.listen(port) {
+            error!("error starting remote listener: {:?}", err);
+        }
+    }
+
     {
         for annot_path in &args.annotation_files {
             if annot_path.exists() {
@@ -555,15 +606,23 @@ fn node_color(id) {
 
                 gui.push_event(egui::Event::PointerMoved(mouse_pos.into()));
 
-                let hover_node = main_view
-                    .read_node_id_at(mouse_pos)
-                    .map(|nid| NodeId::from(nid as u64));
+                let hover_node = main_view.hover_node_at(mouse_pos);
 
                 app.channels().app_tx.send(AppMsg::HoverNode(hover_node)).unwrap();
 
                 gui.set_hover_node(hover_node);
 
+                if hover_node.is_none() && !main_view.overlay_graphs().is_empty() {
+                    gui.set_hover_overlay_graph_node(
+                        main_view.overlay_graph_node_at(mouse_pos),
+                    );
+                } else {
+                    gui.set_hover_overlay_graph_node(None);
+                }
+
                 if app.selection_changed() {
+                    let selection_bounds = app.selected_nodes_bounding_box;
+
                     if let Some(selected) = app.selected_nodes() {
 
                         log::warn!("sending selection");
@@ -581,7 +640,21 @@ fn node_color(id) {
                             .node_list()
                             .send(NodeListMsg::SetFiltered(nodes));
 
-                        main_view.update_node_selection(selected).unwrap();
+                        if gui.selection_stats_open() {
+                            gui.set_selection_stats(SelectionStats::new(
+                                &graph_query,
+                                selected,
+                                selection_bounds,
+                            ));
+                        } else {
+                            selection_stats_stale = true;
+                        }
+
+                        if let Err(err) =
+                            main_view.update_node_selection(selected)
+                        {
+                            error!("error updating node selection: {:?}", err);
+                        }
 
 
                     } else {
@@ -589,10 +662,32 @@ fn node_color(id) {
                             .node_list()
                             .send(NodeListMsg::SetFiltered(Vec::new()));
 
+                        gui.set_selection_stats(SelectionStats::default());
+                        selection_stats_stale = false;
+
                         main_view.clear_node_selection().unwrap();
                     }
                 }
 
+                if selection_stats_stale && gui.selection_stats_open() {
+                    let selection_bounds = app.selected_nodes_bounding_box;
+
+                    if let Some(selected) = app.selected_nodes() {
+                        gui.set_selection_stats(SelectionStats::new(
+                            &graph_query,
+                            selected,
+                            selection_bounds,
+                        ));
+                    }
+                    selection_stats_stale = false;
+                }
+
+                input_manager.set_main_view_buttons(
+                    app.settings.pan_button().load(),
+                    app.settings.select_button().load(),
+                );
+                input_manager.set_ruler_mode(app.ruler().is_some());
+
                 while let Ok((key_code, command)) = app.channels().binds_rx.try_recv() {
                     if let Some(cmd) = command {
                         input_manager.add_binding(key_code, cmd);
@@ -602,7 +697,7 @@ fn node_color(id) {
                 }
 
                 while let Ok(app_in) = app_rx.try_recv() {
-                    app.apply_input(app_in, &gui_msg_tx);
+                    app.apply_input(app_in, &gui_msg_tx, &graph_query);
                 }
 
                 while let Ok(gui_in) = gui_rx.try_recv() {
@@ -630,6 +725,248 @@ fn node_color(id) {
 
                     }
 
+                    if let AppMsg::RequestData { key, index, sender } = &app_msg {
+                        if key == "overlay_colors" {
+                            // the colors of each node in the active overlay,
+                            // for `select_where_overlay`
+                            let overlay_state = &app.shared_state().overlay_state;
+
+                            let colors = overlay_state
+                                .current_overlay()
+                                .and_then(|id| main_view.node_draw_system.pipelines.overlay(id))
+                                .map(|overlay| {
+                                    let gradient = overlay_state.gradient().gradient();
+                                    overlay.node_colors(graph_query.node_count(), gradient)
+                                });
+
+                            let result = colors
+                                .map(rhai::Dynamic::from)
+                                .ok_or_else(|| anyhow::anyhow!("No active overlay"));
+
+                            sender.send(result).unwrap();
+                        } else if key == "node_id_at" {
+                            // the screen position is sent as "x,y"
+                            let dims = app.dims();
+
+                            let point = index
+                                .split_once(',')
+                                .and_then(|(x, y)| {
+                                    let x = x.parse::<f32>().ok()?;
+                                    let y = y.parse::<f32>().ok()?;
+                                    Some(Point::new(x, y))
+                                });
+
+                            let result = match point {
+                                Some(p) if p.x >= 0.0 && p.y >= 0.0
+                                    && p.x < dims.width && p.y < dims.height =>
+                                {
+                                    // nodes of overlaid graphs aren't
+                                    // nodes of the main graph
+                                    let id = main_view
+                                        .read_node_id_at(p)
+                                        .and_then(|id| main_view.resolve_node_id(id))
+                                        .and_then(|(graph, node)| {
+                                            graph.is_none().then(|| node)
+                                        })
+                                        .map(rhai::Dynamic::from)
+                                        .unwrap_or(rhai::Dynamic::UNIT);
+                                    Ok(id)
+                                }
+                                Some(_) => Err(anyhow::anyhow!("Screen position out of bounds")),
+                                None => Err(anyhow::anyhow!("Invalid screen position")),
+                            };
+
+                            sender.send(result).unwrap();
+                        }
+                    }
+
+                    if let AppMsg::ExportSvg { path, sender } = &app_msg {
+                        let result = main_view.export_svg(
+                            path,
+                            graph_query.graph(),
+                            universe.layout().nodes(),
+                        );
+
+                        if let Err(err) = &result {
+                            log::error!("Error exporting SVG: {:?}", err);
+                        }
+
+                        let _ = sender.send(result);
+                    }
+
+                    if let AppMsg::ReloadGraph { gfa, layout } = &app_msg {
+                        // replace everything that depends on the graph;
+                        // the GPU must be idle, and no selection or
+                        // translation can be in flight, as those use
+                        // buffers sized to the old graph
+                        let reloaded = if select_fence_id.is_some()
+                            || translate_fence_id.is_some()
+                        {
+                            Err(anyhow::anyhow!("GPU compute in progress"))
+                        } else {
+                            GraphQuery::load_gfa(gfa).and_then(|query| {
+                                let (universe, stats) =
+                                    universe_from_gfa_layout(&query, layout.as_deref())?;
+                                Ok((Arc::new(query), universe, stats))
+                            })
+                        };
+
+                        match reloaded {
+                            Ok((new_graph_query, new_universe, stats)) => {
+                                info!("Reloading graph from {}", gfa);
+
+                                window.set_title(&window_title(gfa));
+
+                                gfaestus.wait_gpu_idle().unwrap();
+
+                                let device = gfaestus.vk_context().device();
+
+                                main_view.selection_buffer.destroy(device);
+                                main_view.node_id_buffer.destroy(device);
+                                main_view.node_draw_system.destroy(&gfaestus);
+                                main_view.destroy_overlay_graphs(&gfaestus);
+
+                                if let Some(er) = edge_renderer.as_mut() {
+                                    er.destroy();
+                                    er.destroy_buffers(&gfaestus).unwrap();
+                                }
+
+                                graph_query = new_graph_query;
+                                universe = new_universe;
+
+                                reactor.graph_query = graph_query.clone();
+                                graph_query_worker = GraphQueryWorker::new(
+                                    graph_query.clone(),
+                                    thread_pool.clone(),
+                                );
+
+                                let node_count = graph_query.node_count();
+
+                                gpu_selection =
+                                    GpuSelection::new(&gfaestus, node_count).unwrap();
+                                node_translation =
+                                    NodeTranslation::new(&gfaestus, node_count).unwrap();
+
+                                main_view = MainView::new(
+                                    &gfaestus,
+                                    app.clone_channels(),
+                                    app.settings.clone(),
+                                    app.shared_state().clone(),
+                                    node_count,
+                                )
+                                .unwrap();
+
+                                main_view.rebuild_node_tree(universe.layout().nodes());
+                                main_view
+                                    .node_draw_system
+                                    .vertices
+                                    .upload_vertices(&gfaestus, &universe.node_vertices())
+                                    .unwrap();
+
+                                if edge_renderer.is_some() {
+                                    edge_renderer = Some(
+                                        EdgeRenderer::new(
+                                            &gfaestus,
+                                            &graph_query.graph_arc(),
+                                            universe.layout(),
+                                        )
+                                        .unwrap(),
+                                    );
+                                }
+
+                                let bounds = universe.layout().bounding_box();
+                                top_left = bounds.0;
+                                bottom_right = bounds.1;
+                                tree_bounding_box =
+                                    label_tree_bounds(top_left, bottom_right);
+
+                                app.clear_graph_state();
+
+                                create_default_overlays(&gfaestus, &mut main_view, &reactor)
+                                    .unwrap();
+                                app.shared_state()
+                                    .overlay_state
+                                    .set_current_overlay(Some(0));
+
+                                gui.set_graph(&mut reactor, &graph_query).unwrap();
+                                gui.populate_overlay_list(
+                                    main_view
+                                        .node_draw_system
+                                        .pipelines
+                                        .overlay_names()
+                                        .into_iter(),
+                                );
+                                gui.app_view_state().graph_stats().send(GraphStatsMsg {
+                                    node_count: Some(stats.node_count),
+                                    edge_count: Some(stats.edge_count),
+                                    path_count: Some(stats.path_count),
+                                    total_len: Some(stats.total_len),
+                                    layout_source: Some(stats.layout_source),
+                                    bp_per_unit: Some(stats.bp_per_unit),
+                                    memory: None,
+                                });
+
+                                // like `fit_graph`
+                                let initial_view = View::fit_rect(
+                                    app.dims(),
+                                    Rect::new(top_left, bottom_right),
+                                    1.2,
+                                );
+                                main_view.set_initial_view(
+                                    Some(initial_view.center),
+                                    Some(initial_view.scale),
+                                );
+                                main_view.reset_view();
+                            }
+                            Err(err) => {
+                                error!("Error reloading graph from {}: {:?}", gfa, err);
+                            }
+                        }
+                    }
+
+                    if let AppMsg::LoadOverlayGraph { gfa, layout } = &app_msg {
+                        let loaded = GraphQuery::load_gfa(gfa).and_then(|query| {
+                            let (universe, _stats) =
+                                universe_from_gfa_layout(&query, layout.as_deref())?;
+                            Ok((query.node_count(), universe))
+                        });
+
+                        let added = loaded.and_then(|(node_count, overlay_universe)| {
+                            main_view.add_overlay_graph(
+                                &gfaestus,
+                                gfa,
+                                &overlay_universe.node_vertices(),
+                                node_count,
+                                path_color(gfa.as_bytes()),
+                            )
+                        });
+
+                        match added {
+                            Ok(ix) => {
+                                info!("Loaded overlaid graph {} from {}", ix, gfa);
+                            }
+                            Err(err) => {
+                                error!("Error loading overlaid graph from {}: {:?}", gfa, err);
+                            }
+                        }
+                    }
+
+                    if let AppMsg::ToggleOverlayGraph(index) = &app_msg {
+                        if let Some(graph) = main_view.overlay_graphs_mut().get_mut(*index) {
+                            graph.visible = !graph.visible;
+                        } else {
+                            warn!("No overlaid graph with index {}", index);
+                        }
+                    }
+
+                    if let AppMsg::SetOverlayGraphOffset { index, offset } = &app_msg {
+                        if let Some(graph) = main_view.overlay_graphs_mut().get_mut(*index) {
+                            graph.offset = *offset;
+                        } else {
+                            warn!("No overlaid graph with index {}", index);
+                        }
+                    }
+
                     if let AppMsg::TranslateSelected(delta) = &app_msg {
                         if select_fence_id.is_none() && translate_fence_id.is_none() {
 
@@ -655,6 +992,8 @@ fn node_color(id) {
                     );
                 }
 
+                app.update_follow_selection(main_view.main_view_msg_tx());
+
                 gui.apply_received_gui_msgs();
 
                 while let Ok(main_view_msg) = main_view.main_view_msg_rx().try_recv() {
@@ -662,12 +1001,25 @@ fn node_color(id) {
                 }
 
                 while let Ok(new_overlay) = new_overlay_rx.try_recv() {
-                    if let Ok(_) = handle_new_overlay(
+                    app.shared_state().overlay_progress.store(None);
+
+                    let set_colors =
+                        matches!(new_overlay, OverlayCreatorMsg::SetNodeColors { .. });
+
+                    if let Ok(overlay_id) = handle_new_overlay(
                         &gfaestus,
                         &mut main_view,
+                        &app.shared_state().overlay_state,
                         graph_query.node_count(),
                         new_overlay
                     ) {
+                        // manually colored nodes should be visible right away
+                        if set_colors {
+                            app.shared_state()
+                                .overlay_state
+                                .set_current_overlay(Some(overlay_id));
+                        }
+
                         gui.populate_overlay_list(
                             main_view
                                 .node_draw_system
@@ -683,9 +1035,79 @@ fn node_color(id) {
                 let mouse_pos = app.mouse_pos();
                 main_view.update_view_animation(screen_dims, mouse_pos);
 
-                let edge_ubo = app.settings.edge_renderer().load();
+                // the edge colors and ribbons are cleared when a path
+                // color is overridden, or the palette changes, and
+                // recomputed when they're next needed
+                let version = app.settings.path_colors().version();
+                if version != path_colors_version {
+                    path_colors_version = version;
+
+                    gfaestus.wait_gpu_idle().unwrap();
+
+                    for er in edge_renderer.iter_mut() {
+                        er.clear_path_colors();
+                    }
+
+                    let cleared = main_view
+                        .node_draw_system
+                        .clear_path_ribbons(&gfaestus);
+
+                    if let Err(err) = cleared {
+                        error!("Error clearing path ribbons: {}", err);
+                    }
+                }
+
+                // the path ribbons are computed when they're enabled,
+                // and whenever the graph is reloaded or the path colors
+                // change; if that fails, they're disabled, rather than
+                // retried every frame
+                if app.settings.path_ribbons().load()
+                    && !main_view.node_draw_system.has_path_ribbons()
+                {
+                    let ribbons = PathRibbons::new(
+                        &gfaestus,
+                        graph_query.graph(),
+                        app.settings.path_colors(),
+                    );
+
+                    gfaestus.wait_gpu_idle().unwrap();
+
+                    let result = ribbons.and_then(|ribbons| {
+                        main_view
+                            .node_draw_system
+                            .set_path_ribbons(&gfaestus, ribbons)
+                    });
+
+                    if let Err(err) = result {
+                        error!("Error creating path ribbons: {}", err);
+                        app.settings.path_ribbons().store(false);
+                    }
+                }
+
+                let mut edge_ubo = app.settings.edge_renderer().load();
 
                 for er in edge_renderer.iter_mut() {
+                    // the path edge colors are only computed when the
+                    // edges are colored by path; like the ribbons, if
+                    // that fails, the edges go back to a single color
+                    if edge_ubo.color_mode == EdgeColorMode::ByPath
+                        && !er.has_path_colors()
+                    {
+                        gfaestus.wait_gpu_idle().unwrap();
+
+                        let updated = er.update_path_colors(
+                            &gfaestus,
+                            graph_query.graph(),
+                            app.settings.path_colors(),
+                        );
+
+                        if let Err(err) = updated {
+                            error!("Error updating edge path colors: {}", err);
+                            edge_ubo.color_mode = EdgeColorMode::Single;
+                            app.settings.edge_renderer().store(edge_ubo);
+                        }
+                    }
+
                     er.write_ubo(&edge_ubo).unwrap();
                 }
             }
@@ -704,6 +1126,7 @@ fn node_color(id) {
                         log::trace!("Compute fence freed, updating CPU node positions");
                         universe.update_positions_from_gpu(&gfaestus,
                                                            &main_view.node_draw_system.vertices).unwrap();
+                        main_view.rebuild_node_tree(universe.layout().nodes());
 
                         translate_fence_id = None;
                     }
@@ -751,11 +1174,79 @@ fn node_color(id) {
 
                 let frame_t = std::time::Instant::now();
 
+                let msaa_samples = app.settings.msaa_samples().load();
+                if msaa_samples != gfaestus.msaa_samples.as_raw() {
+                    if let Err(err) = gfaestus.set_msaa_samples(msaa_samples) {
+                        // nothing changed, so the setting is reverted
+                        error!("Error setting MSAA samples: {}", err);
+                        app.settings
+                            .msaa_samples()
+                            .store(gfaestus.msaa_samples.as_raw());
+                    } else {
+                        main_view
+                            .node_draw_system
+                            .recreate_pipelines(&gfaestus)
+                            .unwrap();
+
+                        for er in edge_renderer.iter_mut() {
+                            er.recreate_pipeline(&gfaestus).unwrap();
+                        }
+
+                        selection_edge.write_descriptor_set(
+                            gfaestus.vk_context().device(),
+                            gfaestus.node_attachments.mask_resolve,
+                        );
+
+                        selection_blur.write_descriptor_set(
+                            gfaestus.vk_context().device(),
+                            gfaestus.offscreen_attachment.color,
+                        );
+
+                        post_adjust.write_descriptor_set(
+                            gfaestus.vk_context().device(),
+                            gfaestus.node_attachments.resolve,
+                            None,
+                        );
+                    }
+                }
+
+                // the swapchain is recreated with the new present mode
+                let present_mode = app.settings.present_mode().load();
+                if present_mode != gfaestus.present_mode {
+                    gfaestus.present_mode = present_mode;
+                    dirty_swapchain = true;
+                }
+
                 if dirty_swapchain {
                     let size = window.inner_size();
                     log::trace!("Dirty swapchain, reconstructing");
                     if size.width > 0 && size.height > 0 {
+                        let old_dims = app.dims();
                         app.update_dims([size.width as f32, size.height as f32]);
+
+                        // keep what was visible in view, once the
+                        // initial view has been set; the start is reset
+                        // if the view was changed since the last resize
+                        if initial_view.is_some() {
+                            let view = app.shared_state().view();
+
+                            let (start_view, start_dims) = match resize_start {
+                                Some((start_view, start_dims, last))
+                                    if last == view =>
+                                {
+                                    (start_view, start_dims)
+                                }
+                                _ => (view, old_dims),
+                            };
+
+                            let view =
+                                start_view.resized(start_dims, app.dims());
+                            main_view.set_view(view);
+
+                            resize_start =
+                                Some((start_view, start_dims, view));
+                        }
+
                         gfaestus
                             .recreate_swapchain(Some([size.width, size.height]))
                             .unwrap();
@@ -770,12 +1261,23 @@ fn node_color(id) {
                             gfaestus.offscreen_attachment.color,
                         );
 
+                        post_adjust.write_descriptor_set(
+                            gfaestus.vk_context().device(),
+                            gfaestus.node_attachments.resolve,
+                            None,
+                        );
+
                         main_view
                             .recreate_node_id_buffer(&gfaestus, size.width, size.height)
                             .unwrap();
 
-                        let new_initial_view =
-                            View::from_dims_and_target(app.dims(), top_left, bottom_right);
+                        // the graph is fit to the screen on load, like
+                        // with `fit_graph`
+                        let new_initial_view = View::fit_rect(
+                            app.dims(),
+                            Rect::new(top_left, bottom_right),
+                            1.2,
+                        );
                         if initial_view.is_none()
                             && initial_resize_timer.elapsed().as_millis() > 100
                         {
@@ -804,6 +1306,20 @@ fn node_color(id) {
 
                 modal_handler.show(&gui.ctx);
 
+                main_view.draw_box_select(&gui.ctx);
+
+                if let Some(ruler) = app.ruler() {
+                    let view = app.shared_state().view();
+                    let mouse_world =
+                        view.screen_point_to_world(app.dims(), app.mouse_pos());
+                    ruler.draw(&gui.ctx, view, mouse_world);
+                }
+
+                for path_ruler in app.path_rulers() {
+                    let view = app.shared_state().view();
+                    path_ruler.draw(&gui.ctx, view, app.dims());
+                }
+
                 {
                     let ctx = &gui.ctx;
                     let clipboard = &mut gui.clipboard_ctx;
@@ -827,8 +1343,13 @@ fn node_color(id) {
                     let cluster_tree = labels.cluster(tree_bounding_box,
                                                       app.settings.label_radius().load(),
                                                       view);
+                    // node ID labels share the layout with the annotation
+                    // labels, so that neither overlaps the other
+                    let mut label_layout = LabelLayout::default();
+                    main_view.add_node_id_labels(&gui.ctx, &mut label_layout);
+
                     // log::debug!("Drawing label sets");
-                    cluster_tree.draw_labels(&gui.ctx, shared_state);
+                    cluster_tree.draw_labels(&gui.ctx, shared_state, label_layout);
                     // cluster_tree.draw_clusters(&gui.ctx, view);
                 }
 
@@ -989,14 +1510,29 @@ fn node_color(id) {
                 let edges_pass = gfaestus.render_passes.edges;
                 let edge_pass = gfaestus.render_passes.selection_edge_detect;
                 let blur_pass = gfaestus.render_passes.selection_blur;
+                let adjust_pass = gfaestus.render_passes.post_adjust;
                 let gui_pass = gfaestus.render_passes.gui;
 
                 let node_id_image = gfaestus.node_attachments.id_resolve.image;
 
+                let node_color_image = gfaestus.node_attachments.resolve.image;
+
+                let color_adjustment = app.settings.color_adjustment().load();
+
+                let selection_style = app.settings.selection_style().load();
+                // the blur is grayscale, which the palettes leave as is
+                let selection_outline_color = app
+                    .settings
+                    .palette()
+                    .load()
+                    .apply(app.settings.selection_outline_color().load());
+
                 let offscreen_image = gfaestus.offscreen_attachment.color.image;
 
                 let overlay =
                     app.shared_state().overlay_state().current_overlay();
+                let base_overlay =
+                    app.shared_state().overlay_state().base_overlay();
 
                 let current_view = app.shared_state().view();
 
@@ -1075,6 +1611,7 @@ fn node_color(id) {
                             size.into(),
                             Point::ZERO,
                             overlay,
+                            base_overlay,
                             gradient,
                         ).unwrap();
 
@@ -1117,6 +1654,61 @@ fn node_color(id) {
                             debug::end_cmd_buf_label(debug_utils, cmd_buf);
                         }
 
+                        log::trace!("Node color image transition");
+                        unsafe {
+                            let image_memory_barrier = vk::ImageMemoryBarrier::builder()
+                                .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                                .old_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                                .image(node_color_image)
+                                .subresource_range(vk::ImageSubresourceRange {
+                                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                                    base_mip_level: 0,
+                                    level_count: 1,
+                                    base_array_layer: 0,
+                                    layer_count: 1,
+                                })
+                                .build();
+
+                            let memory_barriers = [];
+                            let buffer_memory_barriers = [];
+                            let image_memory_barriers = [image_memory_barrier];
+                            device.cmd_pipeline_barrier(
+                                cmd_buf,
+                                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                                vk::DependencyFlags::BY_REGION,
+                                &memory_barriers,
+                                &buffer_memory_barriers,
+                                &image_memory_barriers,
+                            );
+                        }
+
+                        debug::begin_cmd_buf_label(
+                            debug_utils,
+                            cmd_buf,
+                            "Color adjustment",
+                        );
+
+                        // copies the nodes and edges to the swapchain
+                        // image, applying brightness, contrast, and gamma
+                        log::trace!("Drawing color adjustment");
+                        post_adjust
+                            .draw(
+                                &device,
+                                cmd_buf,
+                                adjust_pass,
+                                framebuffers.post_adjust,
+                                size.into(),
+                                size.into(),
+                                color_adjustment,
+                            )
+                            .unwrap();
+
+                        debug::end_cmd_buf_label(debug_utils, cmd_buf);
 
                         log::trace!("Post-edge image transitions");
                         unsafe {
@@ -1210,6 +1802,8 @@ fn node_color(id) {
                                 blur_pass,
                                 framebuffers,
                                 [size.width as f32, size.height as f32],
+                                selection_style,
+                                selection_outline_color,
                             )
                             .unwrap();
 
@@ -1266,11 +1860,34 @@ fn node_color(id) {
                     let fps = 1.0 / avg;
                     let avg_ms = avg * 1000.0;
 
-                    gui.app_view_state().fps().send(FrameRateMsg(FrameRate {
+                    let frame_rate = FrameRate {
                         fps,
                         frame_time: avg_ms,
                         frame,
-                    }));
+                    };
+
+                    app.shared_state().frame_rate.store(frame_rate);
+                    gui.app_view_state().fps().send(FrameRateMsg(frame_rate));
+
+                    gui.app_view_state().graph_stats().send(GraphStatsMsg {
+                        memory: Some(main_view.memory_usage()),
+                        ..GraphStatsMsg::default()
+                    });
+                }
+
+                if let Some(bench) = benchmark.as_mut() {
+                    if initialized_view {
+                        let next_view = bench.record_frame(main_view.view(), frame_time);
+                        main_view.set_view(next_view);
+
+                        if bench.is_done() {
+                            match bench.write_csv() {
+                                Ok(_) => info!("wrote frame times to {:?}", bench.out_path()),
+                                Err(err) => error!("error writing frame times: {:?}", err),
+                            }
+                            *control_flow = ControlFlow::Exit;
+                        }
+                    }
                 }
 
                 frame += 1;
@@ -1295,11 +1912,13 @@ fn node_color(id) {
                 main_view.selection_buffer.destroy(device);
                 main_view.node_id_buffer.destroy(device);
                 main_view.node_draw_system.destroy(&gfaestus);
+                main_view.destroy_overlay_graphs(&gfaestus);
 
                 gui.draw_system.destroy(&gfaestus.allocator);
 
                 selection_edge.destroy(device);
                 selection_blur.destroy(device);
+                post_adjust.destroy(device);
             }
             _ => (),
         }
@@ -1309,15 +1928,112 @@ fn node_color(id) {
 fn handle_new_overlay(
     app: &GfaestusVk,
     main_view: &mut MainView,
+    overlay_state: &OverlayState,
     node_count: usize,
     msg: OverlayCreatorMsg,
-) -> Result<()> {
-    let OverlayCreatorMsg::NewOverlay { name, data } = msg;
+) -> Result<usize> {
+    match msg {
+        OverlayCreatorMsg::NewOverlay { name, data, legend } => {
+            let overlay_id = create_overlay_from_data(
+                app, main_view, node_count, &name, data,
+            )?;
+            overlay_state.set_legend(overlay_id, legend);
+            Ok(overlay_id)
+        }
+        OverlayCreatorMsg::NewOverlayNotify {
+            name,
+            data,
+            created_tx,
+        } => {
+            let kind = data.kind();
+            let overlay_id = create_overlay_from_data(
+                app, main_view, node_count, &name, data,
+            )?;
+            overlay_state.set_legend(overlay_id, None);
+            // the script may have stopped waiting
+            let _ = created_tx.send((overlay_id, kind));
+            Ok(overlay_id)
+        }
+        OverlayCreatorMsg::SetNodeColors { name, colors } => {
+            let overlay_id = set_overlay_node_colors(
+                app, main_view, node_count, &name, colors,
+            )?;
+            overlay_state.set_legend(overlay_id, None);
+            Ok(overlay_id)
+        }
+    }
+}
+
+/// Writes the node colors into the RGB overlay with the given name,
+/// first creating it with every node gray if it doesn't exist,
+/// returning the overlay ID
+fn set_overlay_node_colors(
+    app: &GfaestusVk,
+    main_view: &mut MainView,
+    node_count: usize,
+    name: &str,
+    colors: Vec<(NodeId, rgb::RGBA<f32>)>,
+) -> Result<usize> {
+    let colors = colors
+        .into_iter()
+        .filter(|(node, _)| node.0 >= 1 && node.0 as usize <= node_count);
+
+    let pipelines = &mut main_view.node_draw_system.pipelines;
+
+    if let Some((overlay_id, overlay)) =
+        pipelines.overlay_by_name_mut(name, OverlayKind::RGB)
+    {
+        overlay.update_rgb_overlay(colors)?;
+        return Ok(overlay_id);
+    }
+
+    let mut overlay = Overlay::new_empty_rgb(name, app, node_count)?;
+
+    let gray = rgb::RGBA::new(0.5, 0.5, 0.5, 1.0);
+    overlay.update_rgb_overlay(
+        (0..node_count).map(|ix| (NodeId::from((ix as u64) + 1), gray)),
+    )?;
+    overlay.update_rgb_overlay(colors)?;
+
+    Ok(pipelines.create_overlay(overlay))
+}
+
+/// Creates an overlay from the data, or updates the existing overlay
+/// with the same name and kind, returning the overlay ID
+fn create_overlay_from_data(
+    app: &GfaestusVk,
+    main_view: &mut MainView,
+    node_count: usize,
+    name: &str,
+    data: OverlayData,
+) -> Result<usize> {
+    let pipelines = &mut main_view.node_draw_system.pipelines;
+
+    // overlays are replaced by name, so that they can be recomputed
+    // (e.g. by `:animate_overlay`) without creating new ones
+    if let Some((overlay_id, overlay)) =
+        pipelines.overlay_by_name_mut(name, data.kind())
+    {
+        match data {
+            OverlayData::RGB(data) => overlay.update_rgb_overlay(
+                data.iter()
+                    .enumerate()
+                    .map(|(ix, col)| (NodeId::from((ix as u64) + 1), *col)),
+            )?,
+            OverlayData::Value(data) => overlay.update_value_overlay(
+                data.iter()
+                    .enumerate()
+                    .map(|(ix, v)| (NodeId::from((ix as u64) + 1), *v)),
+            )?,
+        }
+
+        return Ok(overlay_id);
+    }
 
     let overlay = match data {
         OverlayData::RGB(data) => {
             let mut overlay =
-                Overlay::new_empty_rgb(&name, app, node_count).unwrap();
+                Overlay::new_empty_rgb(name, app, node_count).unwrap();
 
             overlay
                 .update_rgb_overlay(
@@ -1331,7 +2047,7 @@ fn handle_new_overlay(
         }
         OverlayData::Value(data) => {
             let mut overlay =
-                Overlay::new_empty_value(&name, &app, node_count).unwrap();
+                Overlay::new_empty_value(name, &app, node_count).unwrap();
 
             overlay
                 .update_value_overlay(
@@ -1345,9 +2061,9 @@ fn handle_new_overlay(
         }
     };
 
-    main_view.node_draw_system.pipelines.create_overlay(overlay);
+    let overlay_id = pipelines.create_overlay(overlay);
 
-    Ok(())
+    Ok(overlay_id)
 }
 
 fn create_overlay(
@@ -1370,16 +2086,67 @@ fn create_overlay(
         &reactor.graph_query,
         script,
     ) {
-        let msg = OverlayCreatorMsg::NewOverlay {
-            name: name.to_string(),
-            data,
-        };
-        handle_new_overlay(app, main_view, node_count, msg)?;
+        create_overlay_from_data(app, main_view, node_count, name, data)?;
     }
 
     Ok(())
 }
 
+fn create_default_overlays(
+    app: &GfaestusVk,
+    main_view: &mut MainView,
+    reactor: &Reactor,
+) -> Result<()> {
+    let node_seq_script = "
+fn node_color(id) {
+  let h = handle(id, false);
+  let seq = graph.sequence(h);
+  let hash = hash_bytes(seq);
+  let color = hash_color(hash);
+  color
+}
+";
+
+    let step_count_script = "
+fn node_color(id) {
+  let h = handle(id, false);
+
+  let steps = graph.steps_on_handle(h);
+  let count = 0.0;
+
+  for step in steps {
+    count += 1.0;
+  }
+
+  count
+}
+";
+
+    create_overlay(app, main_view, reactor, "Node Seq Hash", node_seq_script)?;
+    create_overlay(
+        app,
+        main_view,
+        reactor,
+        "Node Step Count",
+        step_count_script,
+    )?;
+
+    Ok(())
+}
+
+// the bounds used when clustering the annotation labels
+fn label_tree_bounds(top_left: Point, bottom_right: Point) -> Rect {
+    let height = (bottom_right.x - top_left.x) / 4.0;
+
+    let mut tl = top_left;
+    let mut br = bottom_right;
+
+    tl.y = -height;
+    br.y = height;
+
+    Rect::new(tl, br)
+}
+
 fn draw_tree<T>(ctx: &egui::CtxRef, tree: &QuadTree<T>, app: &App)
 where
     T: Clone + ToString,
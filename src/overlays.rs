@@ -1,3 +1,13 @@
+pub mod cache;
+
+use std::sync::Arc;
+
+use crossbeam::atomic::AtomicCell;
+use parking_lot::RwLock;
+use rustc_hash::FxHashMap;
+
+use crate::vulkan::texture::Gradients;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// Defines the type of mapping from node ID to colors used by an
 /// overlay script
@@ -15,6 +25,305 @@ pub enum OverlayData {
     Value(Vec<f32>),
 }
 
+impl OverlayData {
+    pub fn kind(&self) -> OverlayKind {
+        match self {
+            OverlayData::RGB(_) => OverlayKind::RGB,
+            OverlayData::Value(_) => OverlayKind::Value,
+        }
+    }
+}
+
+/// Describes what an overlay's colors mean, for the overlay legend
+#[derive(Debug, Clone)]
+pub enum OverlayLegend {
+    /// The values of a single-channel overlay were normalized from
+    /// this range before being mapped through the gradient
+    Range { min: f32, max: f32 },
+    /// The name and color of each category in an RGB overlay
+    Categories(Vec<(String, rgb::RGBA<f32>)>),
+}
+
+/// Color palette modes for users with color vision deficiencies.
+///
+/// All overlay colors, including those produced by `hash_color` and
+/// other script color functions, are remapped in the node fragment
+/// shaders according to the active palette (see
+/// `shaders/nodes/palette.glsl`); colors that are drawn without going
+/// through those shaders, such as edge path colors, path ribbons, and
+/// legend swatches, are remapped with `apply`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Palette {
+    Default,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl std::default::Default for Palette {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl Palette {
+    /// The value passed to the node shaders in the push constants
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            Palette::Default => 0,
+            Palette::Deuteranopia => 1,
+            Palette::Protanopia => 2,
+        }
+    }
+
+    /// Remaps a color the same way as `apply_palette` in
+    /// `shaders/nodes/palette.glsl`
+    pub fn apply(&self, color: rgb::RGB<f32>) -> rgb::RGB<f32> {
+        const RGB_TO_LMS: [[f32; 3]; 3] = [
+            [17.8824, 43.5161, 4.11935],
+            [3.45565, 27.1554, 3.86714],
+            [0.0299566, 0.184309, 1.46709],
+        ];
+
+        const LMS_TO_RGB: [[f32; 3]; 3] = [
+            [0.08094445, -0.1305044, 0.1167211],
+            [-0.01024853, 0.05401933, -0.1136147],
+            [-0.0003652969, -0.004121615, 0.6935114],
+        ];
+
+        const SIM_PROTANOPIA: [[f32; 3]; 3] =
+            [[0.0, 2.02344, -2.52581], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+        const SIM_DEUTERANOPIA: [[f32; 3]; 3] =
+            [[1.0, 0.0, 0.0], [0.494207, 0.0, 1.24827], [0.0, 0.0, 1.0]];
+
+        const SHIFT_ERROR: [[f32; 3]; 3] =
+            [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+        fn mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+            let row = |r: &[f32; 3]| r[0] * v[0] + r[1] * v[1] + r[2] * v[2];
+            [row(&m[0]), row(&m[1]), row(&m[2])]
+        }
+
+        let sim = match self {
+            Palette::Default => return color,
+            Palette::Deuteranopia => &SIM_DEUTERANOPIA,
+            Palette::Protanopia => &SIM_PROTANOPIA,
+        };
+
+        let color = [color.r, color.g, color.b];
+
+        let simulated = mul(&LMS_TO_RGB, mul(sim, mul(&RGB_TO_LMS, color)));
+        let error = [
+            color[0] - simulated[0],
+            color[1] - simulated[1],
+            color[2] - simulated[2],
+        ];
+        let shift = mul(&SHIFT_ERROR, error);
+
+        let channel = |i: usize| (color[i] + shift[i]).max(0.0).min(1.0);
+        rgb::RGB::new(channel(0), channel(1), channel(2))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::Protanopia => "protanopia",
+        }
+    }
+}
+
+impl std::str::FromStr for Palette {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "default" => Ok(Palette::Default),
+            "deuteranopia" => Ok(Palette::Deuteranopia),
+            "protanopia" => Ok(Palette::Protanopia),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A color for a path, derived from a hash of its name, so that a
+/// path gets the same color across sessions. The hash only picks the
+/// hue, so that all path colors are equally bright and saturated.
+pub fn path_color(path_name: &[u8]) -> rgb::RGB<f32> {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path_name.hash(&mut hasher);
+
+    let hue = (hasher.finish() % 3600) as f32 / 10.0;
+    hsv_to_rgb(hue, 0.65, 0.9)
+}
+
+fn hsv_to_rgb(hue: f32, sat: f32, val: f32) -> rgb::RGB<f32> {
+    let c = val * sat;
+    let h = hue / 60.0;
+    let x = c * (1.0 - ((h % 2.0) - 1.0).abs());
+    let m = val - c;
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    rgb::RGB::new(r + m, g + m, b + m)
+}
+
+/// The colors used for paths by everything that colors by path, so
+/// that they agree; paths use `path_color` unless overridden
+#[derive(Debug, Clone)]
+pub struct PathColors {
+    overrides: Arc<RwLock<FxHashMap<Vec<u8>, rgb::RGB<f32>>>>,
+    palette: Arc<AtomicCell<Palette>>,
+    // incremented whenever the overrides change
+    generation: Arc<AtomicCell<usize>>,
+}
+
+impl PathColors {
+    pub fn new(palette: Arc<AtomicCell<Palette>>) -> Self {
+        Self {
+            overrides: Default::default(),
+            palette,
+            generation: Arc::new(AtomicCell::new(0)),
+        }
+    }
+
+    /// The color of the path, before the palette is applied; colors
+    /// used in overlays are remapped by the node shaders
+    pub fn color(&self, path_name: &[u8]) -> rgb::RGB<f32> {
+        self.overrides
+            .read()
+            .get(path_name)
+            .copied()
+            .unwrap_or_else(|| path_color(path_name))
+    }
+
+    /// The color of the path remapped with the active palette, for
+    /// anything drawn without the node shaders, such as the edges
+    /// and path ribbons
+    pub fn display_color(&self, path_name: &[u8]) -> rgb::RGB<f32> {
+        self.palette.load().apply(self.color(path_name))
+    }
+
+    /// Changes whenever the colors returned by `display_color` may
+    /// have changed, i.e. when an override is set or removed, or the
+    /// palette changes
+    pub fn version(&self) -> (usize, Palette) {
+        (self.generation.load(), self.palette.load())
+    }
+
+    /// Sets the color of the path, or removes its override if
+    /// `color` is `None`
+    pub fn set_override(&self, path_name: &[u8], color: Option<rgb::RGB<f32>>) {
+        let mut overrides = self.overrides.write();
+
+        if let Some(color) = color {
+            overrides.insert(path_name.to_vec(), color);
+        } else {
+            overrides.remove(path_name);
+        }
+
+        self.generation.fetch_add(1);
+    }
+}
+
+/// A colormap that linearly interpolates between evenly spaced
+/// control colors
+#[derive(Debug, Clone)]
+pub struct Colormap {
+    colors: Vec<rgb::RGBA<f32>>,
+}
+
+impl Colormap {
+    /// Returns `None` if `colors` is empty
+    pub fn new(colors: Vec<rgb::RGBA<f32>>) -> Option<Self> {
+        if colors.is_empty() {
+            None
+        } else {
+            Some(Self { colors })
+        }
+    }
+
+    /// Samples the colormap at `t`, clamped to [0, 1]
+    pub fn sample(&self, t: f32) -> rgb::RGBA<f32> {
+        let last = self.colors.len() - 1;
+
+        let t = if t.is_nan() { 0.0 } else { t.max(0.0).min(1.0) };
+        let pos = t * last as f32;
+
+        let ix = (pos.floor() as usize).min(last);
+        let next = (ix + 1).min(last);
+        let f = pos - ix as f32;
+
+        let a = self.colors[ix];
+        let b = self.colors[next];
+
+        rgb::RGBA::new(
+            a.r + (b.r - a.r) * f,
+            a.g + (b.g - a.g) * f,
+            a.b + (b.b - a.b) * f,
+            a.a + (b.a - a.a) * f,
+        )
+    }
+
+    fn from_gradient(gradient: colorous::Gradient) -> Self {
+        const SAMPLES: usize = 32;
+
+        let colors = (0..SAMPLES)
+            .map(|i| {
+                let c = gradient.eval_rational(i, SAMPLES - 1);
+                rgb::RGBA::new(
+                    c.r as f32 / 255.0,
+                    c.g as f32 / 255.0,
+                    c.b as f32 / 255.0,
+                    1.0,
+                )
+            })
+            .collect();
+
+        Self { colors }
+    }
+}
+
+/// The colormaps registered from scripts with `register_colormap`,
+/// by name.
+///
+/// These are only sampled on the CPU, by script functions such as
+/// `overlay_from_column`; the gradient of value overlays is always
+/// one of the builtin `GradientName`s, which have GPU textures, so a
+/// registered colormap can't be picked as the overlay gradient.
+#[derive(Debug, Default, Clone)]
+pub struct Colormaps {
+    colormaps: Arc<RwLock<FxHashMap<String, Colormap>>>,
+}
+
+impl Colormaps {
+    /// The registered colormap with the given name, or if there is
+    /// none, the builtin gradient with that name
+    pub fn get(&self, name: &str) -> Option<Colormap> {
+        if let Some(colormap) = self.colormaps.read().get(name) {
+            return Some(colormap.clone());
+        }
+
+        std::array::IntoIter::new(Gradients::GRADIENT_NAMES)
+            .find(|gradient| gradient.to_string() == name)
+            .map(|gradient| Colormap::from_gradient(gradient.gradient()))
+    }
+
+    /// Registers the colormap, replacing any with the same name
+    pub fn insert(&self, name: &str, colormap: Colormap) {
+        self.colormaps.write().insert(name.to_string(), colormap);
+    }
+}
+
 pub fn hash_node_color(hash: u64) -> (f32, f32, f32) {
     let r_u16 = ((hash >> 32) & 0xFFFFFFFF) as u16;
     let g_u16 = ((hash >> 16) & 0xFFFFFFFF) as u16;
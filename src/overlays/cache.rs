@@ -0,0 +1,200 @@
+//! On-disk cache of overlay data, so that expensive overlay scripts
+//! don't have to be rerun every session.
+//!
+//! Caching is opt-in, and only used when a cache directory has been
+//! set (see `AppSettings::overlay_cache_dir`).
+//!
+//! Each cache file is keyed by a hash of the graph and a hash of the
+//! script source that produced the overlay, and contains the overlay
+//! kind followed by the overlay's per-node data as little-endian
+//! `f32`s. The hashes are computed with `StableHasher`, so that they
+//! are the same across sessions and builds.
+
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use handlegraph::{handle::Handle, handlegraph::*, pathhandlegraph::*};
+
+use rayon::prelude::*;
+
+use anyhow::Result;
+
+use crate::graph_query::GraphQuery;
+
+use super::OverlayData;
+
+const MAGIC: &[u8; 4] = b"GFOC";
+
+/// A 64-bit FNV-1a hasher; unlike `DefaultHasher`, its output is
+/// guaranteed not to change between Rust versions, so it can be used
+/// for keys that are stored on disk
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl std::default::Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A hash of the graph's node sequences and the paths on each node;
+/// this walks the entire graph, so it should be computed once per
+/// loaded graph
+pub fn graph_hash(rayon_pool: &rayon::ThreadPool, graph: &GraphQuery) -> u64 {
+    let graph = graph.graph();
+
+    let node_ids = {
+        let mut ids = graph.handles().map(|h| h.id()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    let mut node_hashes: Vec<u64> = Vec::with_capacity(node_ids.len());
+
+    rayon_pool.install(|| {
+        node_ids
+            .par_iter()
+            .map(|&id| {
+                let handle = Handle::pack(id, false);
+
+                let mut hasher = StableHasher::default();
+                hasher.write(&id.0.to_le_bytes());
+                hasher.write(&graph.sequence_vec(handle));
+
+                if let Some(steps) = graph.steps_on_handle(handle) {
+                    for (path, _) in steps {
+                        hasher.write(&path.0.to_le_bytes());
+                    }
+                }
+
+                hasher.finish()
+            })
+            .collect_into_vec(&mut node_hashes);
+    });
+
+    let mut hasher = StableHasher::default();
+    hasher.write(&(node_ids.len() as u64).to_le_bytes());
+    for node_hash in node_hashes {
+        hasher.write(&node_hash.to_le_bytes());
+    }
+    hasher.finish()
+}
+
+fn cache_path(dir: &Path, graph_hash: u64, script_hash: u64) -> PathBuf {
+    let mut path = dir.to_owned();
+    path.push(format!("{:016x}-{:016x}.bin", graph_hash, script_hash));
+    path
+}
+
+/// Loads the cached overlay data for the given graph and script
+/// hashes from the cache directory `dir`, if it exists
+pub fn load(
+    dir: &Path,
+    graph_hash: u64,
+    script_hash: u64,
+) -> Option<OverlayData> {
+    let path = cache_path(dir, graph_hash, script_hash);
+
+    let mut bytes = Vec::new();
+    std::fs::File::open(&path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .ok()?;
+
+    if bytes.len() < 5 || &bytes[0..4] != MAGIC {
+        log::warn!("ignoring invalid overlay cache file {:?}", path);
+        return None;
+    }
+
+    let values = bytes[5..]
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect::<Vec<_>>();
+
+    match bytes[4] {
+        0 if values.len() % 4 == 0 => {
+            let colors = values
+                .chunks_exact(4)
+                .map(|c| rgb::RGBA::new(c[0], c[1], c[2], c[3]))
+                .collect();
+            Some(OverlayData::RGB(colors))
+        }
+        1 => Some(OverlayData::Value(values)),
+        _ => {
+            log::warn!("ignoring invalid overlay cache file {:?}", path);
+            None
+        }
+    }
+}
+
+/// Writes the overlay data to the cache directory `dir`, replacing
+/// any existing entry for the given graph and script hashes
+pub fn store(
+    dir: &Path,
+    graph_hash: u64,
+    script_hash: u64,
+    data: &OverlayData,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+
+    match data {
+        OverlayData::RGB(colors) => {
+            bytes.push(0);
+            for c in colors {
+                for v in [c.r, c.g, c.b, c.a].iter() {
+                    bytes.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+        OverlayData::Value(values) => {
+            bytes.push(1);
+            for v in values {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+
+    let mut file =
+        std::fs::File::create(cache_path(dir, graph_hash, script_hash))?;
+    file.write_all(&bytes)?;
+
+    Ok(())
+}
+
+/// Deletes all cached overlays in the cache directory `dir`,
+/// returning the number of files removed
+pub fn clear(dir: &Path) -> Result<usize> {
+    let dir = match std::fs::read_dir(dir) {
+        Ok(dir) => dir,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut count = 0;
+
+    for entry in dir {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("bin") {
+            std::fs::remove_file(path)?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
@@ -1,8 +1,13 @@
 use std::pin::Pin;
 use std::sync::Arc;
 
-use crossbeam::channel::{Receiver, Sender};
+use crossbeam::{
+    atomic::AtomicCell,
+    channel::{Receiver, Sender},
+};
 use futures::{future::RemoteHandle, task::SpawnExt, Future};
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
 
 mod modal;
 mod paired;
@@ -25,6 +30,8 @@ pub struct Reactor {
     pub overlay_create_tx: Sender<OverlayCreatorMsg>,
     pub overlay_create_rx: Receiver<OverlayCreatorMsg>,
 
+    pub running_scripts: RunningScripts,
+
     pub future_tx:
         Sender<Pin<Box<dyn Future<Output = ()> + Send + Sync + 'static>>>,
     // pub future_tx: Sender<Box<dyn Future<Output = ()> + 'static>>,
@@ -64,6 +71,8 @@ impl Reactor {
             overlay_create_tx: channels.new_overlay_tx.clone(),
             overlay_create_rx: channels.new_overlay_rx.clone(),
 
+            running_scripts: RunningScripts::default(),
+
             future_tx: task_tx,
             // task_rx,
             _task_thread,
@@ -176,3 +185,44 @@ impl Reactor {
         Ok(())
     }
 }
+
+/// The cancellation tokens of the scripts that are currently being
+/// evaluated, so that they can be aborted from the GUI.
+///
+/// A script's engine should check its token in an `on_progress`
+/// callback, and the script must be removed with `finish` once its
+/// evaluation ends.
+#[derive(Debug, Default, Clone)]
+pub struct RunningScripts {
+    next_id: Arc<AtomicCell<u64>>,
+    tokens: Arc<Mutex<FxHashMap<u64, Arc<AtomicCell<bool>>>>>,
+}
+
+impl RunningScripts {
+    /// Registers a new script, returning its ID and cancellation
+    /// token
+    pub fn start(&self) -> (u64, Arc<AtomicCell<bool>>) {
+        let id = self.next_id.fetch_add(1);
+        let token = Arc::new(AtomicCell::new(false));
+        self.tokens.lock().insert(id, token.clone());
+        (id, token)
+    }
+
+    pub fn finish(&self, id: u64) {
+        self.tokens.lock().remove(&id);
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.tokens.lock().len()
+    }
+
+    /// Signals every running script to abort, returning the number
+    /// of scripts that were running
+    pub fn cancel_all(&self) -> usize {
+        let tokens = self.tokens.lock();
+        for token in tokens.values() {
+            token.store(true);
+        }
+        tokens.len()
+    }
+}
@@ -1,3 +1,6 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam::atomic::AtomicCell;
 use rhai::{Engine, EvalAltResult};
 
 use rayon::prelude::*;
@@ -10,6 +13,7 @@ use handlegraph::{
 
 use rustc_hash::FxHashMap;
 
+use crate::annotations::AnnotationCollection;
 use crate::overlays::{OverlayData, OverlayKind};
 use crate::{app::selection::NodeSelection, graph_query::GraphQuery};
 
@@ -17,6 +21,10 @@ use rhai::plugin::*;
 
 pub mod plugins;
 
+/// The number of nodes colored between each progress update in
+/// `overlay_colors_tgt_ast`
+const PROGRESS_CHUNK_SIZE: usize = 4096;
+
 pub fn create_engine() -> Engine {
     let mut engine = Engine::new();
 
@@ -193,6 +201,7 @@ pub fn overlay_colors_tgt_ast(
     engine: &rhai::Engine,
     scope: rhai::Scope<'_>,
     node_color_ast: rhai::AST,
+    progress: Option<&AtomicCell<Option<f32>>>,
 ) -> std::result::Result<OverlayData, Box<EvalAltResult>> {
     match config.target.clone() {
         ScriptTarget::Nodes => {
@@ -200,28 +209,48 @@ pub fn overlay_colors_tgt_ast(
                 graph.graph().handles().map(|h| h.id()).collect::<Vec<_>>();
             node_ids.sort();
 
-            let values = rayon_pool.install(|| {
-                let mut values: Vec<rhai::Dynamic> =
-                    Vec::with_capacity(node_ids.len());
-                node_ids
-                    .into_par_iter()
-                    .map_with(scope, |mut thread_scope, node_id| {
-                        let value = engine
-                            .call_fn(
-                                &mut thread_scope,
-                                &node_color_ast,
-                                "node_color",
-                                (node_id,),
-                            )
-                            .unwrap();
+            let node_count = node_ids.len().max(1);
+            let nodes_done = AtomicUsize::new(0);
 
-                        value
-                    })
-                    .collect_into_vec(&mut values);
+            if let Some(progress) = progress {
+                progress.store(Some(0.0));
+            }
 
-                values
+            // the nodes are processed in chunks so that the progress
+            // can be updated without touching the atomics per node
+            let chunks = rayon_pool.install(|| {
+                node_ids
+                    .par_chunks(PROGRESS_CHUNK_SIZE)
+                    .map_with(scope, |thread_scope, chunk| {
+                        let values = chunk
+                            .iter()
+                            .map(|&node_id| {
+                                engine
+                                    .call_fn(
+                                        thread_scope,
+                                        &node_color_ast,
+                                        "node_color",
+                                        (node_id,),
+                                    )
+                                    .unwrap()
+                            })
+                            .collect::<Vec<rhai::Dynamic>>();
+
+                        if let Some(progress) = progress {
+                            let done = chunk.len()
+                                + nodes_done
+                                    .fetch_add(chunk.len(), Ordering::Relaxed);
+                            progress
+                                .store(Some(done as f32 / node_count as f32));
+                        }
+
+                        values
+                    })
+                    .collect::<Vec<_>>()
             });
 
+            let values = chunks.into_iter().flatten().collect::<Vec<_>>();
+
             let data = cast_overlay_data(values)
                 .ok_or("Couldn't process overlay data")?;
 
@@ -459,6 +488,86 @@ pub fn overlay_colors_tgt(
     }
 }
 
+/// Calls the `keep` function in `keep_ast` on every record in
+/// `records`, in parallel on the rayon pool, and returns the indices
+/// of the records for which it returned `true`, in order.
+pub fn filter_records_ast<C>(
+    rayon_pool: &rayon::ThreadPool,
+    engine: &rhai::Engine,
+    scope: rhai::Scope<'_>,
+    keep_ast: rhai::AST,
+    records: &C,
+) -> std::result::Result<Vec<usize>, Box<EvalAltResult>>
+where
+    C: AnnotationCollection,
+    C::Record: Clone + Send + Sync + 'static,
+{
+    let results = rayon_pool.install(|| {
+        let mut results: Vec<std::result::Result<bool, Box<EvalAltResult>>> =
+            Vec::with_capacity(records.len());
+
+        records
+            .records()
+            .par_iter()
+            .map_with(scope, |mut thread_scope, record| {
+                engine.call_fn(
+                    &mut thread_scope,
+                    &keep_ast,
+                    "keep",
+                    (record.clone(),),
+                )
+            })
+            .collect_into_vec(&mut results);
+
+        results
+    });
+
+    let mut indices = Vec::new();
+
+    for (ix, keep) in results.into_iter().enumerate() {
+        if keep? {
+            indices.push(ix);
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Runs the `keep` function defined in `keep_ast` on each node color
+/// of an overlay, in parallel on the rayon pool, and returns the IDs
+/// of the nodes for which it returned `true`, in order.
+pub fn filter_colors_ast(
+    rayon_pool: &rayon::ThreadPool,
+    engine: &rhai::Engine,
+    scope: rhai::Scope<'_>,
+    keep_ast: rhai::AST,
+    colors: &[rgb::RGBA<f32>],
+) -> std::result::Result<Vec<NodeId>, Box<EvalAltResult>> {
+    let results = rayon_pool.install(|| {
+        let mut results: Vec<std::result::Result<bool, Box<EvalAltResult>>> =
+            Vec::with_capacity(colors.len());
+
+        colors
+            .par_iter()
+            .map_with(scope, |mut thread_scope, &color| {
+                engine.call_fn(&mut thread_scope, &keep_ast, "keep", (color,))
+            })
+            .collect_into_vec(&mut results);
+
+        results
+    });
+
+    let mut nodes = Vec::new();
+
+    for (ix, keep) in results.into_iter().enumerate() {
+        if keep? {
+            nodes.push(NodeId::from((ix + 1) as u64));
+        }
+    }
+
+    Ok(nodes)
+}
+
 pub fn hash_node_seq(graph: &GraphQuery, node_id: NodeId) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
@@ -537,6 +537,33 @@ pub mod selection {
         first.difference(&other)
     }
 
+    // free function versions of the above, so that selections can
+    // be composed, e.g.
+    // `selection_difference(path_selection("A"), path_selection("B"))`
+    #[rhai_fn(pure)]
+    pub fn selection_union(
+        a: &mut NodeSelection,
+        b: NodeSelection,
+    ) -> NodeSelection {
+        a.union(&b)
+    }
+
+    #[rhai_fn(pure)]
+    pub fn selection_intersect(
+        a: &mut NodeSelection,
+        b: NodeSelection,
+    ) -> NodeSelection {
+        a.intersection(&b)
+    }
+
+    #[rhai_fn(pure)]
+    pub fn selection_difference(
+        a: &mut NodeSelection,
+        b: NodeSelection,
+    ) -> NodeSelection {
+        a.difference(&b)
+    }
+
     pub fn add_one(sel: &mut NodeSelection, node: NodeId) {
         sel.add_one(false, node);
     }
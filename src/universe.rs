@@ -15,6 +15,10 @@ use log::{debug, error, info, trace, warn};
 
 use anyhow::Result;
 
+use rustc_hash::FxHashMap;
+
+use gfa_modified::optfields::{OptField, OptFieldVal};
+
 use crate::vulkan::{draw_system::Vertex, GfaestusVk};
 use crate::{geometry::*, vulkan::draw_system::nodes::NodeVertices};
 
@@ -74,6 +78,30 @@ pub struct Universe<G: GraphLayout> {
     // view_config: ViewConfig,
 }
 
+/// Where the node positions of the graph layout were read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutSource {
+    /// A layout file, as produced by e.g. `odgi layout`
+    LayoutFile,
+    /// `LX`/`LY` tags on the GFA segments
+    GfaTags,
+}
+
+impl std::default::Default for LayoutSource {
+    fn default() -> Self {
+        Self::LayoutFile
+    }
+}
+
+impl LayoutSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LayoutSource::LayoutFile => "layout file",
+            LayoutSource::GfaTags => "GFA tags",
+        }
+    }
+}
+
 impl<G: GraphLayout> Universe<G> {
     pub fn layout(&self) -> &G {
         &self.graph_layout
@@ -103,6 +131,27 @@ impl Universe<FlatLayout> {
         })
     }
 
+    /// Builds the layout from coordinates stored as `LX`/`LY` tags
+    /// on the GFA segments, see `FlatLayout::from_gfa_tags`. Returns
+    /// `None` if any node is missing its coordinates.
+    pub fn from_gfa_tags(
+        graph: &PackedGraph,
+        tags: &FxHashMap<NodeId, Vec<OptField>>,
+    ) -> Option<Self> {
+        let bp_per_world_unit = 1.0;
+        let offset = Point::new(0.0, 0.0);
+        let angle = 0.0;
+
+        let graph_layout = FlatLayout::from_gfa_tags(graph, tags)?;
+
+        Some(Self {
+            bp_per_world_unit,
+            graph_layout,
+            offset,
+            angle,
+        })
+    }
+
     pub fn update_positions_from_gpu(
         &mut self,
         app: &GfaestusVk,
@@ -180,6 +229,59 @@ impl Node {
         let diff = self.p1 - self.p0;
         self.p0 + (diff / 2.0)
     }
+
+    /// The distance from `p` to the closest point on the line
+    /// segment between the node's endpoints
+    pub fn distance_to(&self, p: Point) -> f32 {
+        let seg = self.p1 - self.p0;
+        let len_sq = seg.x * seg.x + seg.y * seg.y;
+
+        if len_sq == 0.0 {
+            return p.dist(self.p0);
+        }
+
+        let t = ((p - self.p0).x * seg.x + (p - self.p0).y * seg.y) / len_sq;
+        let t = t.clamp(0.0, 1.0);
+
+        p.dist(self.p0 + seg * t)
+    }
+
+    /// Whether any part of the line segment between the node's
+    /// endpoints is inside `rect`
+    pub fn intersects_rect(&self, rect: Rect) -> bool {
+        if rect.contains(self.p0) || rect.contains(self.p1) {
+            return true;
+        }
+
+        // clip the segment against each side of the rectangle
+        let d = self.p1 - self.p0;
+        let (min, max) = (rect.min(), rect.max());
+
+        let sides = [
+            (-d.x, self.p0.x - min.x),
+            (d.x, max.x - self.p0.x),
+            (-d.y, self.p0.y - min.y),
+            (d.y, max.y - self.p0.y),
+        ];
+
+        let mut t0 = 0.0f32;
+        let mut t1 = 1.0f32;
+
+        for &(p, q) in sides.iter() {
+            if p == 0.0 {
+                // parallel to this side, and outside it
+                if q < 0.0 {
+                    return false;
+                }
+            } else if p < 0.0 {
+                t0 = t0.max(q / p);
+            } else {
+                t1 = t1.min(q / p);
+            }
+        }
+
+        t0 <= t1
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -229,8 +331,6 @@ impl FlatLayout {
         use std::io::prelude::*;
         use std::io::BufReader;
 
-        use rustc_hash::FxHashMap;
-
         info!("loading layout");
         let layout_file = File::open(layout_path)?;
         let reader = BufReader::new(layout_file);
@@ -296,6 +396,79 @@ impl FlatLayout {
             }
         }
 
+        Ok(Self::from_node_positions(
+            graph,
+            &layout_map,
+            &component_map,
+            components,
+        ))
+    }
+
+    /// Reads the node positions from the `LX` and `LY` tags of each
+    /// segment. The tags can either be numbers, giving the start of
+    /// the node, in which case the node is laid out horizontally
+    /// with its sequence length, or numeric arrays holding both the
+    /// start and end coordinates, e.g. `LX:B:f,0.0,12.5`.
+    ///
+    /// Returns `None` if any node lacks either tag.
+    fn from_gfa_tags(
+        graph: &PackedGraph,
+        tags: &FxHashMap<NodeId, Vec<OptField>>,
+    ) -> Option<Self> {
+        fn coords(field: &OptField) -> Option<(f32, Option<f32>)> {
+            match &field.value {
+                OptFieldVal::Float(v) => Some((*v, None)),
+                OptFieldVal::Int(v) => Some((*v as f32, None)),
+                OptFieldVal::BFloat(vs) if !vs.is_empty() => {
+                    Some((vs[0], vs.get(1).copied()))
+                }
+                OptFieldVal::BInt(vs) if !vs.is_empty() => {
+                    Some((vs[0] as f32, vs.get(1).map(|&v| v as f32)))
+                }
+                _ => None,
+            }
+        }
+
+        let mut layout_map: FxHashMap<NodeId, (Point, Point)> =
+            FxHashMap::default();
+
+        for handle in graph.handles() {
+            let id = handle.id();
+            let fields = tags.get(&id)?;
+
+            let find = |tag: &[u8; 2]| {
+                fields.iter().find(|f| &f.tag == tag).and_then(coords)
+            };
+
+            let (x0, x1) = find(b"LX")?;
+            let (y0, y1) = find(b"LY")?;
+
+            let p0 = Point::new(x0, y0);
+
+            let p1 = match (x1, y1) {
+                (Some(x1), Some(y1)) => Point::new(x1, y1),
+                _ => p0 + Point::new(graph.node_len(handle) as f32, 0.0),
+            };
+
+            layout_map.insert(id, (p0, p1));
+        }
+
+        info!("using layout from GFA tags");
+
+        Some(Self::from_node_positions(
+            graph,
+            &layout_map,
+            &FxHashMap::default(),
+            Vec::new(),
+        ))
+    }
+
+    fn from_node_positions(
+        graph: &PackedGraph,
+        layout_map: &FxHashMap<NodeId, (Point, Point)>,
+        component_map: &FxHashMap<NodeId, usize>,
+        components: Vec<usize>,
+    ) -> Self {
         let mut node_ids = Vec::with_capacity(graph.node_count());
         let mut nodes = Vec::with_capacity(graph.node_count());
 
@@ -337,12 +510,12 @@ impl FlatLayout {
         let top_left = Point::new(min_x, min_y);
         let bottom_right = Point::new(max_x, max_y);
 
-        Ok(FlatLayout {
+        FlatLayout {
             node_ids,
             nodes,
             component_offsets: components,
             top_left,
             bottom_right,
-        })
+        }
     }
 }
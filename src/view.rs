@@ -1,4 +1,4 @@
-use crate::geometry::Point;
+use crate::geometry::{Point, Rect};
 
 use nalgebra_glm as glm;
 
@@ -134,6 +134,55 @@ impl View {
         View { center, scale }
     }
 
+    /// Adjusts the view for a change in screen size, keeping the same
+    /// center, and scaling it so that the world region that was
+    /// visible at `old_dims` fits on the screen at `new_dims`
+    pub fn resized<D: Into<ScreenDims>>(
+        self,
+        old_dims: D,
+        new_dims: D,
+    ) -> Self {
+        let old = old_dims.into();
+        let new = new_dims.into();
+
+        if old.width <= 0.0
+            || old.height <= 0.0
+            || new.width <= 0.0
+            || new.height <= 0.0
+        {
+            return self;
+        }
+
+        let ratio = (old.width / new.width).max(old.height / new.height);
+
+        View {
+            center: self.center,
+            scale: self.scale * ratio,
+        }
+    }
+
+    /// Returns a view centered on `rect`, scaled so that the entire
+    /// rectangle fits on the screen, with the extra space given by
+    /// `padding` (e.g. 1.2 for 20% extra)
+    pub fn fit_rect<D: Into<ScreenDims>>(
+        screen_dims: D,
+        rect: Rect,
+        padding: f32,
+    ) -> Self {
+        let dims = screen_dims.into();
+
+        // avoid a zero scale if the rectangle is a single point
+        let width = rect.width().max(1.0);
+        let height = rect.height().max(1.0);
+
+        let scale = (width / dims.width).max(height / dims.height);
+
+        View {
+            center: rect.center(),
+            scale: scale * padding,
+        }
+    }
+
     #[rustfmt::skip]
     #[inline]
     pub fn to_scaled_matrix(&self) -> glm::Mat4 {
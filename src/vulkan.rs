@@ -33,7 +33,10 @@ use winit::{
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 
-use crate::{app::Args, view::ScreenDims};
+use crate::{
+    app::{window_title, Args},
+    view::ScreenDims,
+};
 
 pub struct GfaestusVk {
     pub allocator: Allocator,
@@ -46,6 +49,10 @@ pub struct GfaestusVk {
 
     pub msaa_samples: vk::SampleCountFlags,
 
+    /// The present mode used when the swapchain is (re)created, if
+    /// the surface supports it
+    pub present_mode: PresentMode,
+
     pub swapchain: Swapchain,
     pub swapchain_khr: vk::SwapchainKHR,
     pub swapchain_props: SwapchainProperties,
@@ -104,7 +111,7 @@ impl GfaestusVk {
 
             log::debug!("Creating window");
             let window = WindowBuilder::new()
-                .with_title("Gfaestus")
+                .with_title(window_title(&args.gfa))
                 .with_inner_size(winit::dpi::PhysicalSize::new(800, 600))
                 .build(&event_loop)?;
 
@@ -165,12 +172,15 @@ impl GfaestusVk {
         let width = 800u32;
         let height = 600u32;
 
+        let present_mode = PresentMode::default();
+
         let (swapchain, swapchain_khr, swapchain_props, images) =
             create_swapchain_and_images(
                 &vk_context,
                 graphics_ix,
                 present_ix,
                 [width, height],
+                present_mode,
             )?;
         let swapchain_image_views = create_swapchain_image_views(
             vk_context.device(),
@@ -244,6 +254,8 @@ impl GfaestusVk {
 
             msaa_samples,
 
+            present_mode,
+
             swapchain,
             swapchain_khr,
             swapchain_props,
@@ -1225,6 +1237,45 @@ impl GfaestusVk {
         Ok((buffer, memory))
     }
 
+    /// Sets the MSAA sample count used by the node and edge render
+    /// passes, and recreates the swapchain and its attachments if it
+    /// changed; 1 disables MSAA.
+    ///
+    /// Returns the sample count now in use; the node and edge
+    /// pipelines must be rebuilt if it differs from the previous one.
+    ///
+    /// Returns an error, without changing anything, if the device
+    /// doesn't support the sample count.
+    pub fn set_msaa_samples(
+        &mut self,
+        samples: u32,
+    ) -> Result<vk::SampleCountFlags> {
+        let max_samples = self.vk_context.get_max_usable_sample_count();
+
+        if !samples.is_power_of_two() || samples > max_samples.as_raw() {
+            anyhow::bail!(
+                "Unsupported MSAA sample count {}, must be a power of two \
+                 no greater than {}",
+                samples,
+                max_samples.as_raw()
+            );
+        }
+
+        let msaa_samples = vk::SampleCountFlags::from_raw(samples);
+
+        if msaa_samples != self.msaa_samples {
+            log::debug!(
+                "Changing MSAA samples from {:?} to {:?}",
+                self.msaa_samples,
+                msaa_samples
+            );
+            self.msaa_samples = msaa_samples;
+            self.recreate_swapchain(None)?;
+        }
+
+        Ok(self.msaa_samples)
+    }
+
     pub fn recreate_swapchain(
         &mut self,
         dimensions: Option<[u32; 2]>,
@@ -1246,6 +1297,7 @@ impl GfaestusVk {
                 self.graphics_family_index,
                 self.present_family_index,
                 dimensions,
+                self.present_mode,
             )?;
 
         let swapchain_image_views =
@@ -1434,6 +1486,48 @@ impl Drop for GfaestusVk {
     }
 }
 
+/// The swapchain present modes that can be chosen with the
+/// `present_mode` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PresentMode {
+    Fifo,
+    Mailbox,
+}
+
+impl std::default::Default for PresentMode {
+    fn default() -> Self {
+        Self::Fifo
+    }
+}
+
+impl PresentMode {
+    pub fn vk_mode(&self) -> vk::PresentModeKHR {
+        match self {
+            PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+            PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PresentMode::Fifo => "fifo",
+            PresentMode::Mailbox => "mailbox",
+        }
+    }
+}
+
+impl std::str::FromStr for PresentMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        match s {
+            "fifo" => Ok(PresentMode::Fifo),
+            "mailbox" => Ok(PresentMode::Mailbox),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct SwapchainProperties {
     pub extent: vk::Extent2D,
@@ -1480,10 +1574,13 @@ impl SwapchainSupportDetails {
     fn get_ideal_swapchain_properties(
         &self,
         preferred_dimensions: [u32; 2],
+        preferred_present_mode: PresentMode,
     ) -> SwapchainProperties {
         let format = Self::choose_swapchain_surface_format(&self.formats);
-        let present_mode =
-            Self::choose_swapchain_surface_present_mode(&self.present_modes);
+        let present_mode = Self::choose_swapchain_surface_present_mode(
+            &self.present_modes,
+            preferred_present_mode,
+        );
         let extent = Self::choose_swapchain_extent(
             self.capabilities,
             preferred_dimensions,
@@ -1522,16 +1619,31 @@ impl SwapchainSupportDetails {
 
     /// Choose the swapchain present mode.
     ///
-    /// Will favor MAILBOX if present otherwise FIFO.
-    /// If none is present it will fallback to IMMEDIATE.
+    /// Will use the preferred mode if present, otherwise FIFO, then
+    /// MAILBOX. If none is present it will fallback to IMMEDIATE.
     fn choose_swapchain_surface_present_mode(
         available_present_modes: &[vk::PresentModeKHR],
+        preferred: PresentMode,
     ) -> vk::PresentModeKHR {
         let checkit = |v| available_present_modes.contains(&v).then(|| v);
 
-        checkit(vk::PresentModeKHR::FIFO)
-            .or(checkit(vk::PresentModeKHR::MAILBOX))
-            .unwrap_or(vk::PresentModeKHR::IMMEDIATE)
+        if let Some(mode) = checkit(preferred.vk_mode()) {
+            return mode;
+        }
+
+        log::warn!(
+            "Present mode {} is not supported, falling back",
+            preferred.as_str()
+        );
+
+        // mailbox is only requested to avoid waiting on vsync, e.g. in
+        // the benchmark, so immediate is the closer fallback
+        match preferred {
+            PresentMode::Mailbox => checkit(vk::PresentModeKHR::IMMEDIATE)
+                .unwrap_or(vk::PresentModeKHR::FIFO),
+            PresentMode::Fifo => checkit(vk::PresentModeKHR::MAILBOX)
+                .unwrap_or(vk::PresentModeKHR::IMMEDIATE),
+        }
     }
 
     /// Choose the swapchain extent.
@@ -1,10 +1,14 @@
 use crate::{
     geometry::{Point, Rect},
-    universe::FlatLayout,
+    universe::{FlatLayout, GraphLayout},
     view::{ScreenDims, View},
 };
 
-use handlegraph::{handle::Edge, handlegraph::*};
+use handlegraph::{
+    handle::{Edge, Handle},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
 
 use handlegraph::packedgraph::PackedGraph;
 
@@ -15,12 +19,15 @@ use anyhow::Result;
 
 use nalgebra_glm as glm;
 
+use rustc_hash::FxHashMap;
+
 use std::ffi::CString;
 
 use super::create_shader_module;
 use super::Vertex;
 
 use super::nodes::NodePushConstants;
+use crate::overlays::PathColors;
 use crate::vulkan::render_pass::Framebuffers;
 use crate::vulkan::{draw_system::nodes::NodeVertices, GfaestusVk};
 
@@ -43,10 +50,10 @@ pub struct EdgeRenderer {
 }
 
 impl EdgeRenderer {
-    fn layout_binding() -> vk::DescriptorSetLayoutBinding {
+    fn layout_bindings() -> [vk::DescriptorSetLayoutBinding; 3] {
         use vk::ShaderStageFlags as Stages;
 
-        vk::DescriptorSetLayoutBinding::builder()
+        let ubo = vk::DescriptorSetLayoutBinding::builder()
             .binding(0)
             .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
             .descriptor_count(1)
@@ -56,14 +63,29 @@ impl EdgeRenderer {
                     | Stages::TESSELLATION_EVALUATION
                     | Stages::FRAGMENT,
             )
-            .build()
+            .build();
+
+        let control_points = vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(Stages::TESSELLATION_EVALUATION)
+            .build();
+
+        let edge_colors = vk::DescriptorSetLayoutBinding::builder()
+            .binding(2)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(Stages::FRAGMENT)
+            .build();
+
+        [ubo, control_points, edge_colors]
     }
 
     fn create_descriptor_set_layout(
         device: &Device,
     ) -> Result<vk::DescriptorSetLayout> {
-        let binding = Self::layout_binding();
-        let bindings = [binding];
+        let bindings = Self::layout_bindings();
 
         let layout_info = vk::DescriptorSetLayoutCreateInfo::builder()
             .bindings(&bindings)
@@ -256,7 +278,7 @@ impl EdgeRenderer {
                         | Flags::FRAGMENT,
                 )
                 .offset(0)
-                .size(84)
+                .size(NodePushConstants::PC_RANGE)
                 .build();
 
             let pc_ranges = [pc_range];
@@ -328,12 +350,17 @@ impl EdgeRenderer {
         let image_count = 1;
 
         let descriptor_pool = {
-            let pool_size = vk::DescriptorPoolSize {
+            let ubo_size = vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::UNIFORM_BUFFER,
                 descriptor_count: image_count,
             };
 
-            let pool_sizes = [pool_size];
+            let control_points_size = vk::DescriptorPoolSize {
+                ty: vk::DescriptorType::STORAGE_BUFFER,
+                descriptor_count: image_count * 2,
+            };
+
+            let pool_sizes = [ubo_size, control_points_size];
 
             let pool_info = vk::DescriptorPoolCreateInfo::builder()
                 .pool_sizes(&pool_sizes)
@@ -354,6 +381,9 @@ impl EdgeRenderer {
             unsafe { device.allocate_descriptor_sets(&alloc_info) }
         }?;
 
+        let edge_index_buffer =
+            EdgeIndices::new_with_components(app, graph, layout)?;
+
         for set in descriptor_sets.iter() {
             let buf_info = vk::DescriptorBufferInfo::builder()
                 .buffer(ubo.buffer)
@@ -363,7 +393,7 @@ impl EdgeRenderer {
 
             let buf_infos = [buf_info];
 
-            let descriptor_write = vk::WriteDescriptorSet::builder()
+            let ubo_write = vk::WriteDescriptorSet::builder()
                 .dst_set(*set)
                 .dst_binding(0)
                 .dst_array_element(0)
@@ -371,7 +401,40 @@ impl EdgeRenderer {
                 .buffer_info(&buf_infos)
                 .build();
 
-            let descriptor_writes = [descriptor_write];
+            let control_buf_info = vk::DescriptorBufferInfo::builder()
+                .buffer(edge_index_buffer.control_points)
+                .offset(0)
+                .range(vk::WHOLE_SIZE)
+                .build();
+
+            let control_buf_infos = [control_buf_info];
+
+            let control_points_write = vk::WriteDescriptorSet::builder()
+                .dst_set(*set)
+                .dst_binding(1)
+                .dst_array_element(0)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&control_buf_infos)
+                .build();
+
+            let colors_buf_info = vk::DescriptorBufferInfo::builder()
+                .buffer(edge_index_buffer.edge_colors)
+                .offset(0)
+                .range(vk::WHOLE_SIZE)
+                .build();
+
+            let colors_buf_infos = [colors_buf_info];
+
+            let edge_colors_write = vk::WriteDescriptorSet::builder()
+                .dst_set(*set)
+                .dst_binding(2)
+                .dst_array_element(0)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&colors_buf_infos)
+                .build();
+
+            let descriptor_writes =
+                [ubo_write, control_points_write, edge_colors_write];
 
             unsafe { device.update_descriptor_sets(&descriptor_writes, &[]) }
         }
@@ -403,9 +466,6 @@ impl EdgeRenderer {
             }
         };
 
-        let edge_index_buffer =
-            EdgeIndices::new_with_components(app, graph, layout)?;
-
         Ok(Self {
             descriptor_pool,
             descriptor_set_layout: desc_set_layout,
@@ -423,6 +483,99 @@ impl EdgeRenderer {
         })
     }
 
+    /// Recreates the edge pipeline against the current edge render
+    /// pass, e.g. after the MSAA sample count has changed.
+    ///
+    /// The GPU must be idle when this is called.
+    pub fn recreate_pipeline(&mut self, app: &GfaestusVk) -> Result<()> {
+        let device = app.vk_context().device();
+
+        let msaa_samples = app.msaa_samples;
+        let render_pass = app.render_passes.edges;
+
+        let layouts = [self.descriptor_set_layout];
+
+        use crate::vulkan::context::EdgeRendererType as Edges;
+
+        let (pipeline, pipeline_layout) =
+            match app.vk_context().renderer_config.edges {
+                Edges::TessellationIsolines => Self::create_isoline_pipeline(
+                    device,
+                    msaa_samples,
+                    render_pass,
+                    &layouts,
+                ),
+                Edges::TessellationQuads => Self::create_quad_pipeline(
+                    device,
+                    msaa_samples,
+                    render_pass,
+                    &layouts,
+                ),
+                Edges::Disabled => {
+                    anyhow::bail!("Tried to create a Disabled edge renderer!");
+                }
+            };
+
+        unsafe {
+            self.device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            self.device.destroy_pipeline(self.pipeline, None);
+        }
+
+        self.pipeline = pipeline;
+        self.pipeline_layout = pipeline_layout;
+
+        Ok(())
+    }
+
+    /// Whether the edge colors used by the `ByPath` color mode have
+    /// been computed, and are up to date
+    pub fn has_path_colors(&self) -> bool {
+        self.edge_index_buffer.has_path_colors
+    }
+
+    /// Marks the path edge colors as out of date, e.g. after the
+    /// palette changed, so that they're recomputed with
+    /// `update_path_colors` the next time they're needed
+    pub fn clear_path_colors(&mut self) {
+        self.edge_index_buffer.has_path_colors = false;
+    }
+
+    /// Computes the edge colors used by the `ByPath` color mode; this
+    /// is only done when that mode is used, as it walks every path.
+    ///
+    /// The GPU must be idle when this is called.
+    pub fn update_path_colors(
+        &mut self,
+        app: &GfaestusVk,
+        graph: &PackedGraph,
+        path_colors: &PathColors,
+    ) -> Result<()> {
+        self.edge_index_buffer.update_path_colors(app, graph, path_colors)?;
+
+        let colors_buf_info = vk::DescriptorBufferInfo::builder()
+            .buffer(self.edge_index_buffer.edge_colors)
+            .offset(0)
+            .range(vk::WHOLE_SIZE)
+            .build();
+
+        let colors_buf_infos = [colors_buf_info];
+
+        let edge_colors_write = vk::WriteDescriptorSet::builder()
+            .dst_set(self.descriptor_set)
+            .dst_binding(2)
+            .dst_array_element(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .buffer_info(&colors_buf_infos)
+            .build();
+
+        let descriptor_writes = [edge_colors_write];
+
+        unsafe { self.device.update_descriptor_sets(&descriptor_writes, &[]) }
+
+        Ok(())
+    }
+
     pub fn destroy(&mut self) {
         unsafe {
             self.device.destroy_descriptor_set_layout(
@@ -439,6 +592,13 @@ impl EdgeRenderer {
         }
     }
 
+    /// Frees the edge index and uniform buffers; `destroy` only frees
+    /// the pipeline and descriptors
+    pub fn destroy_buffers(&self, app: &GfaestusVk) -> Result<()> {
+        self.edge_index_buffer.destroy(app);
+        self.ubo.destroy(app)
+    }
+
     pub fn write_ubo(&mut self, ubo: &EdgesUBO) -> Result<()> {
         self.ubo.ubo = *ubo;
         self.ubo.write_ubo()
@@ -575,9 +735,179 @@ pub struct EdgeIndices {
     allocation_info: vk_mem::AllocationInfo,
 
     edge_count: usize,
+
+    /// One world-space control point per edge, used by the
+    /// tessellation evaluation shaders when edge bundling is enabled
+    pub control_points: vk::Buffer,
+
+    control_allocation: vk_mem::Allocation,
+    control_allocation_info: vk_mem::AllocationInfo,
+
+    /// One color per edge, blended from the colors of the paths that
+    /// traverse it, used when the edge color mode is `ByPath`; until
+    /// the colors are computed, this only holds a placeholder
+    pub edge_colors: vk::Buffer,
+
+    colors_allocation: vk_mem::Allocation,
+    colors_allocation_info: vk_mem::AllocationInfo,
+
+    has_path_colors: bool,
+
+    /// The index of each edge in the buffers, for recomputing the
+    /// edge colors
+    edge_indices: FxHashMap<Edge, usize>,
 }
 
 impl EdgeIndices {
+    pub fn destroy(&self, app: &GfaestusVk) {
+        app.allocator.destroy_buffer(self.buffer, &self.allocation);
+        app.allocator
+            .destroy_buffer(self.control_points, &self.control_allocation);
+        app.allocator
+            .destroy_buffer(self.edge_colors, &self.colors_allocation);
+    }
+
+    /// Size of the bundling grid cells, relative to the mean edge length
+    const BUNDLE_CELL_SCALE: f32 = 4.0;
+
+    /// How far each edge's control point is pulled from the edge
+    /// midpoint toward the mean midpoint of its grid cell
+    const BUNDLE_STRENGTH: f32 = 0.8;
+
+    /// Bins the edge midpoints into a uniform grid, and pulls each
+    /// edge's control point toward the mean midpoint of its cell, so
+    /// that edges crossing the same dense region curve into a shared
+    /// bundle.
+    fn bundled_control_points(
+        layout: &FlatLayout,
+        edges: &[u32],
+    ) -> Vec<[f32; 2]> {
+        let nodes = layout.nodes();
+
+        let endpoint = |ix: u32| -> Point {
+            let node = &nodes[(ix / 2) as usize];
+            if ix % 2 == 0 {
+                node.p0
+            } else {
+                node.p1
+            }
+        };
+
+        let mut total_len = 0.0;
+
+        let midpoints = edges
+            .chunks_exact(2)
+            .map(|pair| {
+                let p0 = endpoint(pair[0]);
+                let p1 = endpoint(pair[1]);
+
+                let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+                total_len += (dx * dx + dy * dy).sqrt();
+
+                Point {
+                    x: (p0.x + p1.x) / 2.0,
+                    y: (p0.y + p1.y) / 2.0,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mean_len = total_len / (midpoints.len().max(1) as f32);
+        let cell_size = (mean_len * Self::BUNDLE_CELL_SCALE).max(1.0);
+
+        let cell_of = |p: Point| -> (i64, i64) {
+            (
+                (p.x / cell_size).floor() as i64,
+                (p.y / cell_size).floor() as i64,
+            )
+        };
+
+        let mut cells: FxHashMap<(i64, i64), (Point, usize)> =
+            FxHashMap::default();
+
+        for &mid in midpoints.iter() {
+            let (sum, count) =
+                cells.entry(cell_of(mid)).or_insert((Point::ZERO, 0));
+            sum.x += mid.x;
+            sum.y += mid.y;
+            *count += 1;
+        }
+
+        let mut control_points = midpoints
+            .iter()
+            .map(|&mid| {
+                let (sum, count) = cells[&cell_of(mid)];
+                let mean_x = sum.x / count as f32;
+                let mean_y = sum.y / count as f32;
+
+                let t = Self::BUNDLE_STRENGTH;
+                [mid.x + (mean_x - mid.x) * t, mid.y + (mean_y - mid.y) * t]
+            })
+            .collect::<Vec<_>>();
+
+        // storage buffers can't be empty
+        if control_points.is_empty() {
+            control_points.push([0.0, 0.0]);
+        }
+
+        control_points
+    }
+
+    /// Blends the colors of the paths traversing each edge, weighted
+    /// by the number of times each path traverses it; edges that no
+    /// path traverses get a zero alpha, and are drawn in the edge
+    /// color.
+    fn path_edge_colors(
+        graph: &PackedGraph,
+        edge_indices: &FxHashMap<Edge, usize>,
+        path_colors: &PathColors,
+    ) -> Vec<[f32; 4]> {
+        let mut colors = vec![[0.0f32; 4]; edge_indices.len().max(1)];
+
+        for path in graph.path_ids() {
+            let color = graph
+                .get_path_name_vec(path)
+                .map(|name| path_colors.display_color(&name))
+                .unwrap_or(rgb::RGB::new(0.5, 0.5, 0.5));
+
+            let steps = match graph.path_steps(path) {
+                Some(steps) => steps,
+                None => continue,
+            };
+
+            let mut prev: Option<Handle> = None;
+
+            for step in steps {
+                let handle = step.handle();
+
+                if let Some(prev) = prev {
+                    let edge = Edge::edge_handle(prev, handle);
+
+                    if let Some(&ix) = edge_indices.get(&edge) {
+                        let sum = &mut colors[ix];
+                        sum[0] += color.r;
+                        sum[1] += color.g;
+                        sum[2] += color.b;
+                        sum[3] += 1.0;
+                    }
+                }
+
+                prev = Some(handle);
+            }
+        }
+
+        for color in colors.iter_mut() {
+            let count = color[3];
+            if count > 0.0 {
+                color[0] /= count;
+                color[1] /= count;
+                color[2] /= count;
+                color[3] = 1.0;
+            }
+        }
+
+        colors
+    }
+
     fn new_with_components(
         app: &GfaestusVk,
         graph: &PackedGraph,
@@ -586,6 +916,8 @@ impl EdgeIndices {
         let mut edge_count = 0;
         let mut edges: Vec<u32> = Vec::with_capacity(graph.edge_count() * 2);
 
+        let mut edge_indices: FxHashMap<Edge, usize> = FxHashMap::default();
+
         for Edge(left, right) in graph.edges() {
             let left_comp = layout.node_component(left.id());
             let right_comp = layout.node_component(right.id());
@@ -610,6 +942,8 @@ impl EdgeIndices {
 
             edges.push(left_ix as u32);
             edges.push(right_ix as u32);
+
+            edge_indices.insert(Edge::edge_handle(left, right), edge_count);
             edge_count += 1;
         }
 
@@ -624,14 +958,84 @@ impl EdgeIndices {
 
         app.set_debug_object_name(buffer, "Edge Indices Buffer")?;
 
+        let control_points = Self::bundled_control_points(layout, &edges);
+
+        let usage = vk::BufferUsageFlags::TRANSFER_DST
+            | vk::BufferUsageFlags::STORAGE_BUFFER;
+
+        let (control_buffer, control_allocation, control_allocation_info) =
+            app.create_buffer_with_data(
+                usage,
+                memory_usage,
+                false,
+                &control_points,
+            )?;
+
+        app.set_debug_object_name(
+            control_buffer,
+            "Edge Control Points Buffer",
+        )?;
+
+        // the path colors are only computed once the `ByPath` color
+        // mode is used
+        let edge_colors = [[0.0f32; 4]];
+
+        let (colors_buffer, colors_allocation, colors_allocation_info) = app
+            .create_buffer_with_data(usage, memory_usage, false, &edge_colors)?;
+
+        app.set_debug_object_name(colors_buffer, "Edge Colors Buffer")?;
+
         Ok(Self {
             buffer,
             allocation,
             allocation_info,
 
             edge_count,
+
+            control_points: control_buffer,
+            control_allocation,
+            control_allocation_info,
+
+            edge_colors: colors_buffer,
+            colors_allocation,
+            colors_allocation_info,
+
+            has_path_colors: false,
+
+            edge_indices,
         })
     }
+
+    fn update_path_colors(
+        &mut self,
+        app: &GfaestusVk,
+        graph: &PackedGraph,
+        path_colors: &PathColors,
+    ) -> Result<()> {
+        let edge_colors =
+            Self::path_edge_colors(graph, &self.edge_indices, path_colors);
+
+        let usage = vk::BufferUsageFlags::TRANSFER_DST
+            | vk::BufferUsageFlags::STORAGE_BUFFER;
+
+        let memory_usage = vk_mem::MemoryUsage::GpuOnly;
+
+        let (colors_buffer, colors_allocation, colors_allocation_info) = app
+            .create_buffer_with_data(usage, memory_usage, false, &edge_colors)?;
+
+        app.set_debug_object_name(colors_buffer, "Edge Colors Buffer")?;
+
+        app.allocator
+            .destroy_buffer(self.edge_colors, &self.colors_allocation);
+
+        self.edge_colors = colors_buffer;
+        self.colors_allocation = colors_allocation;
+        self.colors_allocation_info = colors_allocation_info;
+
+        self.has_path_colors = true;
+
+        Ok(())
+    }
 }
 
 pub struct PreprocessPushConstants {
@@ -765,7 +1169,13 @@ impl EdgesUBOBuffer {
                           0.0, 0.0, 0.0, tls[3],
                           0.0, 0.0, 0.0, tls[4]],
 
+            _padding: [0.0; 3],
+
             curve_offset: self.ubo.curve_offset,
+
+            bundling: if self.ubo.edge_bundling { 1.0 } else { 0.0 },
+
+            color_by_path: self.ubo.color_mode.shader_value(),
         };
 
         let ubos = [data];
@@ -802,7 +1212,58 @@ struct EdgesUBOData {
 
     tess_levels: [f32; 20],
 
+    // std140 arrays are padded to a multiple of 16 bytes
+    _padding: [f32; 3],
+
     curve_offset: f32,
+
+    bundling: f32,
+
+    color_by_path: f32,
+}
+
+/// How the edges are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EdgeColorMode {
+    /// All edges use the edge color
+    Single,
+    /// Each edge uses the blended colors of the paths traversing it
+    /// (see `PathColors`); edges not on any path use the edge color
+    ByPath,
+}
+
+impl std::default::Default for EdgeColorMode {
+    fn default() -> Self {
+        Self::Single
+    }
+}
+
+impl EdgeColorMode {
+    fn shader_value(&self) -> f32 {
+        match self {
+            EdgeColorMode::Single => 0.0,
+            EdgeColorMode::ByPath => 1.0,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeColorMode::Single => "single",
+            EdgeColorMode::ByPath => "by_path",
+        }
+    }
+}
+
+impl std::str::FromStr for EdgeColorMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "single" => Ok(EdgeColorMode::Single),
+            "by_path" => Ok(EdgeColorMode::ByPath),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -813,6 +1274,12 @@ pub struct EdgesUBO {
     pub tess_levels: [f32; 5],
 
     pub curve_offset: f32,
+
+    /// If true, edges are drawn as curves through their bundled
+    /// control points (see `EdgeIndices`)
+    pub edge_bundling: bool,
+
+    pub color_mode: EdgeColorMode,
 }
 
 impl std::default::Default for EdgesUBO {
@@ -824,13 +1291,17 @@ impl std::default::Default for EdgesUBO {
             tess_levels: [2.0, 3.0, 5.0, 8.0, 16.0],
 
             curve_offset: 0.2,
+
+            edge_bundling: false,
+
+            color_mode: EdgeColorMode::default(),
         }
     }
 }
 
 impl EdgesUBO {
-    pub fn bytes(&self) -> [u8; 116] {
-        let mut bytes = [0u8; 116];
+    pub fn bytes(&self) -> [u8; 124] {
+        let mut bytes = [0u8; 124];
 
         let mut offset = 0;
 
@@ -857,6 +1328,10 @@ impl EdgesUBO {
 
         add_float(self.curve_offset);
 
+        add_float(if self.edge_bundling { 1.0 } else { 0.0 });
+
+        add_float(self.color_mode.shader_value());
+
         bytes
     }
 }
@@ -1,7 +1,7 @@
 use ash::version::DeviceV1_0;
 use ash::{vk, Device};
 use handlegraph::handle::NodeId;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use std::ops::RangeInclusive;
 
@@ -9,6 +9,7 @@ use nalgebra_glm as glm;
 
 use anyhow::*;
 
+use crate::overlays::Palette;
 use crate::view::View;
 use crate::vulkan::context::NodeRendererType;
 use crate::vulkan::GfaestusVk;
@@ -16,21 +17,51 @@ use crate::{geometry::Point, vulkan::texture::GradientTexture};
 
 use crate::vulkan::render_pass::Framebuffers;
 
+pub mod background;
 pub mod base;
 pub mod overlay;
+pub mod ribbons;
 pub mod vertices;
 
+pub use background::*;
 pub use base::*;
 pub use overlay::*;
+pub use ribbons::*;
 pub use vertices::*;
 
+/// A graph drawn on top of the main graph, e.g. to compare two
+/// assemblies. Its nodes are drawn in a single color, translated by
+/// `offset`, and are given IDs in the node ID buffer following those
+/// of the main graph.
+pub struct OverlayGraph {
+    pub name: String,
+    pub vertices: NodeVertices,
+    pub node_count: usize,
+    /// Added to the node IDs of this graph in the node ID buffer
+    pub id_offset: u32,
+
+    pub offset: Point,
+    pub color: rgb::RGB<f32>,
+    pub visible: bool,
+}
+
+impl OverlayGraph {
+    pub fn destroy(&mut self, app: &GfaestusVk) {
+        self.vertices.destroy(app).unwrap();
+    }
+}
+
 pub struct NodePipelines {
     pub pipelines: OverlayPipelines,
 
+    background: BackgroundPipeline,
+
     selection_descriptors: SelectionDescriptors,
 
     pub vertices: NodeVertices,
 
+    ribbons: PathRibbons,
+
     device: Device,
 
     renderer_type: NodeRendererType,
@@ -47,8 +78,14 @@ impl NodePipelines {
 
         let vertices = NodeVertices::new(renderer_type);
 
-        let selection_descriptors =
-            SelectionDescriptors::new(app, selection_buffer, 1)?;
+        let ribbons = PathRibbons::empty(app)?;
+
+        let selection_descriptors = SelectionDescriptors::new(
+            app,
+            selection_buffer,
+            ribbons.buffer(),
+            1,
+        )?;
 
         let pipelines = OverlayPipelines::new(
             app,
@@ -56,11 +93,16 @@ impl NodePipelines {
             selection_descriptors.layout,
         )?;
 
+        let background = BackgroundPipeline::new(app)?;
+
         Ok(Self {
             pipelines,
+            background,
             vertices,
             selection_descriptors,
 
+            ribbons,
+
             device: device.clone(),
 
             renderer_type,
@@ -71,10 +113,61 @@ impl NodePipelines {
         &self.device
     }
 
+    /// Recreates the node and background pipelines against the
+    /// current node render pass, e.g. after the MSAA sample count has
+    /// changed. The overlays and descriptor sets are kept.
+    ///
+    /// The GPU must be idle when this is called.
+    pub fn recreate_pipelines(&mut self, app: &GfaestusVk) -> Result<()> {
+        self.pipelines.recreate_pipelines(
+            app,
+            self.renderer_type,
+            self.selection_descriptors.layout,
+        )?;
+
+        self.background.destroy();
+        self.background = BackgroundPipeline::new(app)?;
+
+        Ok(())
+    }
+
     pub fn has_overlay(&self) -> bool {
         self.pipelines.overlay_set_id.is_some()
     }
 
+    /// True if the path ribbons have been computed, see
+    /// `set_path_ribbons`
+    pub fn has_path_ribbons(&self) -> bool {
+        self.ribbons.has_ribbons()
+    }
+
+    /// Replaces the path ribbon buffer with the placeholder, so that
+    /// the ribbons are recomputed the next time they're drawn.
+    ///
+    /// The GPU must be idle when this is called.
+    pub fn clear_path_ribbons(&mut self, app: &GfaestusVk) -> Result<()> {
+        let ribbons = PathRibbons::empty(app)?;
+        self.set_path_ribbons(app, ribbons)
+    }
+
+    /// Replaces the path ribbon buffer, and points the node
+    /// descriptor set to the new one.
+    ///
+    /// The GPU must be idle when this is called.
+    pub fn set_path_ribbons(
+        &mut self,
+        app: &GfaestusVk,
+        ribbons: PathRibbons,
+    ) -> Result<()> {
+        self.ribbons.destroy(app);
+        self.ribbons = ribbons;
+
+        self.selection_descriptors
+            .write_ribbons(&self.device, self.ribbons.buffer());
+
+        Ok(())
+    }
+
     pub fn draw(
         &mut self,
         cmd_buf: vk::CommandBuffer,
@@ -84,13 +177,35 @@ impl NodePipelines {
         node_width: f32,
         view: View,
         offset: Point,
-        background_color: rgb::RGB<f32>,
+        background_color: rgb::RGBA<f32>,
+        background_gradient: Option<(rgb::RGB<f32>, rgb::RGB<f32>)>,
+        palette: Palette,
+        outline_width: f32,
+        outline_color: rgb::RGB<f32>,
+        node_caps: NodeCaps,
+        lod_min_pixels: f32,
+        path_ribbons: bool,
         overlay_id: usize,
+        base_overlay_id: Option<usize>,
         color_scheme: &GradientTexture,
+        overlay_graphs: &[OverlayGraph],
     ) -> Result<()> {
-        self.pipelines.write_overlay(overlay_id, color_scheme)?;
+        // the base overlay is drawn first, with the active overlay
+        // blended on top of it
+        let base_overlay_id = base_overlay_id.filter(|&id| {
+            id != overlay_id && self.pipelines.overlay(id).is_some()
+        });
+
+        let mut layers = Vec::with_capacity(2);
+
+        if let Some(base_id) = base_overlay_id {
+            layers.push((base_id, OverlayLayer::Base));
+        }
+        layers.push((overlay_id, OverlayLayer::Active));
 
-        let overlay = self.pipelines.overlays.get(&overlay_id).unwrap();
+        for &(id, layer) in layers.iter() {
+            self.pipelines.write_overlay(id, layer, color_scheme)?;
+        }
 
         let device = &self.pipelines.device;
 
@@ -99,7 +214,7 @@ impl NodePipelines {
             [
                 vk::ClearValue {
                     color: vk::ClearColorValue {
-                        float32: [bg.r, bg.g, bg.b, 1.0],
+                        float32: [bg.r, bg.g, bg.b, bg.a],
                     },
                 },
                 vk::ClearValue {
@@ -138,35 +253,11 @@ impl NodePipelines {
             )
         };
 
-        self.pipelines.bind_pipeline(device, cmd_buf, overlay.kind);
-
-        let vx_bufs = [self.vertices.vertex_buffer];
-        let offsets = [0];
-
-        unsafe {
-            device.cmd_bind_vertex_buffers(cmd_buf, 0, &vx_bufs, &offsets);
+        if let Some((top, bottom)) = background_gradient {
+            self.background.draw(cmd_buf, viewport_dims, top, bottom);
         }
 
-        self.pipelines.bind_descriptor_sets(
-            device,
-            cmd_buf,
-            overlay_id,
-            self.selection_descriptors.descriptor_set,
-        )?;
-
-        let push_constants = NodePushConstants::new(
-            [offset.x, offset.y],
-            viewport_dims,
-            view,
-            node_width,
-            7,
-        );
-
-        let pc_bytes = push_constants.bytes();
-
-        let layout = self.pipelines.pipeline_layout_kind(overlay.kind);
-
-        unsafe {
+        let pc_stages = {
             use vk::ShaderStageFlags as Flags;
 
             let mut stages = Flags::VERTEX | Flags::FRAGMENT;
@@ -176,13 +267,115 @@ impl NodePipelines {
                     | Flags::TESSELLATION_EVALUATION;
             }
 
-            device.cmd_push_constants(cmd_buf, layout, stages, 0, &pc_bytes)
+            stages
         };
 
-        unsafe {
-            device.cmd_draw(cmd_buf, self.vertices.vertex_count as u32, 1, 0, 0)
+        // ribbons are only drawn once they've been computed
+        let path_ribbons = path_ribbons && self.ribbons.has_ribbons();
+
+        let node_push_constants = |offset: Point| {
+            let mut push_constants = NodePushConstants::new(
+                [offset.x, offset.y],
+                viewport_dims,
+                view,
+                node_width,
+                7,
+            );
+
+            push_constants.palette = palette;
+            push_constants.outline_width = outline_width;
+            push_constants.outline_color = outline_color;
+            push_constants.node_caps = node_caps;
+            push_constants.lod_min_pixels = lod_min_pixels;
+            push_constants.path_ribbons = path_ribbons;
+
+            push_constants
         };
 
+        for &(id, layer) in layers.iter() {
+            let overlay = self.pipelines.overlays.get(&id).unwrap();
+
+            self.pipelines.bind_pipeline(device, cmd_buf, overlay.kind);
+
+            let vx_bufs = [self.vertices.vertex_buffer];
+            let offsets = [0];
+
+            unsafe {
+                device.cmd_bind_vertex_buffers(cmd_buf, 0, &vx_bufs, &offsets);
+            }
+
+            self.pipelines.bind_descriptor_sets(
+                device,
+                cmd_buf,
+                id,
+                layer,
+                self.selection_descriptors.descriptor_set,
+            )?;
+
+            let pc_bytes = node_push_constants(offset).bytes();
+
+            let layout = self.pipelines.pipeline_layout_kind(overlay.kind);
+
+            unsafe {
+                device.cmd_push_constants(
+                    cmd_buf, layout, pc_stages, 0, &pc_bytes,
+                );
+            };
+
+            unsafe {
+                device.cmd_draw(
+                    cmd_buf,
+                    self.vertices.vertex_count as u32,
+                    1,
+                    0,
+                    0,
+                )
+            };
+        }
+
+        let active_kind =
+            self.pipelines.overlays.get(&overlay_id).unwrap().kind;
+        let layout = self.pipelines.pipeline_layout_kind(active_kind);
+
+        for graph in overlay_graphs {
+            if !graph.visible || !graph.vertices.has_vertices() {
+                continue;
+            }
+
+            let c = graph.color;
+            let tint = u32::from_le_bytes([
+                (c.r * 255.0) as u8,
+                (c.g * 255.0) as u8,
+                (c.b * 255.0) as u8,
+                255,
+            ]);
+
+            let mut push_constants = node_push_constants(offset + graph.offset);
+            push_constants.id_offset = graph.id_offset;
+            push_constants.tint = tint;
+
+            unsafe {
+                let vx_bufs = [graph.vertices.vertex_buffer];
+                device.cmd_bind_vertex_buffers(cmd_buf, 0, &vx_bufs, &[0]);
+
+                device.cmd_push_constants(
+                    cmd_buf,
+                    layout,
+                    pc_stages,
+                    0,
+                    &push_constants.bytes(),
+                );
+
+                device.cmd_draw(
+                    cmd_buf,
+                    graph.vertices.vertex_count as u32,
+                    1,
+                    0,
+                    0,
+                );
+            }
+        }
+
         // End render pass
         unsafe { device.cmd_end_render_pass(cmd_buf) };
 
@@ -201,11 +394,15 @@ impl NodePipelines {
                 .destroy_descriptor_pool(self.selection_descriptors.pool, None);
         }
 
+        self.background.destroy();
         self.vertices.destroy(app).unwrap();
+        self.ribbons.destroy(app);
         self.pipelines.destroy(&app.allocator).unwrap();
     }
 }
 
+/// The descriptor set shared by all node pipelines, holding the node
+/// selection flags, and the path ribbons
 pub struct SelectionDescriptors {
     pool: vk::DescriptorPool,
     layout: vk::DescriptorSetLayout,
@@ -219,6 +416,7 @@ impl SelectionDescriptors {
     fn new(
         app: &GfaestusVk,
         buffer: vk::Buffer,
+        ribbons_buffer: vk::Buffer,
         image_count: u32,
         // msaa_samples: vk::SampleCountFlags,
     ) -> Result<Self> {
@@ -230,7 +428,7 @@ impl SelectionDescriptors {
         let descriptor_pool = {
             let pool_size = vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::STORAGE_BUFFER,
-                descriptor_count: image_count,
+                descriptor_count: 2 * image_count,
             };
 
             let pool_sizes = [pool_size];
@@ -276,32 +474,66 @@ impl SelectionDescriptors {
             unsafe { device.update_descriptor_sets(&descriptor_writes, &[]) }
         }
 
-        Ok(Self {
+        let descriptors = Self {
             pool: descriptor_pool,
             layout,
             // TODO should be one per swapchain image
             descriptor_set: descriptor_sets[0],
             // should not be owned by this, but MainView
             // buffer,
-        })
+        };
+
+        descriptors.write_ribbons(device, ribbons_buffer);
+
+        Ok(descriptors)
     }
 
-    fn layout_binding() -> vk::DescriptorSetLayoutBinding {
+    fn write_ribbons(&self, device: &Device, ribbons_buffer: vk::Buffer) {
+        let buf_info = vk::DescriptorBufferInfo::builder()
+            .buffer(ribbons_buffer)
+            .offset(0)
+            .range(vk::WHOLE_SIZE)
+            .build();
+
+        let buf_infos = [buf_info];
+
+        let descriptor_write = vk::WriteDescriptorSet::builder()
+            .dst_set(self.descriptor_set)
+            .dst_binding(1)
+            .dst_array_element(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .buffer_info(&buf_infos)
+            .build();
+
+        let descriptor_writes = [descriptor_write];
+
+        unsafe { device.update_descriptor_sets(&descriptor_writes, &[]) }
+    }
+
+    fn layout_bindings() -> [vk::DescriptorSetLayoutBinding; 2] {
         use vk::ShaderStageFlags as Stages;
 
-        vk::DescriptorSetLayoutBinding::builder()
+        let selection = vk::DescriptorSetLayoutBinding::builder()
             .binding(0)
             .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
             .descriptor_count(1)
             .stage_flags(Stages::FRAGMENT)
-            .build()
+            .build();
+
+        let ribbons = vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(Stages::FRAGMENT)
+            .build();
+
+        [selection, ribbons]
     }
 
     fn create_descriptor_set_layout(
         device: &Device,
     ) -> Result<vk::DescriptorSetLayout> {
-        let binding = Self::layout_binding();
-        let bindings = [binding];
+        let bindings = Self::layout_bindings();
 
         let layout_info = vk::DescriptorSetLayoutCreateInfo::builder()
             .bindings(&bindings)
@@ -325,6 +557,11 @@ pub struct NodeIdBuffer {
 }
 
 impl NodeIdBuffer {
+    /// The size of the buffer in bytes
+    pub fn size(&self) -> usize {
+        self.size as usize
+    }
+
     pub fn read_rect(
         &self,
         device: &Device,
@@ -373,6 +610,62 @@ impl NodeIdBuffer {
         values
     }
 
+    /// Reads the `size`x`size` block of node IDs centered on the
+    /// given point, and returns the most common non-zero ID, if any
+    pub fn read_majority(
+        &self,
+        device: &Device,
+        x: u32,
+        y: u32,
+        size: u32,
+    ) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let radius = size / 2;
+
+        let min_x = x.saturating_sub(radius);
+        let max_x = (x + radius).min(self.width - 1);
+
+        let min_y = y.saturating_sub(radius);
+        let max_y = (y + radius).min(self.height - 1);
+
+        let mut counts: FxHashMap<u32, usize> = FxHashMap::default();
+
+        unsafe {
+            let data_ptr = device
+                .map_memory(
+                    self.memory,
+                    0,
+                    self.size,
+                    vk::MemoryMapFlags::empty(),
+                )
+                .unwrap();
+
+            for y in min_y..=max_y {
+                let row_start = ((y * self.width) + min_x) as usize;
+                let val_ptr = (data_ptr as *const u32).add(row_start);
+
+                let row_width = (max_x - min_x + 1) as usize;
+                let slice = std::slice::from_raw_parts(val_ptr, row_width);
+
+                for &id in slice.iter().filter(|&&id| id != 0) {
+                    *counts.entry(id).or_default() += 1;
+                }
+            }
+
+            device.unmap_memory(self.memory);
+        }
+
+        // ties are broken by the lowest ID, so that the result
+        // doesn't depend on the hash map's iteration order
+        counts
+            .into_iter()
+            .max_by(|(a_id, a), (b_id, b)| a.cmp(b).then(b_id.cmp(a_id)))
+            .map(|(id, _)| id)
+    }
+
     pub fn read(&self, device: &Device, x: u32, y: u32) -> Option<u32> {
         if x >= self.width || y >= self.height {
             return None;
@@ -522,15 +815,80 @@ impl NodeIdBuffer {
     }
 }
 
+/// The shape of the ends of each node.
+///
+/// With `Round`, nodes are drawn as capsules, with semicircular ends
+/// whose diameter is the node width (see `shaders/nodes/outline.glsl`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeCaps {
+    Square,
+    Round,
+}
+
+impl std::default::Default for NodeCaps {
+    fn default() -> Self {
+        Self::Square
+    }
+}
+
+impl NodeCaps {
+    /// The value passed to the node shaders in the push constants
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            NodeCaps::Square => 0,
+            NodeCaps::Round => 1,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeCaps::Square => "square",
+            NodeCaps::Round => "round",
+        }
+    }
+}
+
+impl std::str::FromStr for NodeCaps {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "square" => Ok(NodeCaps::Square),
+            "round" => Ok(NodeCaps::Round),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The push constants of the node pipelines; the edge pipelines use
+/// the same range, but only read the view fields
+#[derive(Clone)]
 pub struct NodePushConstants {
     view_transform: glm::Mat4,
     node_width: f32,
     scale: f32,
     viewport_dims: [f32; 2],
     texture_period: u32,
+
+    pub palette: Palette,
+    pub outline_width: f32,
+    pub outline_color: rgb::RGB<f32>,
+    pub node_caps: NodeCaps,
+    /// The level of detail threshold, used by the tessellation
+    /// control shader
+    pub lod_min_pixels: f32,
+    /// The node ID offset of an overlaid graph
+    pub id_offset: u32,
+    /// The packed color of an overlaid graph, or zero for the main
+    /// graph
+    pub tint: u32,
+    pub path_ribbons: bool,
 }
 
 impl NodePushConstants {
+    /// The size of the push constant range, in bytes
+    pub const PC_RANGE: u32 = 124;
+
     #[inline]
     pub fn new(
         offset: [f32; 2],
@@ -561,48 +919,61 @@ impl NodePushConstants {
             viewport_dims,
             scale: view.scale,
             texture_period,
+
+            palette: Palette::default(),
+            outline_width: 0.0,
+            outline_color: rgb::RGB::new(0.0, 0.0, 0.0),
+            node_caps: NodeCaps::default(),
+            lod_min_pixels: 0.0,
+            id_offset: 0,
+            tint: 0,
+            path_ribbons: false,
         }
     }
 
     #[inline]
-    pub fn bytes(&self) -> [u8; 84] {
+    pub fn bytes(&self) -> [u8; Self::PC_RANGE as usize] {
         use crate::view;
 
-        let mut bytes = [0u8; 84];
+        let mut bytes = [0u8; Self::PC_RANGE as usize];
 
         let view_transform_array = view::mat4_to_array(&self.view_transform);
 
         {
             let mut offset = 0;
 
-            let mut add_float = |f: f32| {
-                let f_bytes = f.to_ne_bytes();
-                for i in 0..4 {
-                    bytes[offset] = f_bytes[i];
-                    offset += 1;
-                }
+            let mut add_bytes = |b: [u8; 4]| {
+                bytes[offset..offset + 4].copy_from_slice(&b);
+                offset += 4;
             };
 
-            for i in 0..4 {
-                let row = view_transform_array[i];
-                for j in 0..4 {
-                    let val = row[j];
-                    add_float(val);
+            for row in view_transform_array.iter() {
+                for val in row.iter() {
+                    add_bytes(val.to_ne_bytes());
                 }
             }
 
-            add_float(self.node_width);
-            add_float(self.scale);
+            add_bytes(self.node_width.to_ne_bytes());
+            add_bytes(self.scale.to_ne_bytes());
 
-            add_float(self.viewport_dims[0]);
-            add_float(self.viewport_dims[1]);
-        }
+            add_bytes(self.viewport_dims[0].to_ne_bytes());
+            add_bytes(self.viewport_dims[1].to_ne_bytes());
+
+            add_bytes(self.texture_period.to_ne_bytes());
+
+            add_bytes(self.palette.shader_index().to_ne_bytes());
+
+            add_bytes(self.outline_width.to_ne_bytes());
+            add_bytes(self.outline_color.r.to_ne_bytes());
+            add_bytes(self.outline_color.g.to_ne_bytes());
+            add_bytes(self.outline_color.b.to_ne_bytes());
+
+            add_bytes(self.node_caps.shader_index().to_ne_bytes());
+            add_bytes(self.lod_min_pixels.to_ne_bytes());
 
-        let u_bytes = self.texture_period.to_ne_bytes();
-        let mut offset = 80;
-        for i in 0..4 {
-            bytes[offset] = u_bytes[i];
-            offset += 1;
+            add_bytes(self.id_offset.to_ne_bytes());
+            add_bytes(self.tint.to_ne_bytes());
+            add_bytes((self.path_ribbons as u32).to_ne_bytes());
         }
 
         bytes
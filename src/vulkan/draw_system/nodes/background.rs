@@ -0,0 +1,269 @@
+use ash::version::DeviceV1_0;
+use ash::{vk, Device};
+
+use std::ffi::CString;
+
+use anyhow::*;
+
+use super::super::create_shader_module;
+use crate::vulkan::GfaestusVk;
+
+/// Draws a vertical gradient covering the entire node render pass,
+/// using the same fullscreen triangle as the post-processing
+/// pipelines.
+///
+/// Only writes to the color attachment; the node ID and selection
+/// mask attachments keep their clear values.
+pub struct BackgroundPipeline {
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+
+    device: Device,
+}
+
+impl BackgroundPipeline {
+    pub const PC_RANGE: u32 = (std::mem::size_of::<f32>() * 10) as u32;
+
+    pub fn new(app: &GfaestusVk) -> Result<Self> {
+        let device = app.vk_context().device();
+
+        let (pipeline, pipeline_layout) = create_background_pipeline(
+            device,
+            app.render_passes.nodes,
+            app.msaa_samples,
+        );
+
+        Ok(Self {
+            pipeline_layout,
+            pipeline,
+
+            device: device.clone(),
+        })
+    }
+
+    /// Must be called inside the node render pass, before any nodes
+    /// are drawn
+    pub fn draw(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        viewport_dims: [f32; 2],
+        top: rgb::RGB<f32>,
+        bottom: rgb::RGB<f32>,
+    ) {
+        let device = &self.device;
+
+        unsafe {
+            device.cmd_bind_pipeline(
+                cmd_buf,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline,
+            )
+        };
+
+        let pc_bytes = Self::push_constant_bytes(viewport_dims, top, bottom);
+
+        unsafe {
+            use vk::ShaderStageFlags as Flags;
+            device.cmd_push_constants(
+                cmd_buf,
+                self.pipeline_layout,
+                Flags::VERTEX | Flags::FRAGMENT,
+                0,
+                &pc_bytes,
+            )
+        };
+
+        unsafe { device.cmd_draw(cmd_buf, 3u32, 1, 0, 0) };
+    }
+
+    pub fn destroy(&self) {
+        unsafe {
+            self.device.destroy_pipeline(self.pipeline, None);
+            self.device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+        }
+    }
+
+    fn push_constant_bytes(
+        viewport_dims: [f32; 2],
+        top: rgb::RGB<f32>,
+        bottom: rgb::RGB<f32>,
+    ) -> [u8; 40] {
+        let mut bytes = [0u8; Self::PC_RANGE as usize];
+
+        let values = [
+            top.r,
+            top.g,
+            top.b,
+            1.0,
+            bottom.r,
+            bottom.g,
+            bottom.b,
+            1.0,
+            viewport_dims[0],
+            viewport_dims[1],
+        ];
+
+        for (ix, val) in values.iter().enumerate() {
+            let offset = ix * 4;
+            bytes[offset..offset + 4].copy_from_slice(&val.to_ne_bytes());
+        }
+
+        bytes
+    }
+}
+
+fn create_background_pipeline(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    msaa_samples: vk::SampleCountFlags,
+) -> (vk::Pipeline, vk::PipelineLayout) {
+    let vert_src = crate::load_shader!("post/post.vert.spv");
+    let frag_src = crate::load_shader!("nodes/background.frag.spv");
+
+    let vert_module = create_shader_module(device, &vert_src);
+    let frag_module = create_shader_module(device, &frag_src);
+
+    let entry_point = CString::new("main").unwrap();
+
+    let vert_state_info = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::VERTEX)
+        .module(vert_module)
+        .name(&entry_point)
+        .build();
+
+    let frag_state_info = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::FRAGMENT)
+        .module(frag_module)
+        .name(&entry_point)
+        .build();
+
+    let shader_state_infos = [vert_state_info, frag_state_info];
+
+    let vert_input_info =
+        vk::PipelineVertexInputStateCreateInfo::builder().build();
+
+    let input_assembly_info =
+        vk::PipelineInputAssemblyStateCreateInfo::builder()
+            .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .primitive_restart_enable(false)
+            .build();
+
+    let viewport_info = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1)
+        .build();
+
+    let dynamic_states = {
+        use vk::DynamicState as DS;
+        [DS::VIEWPORT, DS::SCISSOR]
+    };
+
+    let dynamic_state_info = vk::PipelineDynamicStateCreateInfo::builder()
+        .dynamic_states(&dynamic_states)
+        .build();
+
+    let rasterizer_info = vk::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .depth_bias_enable(false)
+        .depth_bias_constant_factor(0.0)
+        .depth_bias_clamp(0.0)
+        .depth_bias_slope_factor(0.0)
+        .build();
+
+    let multisampling_info = vk::PipelineMultisampleStateCreateInfo::builder()
+        .sample_shading_enable(false)
+        .rasterization_samples(msaa_samples)
+        .min_sample_shading(1.0)
+        .alpha_to_coverage_enable(false)
+        .alpha_to_one_enable(false)
+        .build();
+
+    let color_blend_attachment =
+        vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::all())
+            .blend_enable(false)
+            .build();
+
+    // the node ID and selection mask attachments are left untouched
+    let id_color_blend_attachment =
+        vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::empty())
+            .blend_enable(false)
+            .build();
+
+    let mask_color_blend_attachment =
+        vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::empty())
+            .blend_enable(false)
+            .build();
+
+    let color_blend_attachments = [
+        color_blend_attachment,
+        id_color_blend_attachment,
+        mask_color_blend_attachment,
+    ];
+
+    let color_blending_info = vk::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .logic_op(vk::LogicOp::COPY)
+        .attachments(&color_blend_attachments)
+        .blend_constants([0.0, 0.0, 0.0, 0.0])
+        .build();
+
+    let layout = {
+        use vk::ShaderStageFlags as Flags;
+
+        let pc_range = vk::PushConstantRange::builder()
+            .stage_flags(Flags::VERTEX | Flags::FRAGMENT)
+            .offset(0)
+            .size(BackgroundPipeline::PC_RANGE)
+            .build();
+
+        let pc_ranges = [pc_range];
+
+        let layout_info = vk::PipelineLayoutCreateInfo::builder()
+            .push_constant_ranges(&pc_ranges)
+            .build();
+
+        unsafe { device.create_pipeline_layout(&layout_info, None).unwrap() }
+    };
+
+    let pipeline_info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(&shader_state_infos)
+        .vertex_input_state(&vert_input_info)
+        .input_assembly_state(&input_assembly_info)
+        .viewport_state(&viewport_info)
+        .dynamic_state(&dynamic_state_info)
+        .rasterization_state(&rasterizer_info)
+        .multisample_state(&multisampling_info)
+        .color_blend_state(&color_blending_info)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0)
+        .build();
+
+    let pipeline_infos = [pipeline_info];
+
+    let pipeline = unsafe {
+        device
+            .create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                &pipeline_infos,
+                None,
+            )
+            .unwrap()[0]
+    };
+
+    unsafe {
+        device.destroy_shader_module(vert_module, None);
+        device.destroy_shader_module(frag_module, None);
+    }
+
+    (pipeline, layout)
+}
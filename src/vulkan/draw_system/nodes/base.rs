@@ -7,6 +7,7 @@ use std::ffi::CString;
 use anyhow::*;
 
 use super::super::{create_shader_module, Vertex};
+use super::NodePushConstants;
 use crate::vulkan::context::NodeRendererType;
 use crate::vulkan::GfaestusVk;
 
@@ -224,7 +225,7 @@ pub(crate) fn create_node_pipeline(
         let pc_range = vk::PushConstantRange::builder()
             .stage_flags(stage_flags)
             .offset(0)
-            .size(84)
+            .size(NodePushConstants::PC_RANGE)
             .build();
 
         let pc_ranges = [pc_range];
@@ -10,6 +10,15 @@ use crate::{overlays::OverlayKind, vulkan::GfaestusVk};
 
 use super::NodePipelineConfig;
 
+/// Which of the two overlays drawn each frame a descriptor set is
+/// for; the base overlay, if any, is drawn first, with the active
+/// overlay blended on top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayLayer {
+    Base,
+    Active,
+}
+
 pub struct OverlayPipelines {
     pipeline_rgb: OverlayPipelineRGB,
     pipeline_value: OverlayPipelineValue,
@@ -51,6 +60,25 @@ impl OverlayPipelines {
         })
     }
 
+    pub(super) fn recreate_pipelines(
+        &mut self,
+        app: &GfaestusVk,
+        renderer_type: NodeRendererType,
+        selection_set_layout: vk::DescriptorSetLayout,
+    ) -> Result<()> {
+        self.pipeline_rgb.recreate_pipeline(
+            app,
+            renderer_type,
+            selection_set_layout,
+        )?;
+        self.pipeline_value.recreate_pipeline(
+            app,
+            renderer_type,
+            selection_set_layout,
+        )?;
+        Ok(())
+    }
+
     pub fn destroy(&self, allocator: &vk_mem::Allocator) -> Result<()> {
         self.pipeline_rgb.destroy();
         self.pipeline_value.destroy();
@@ -95,6 +123,7 @@ impl OverlayPipelines {
     pub(super) fn write_overlay(
         &mut self,
         overlay_id: usize,
+        layer: OverlayLayer,
         color_scheme: &GradientTexture,
     ) -> Result<()> {
         let overlay = self.overlays.get(&overlay_id).ok_or(anyhow!(
@@ -104,15 +133,20 @@ impl OverlayPipelines {
 
         match overlay.kind {
             OverlayKind::RGB => {
-                self.pipeline_rgb.write_active_overlay(overlay)?;
+                self.pipeline_rgb.write_active_overlay(overlay, layer)?;
             }
             OverlayKind::Value => {
-                self.pipeline_value
-                    .write_active_overlay(color_scheme, overlay)?;
+                self.pipeline_value.write_active_overlay(
+                    color_scheme,
+                    overlay,
+                    layer,
+                )?;
             }
         }
 
-        self.overlay_set_id = Some(overlay_id);
+        if layer == OverlayLayer::Active {
+            self.overlay_set_id = Some(overlay_id);
+        }
 
         Ok(())
     }
@@ -122,6 +156,7 @@ impl OverlayPipelines {
         device: &Device,
         cmd_buf: vk::CommandBuffer,
         overlay_id: usize,
+        layer: OverlayLayer,
         // overlay: (usize, OverlayKind),
         selection_descriptor: vk::DescriptorSet,
     ) -> Result<()> {
@@ -130,14 +165,18 @@ impl OverlayPipelines {
         unsafe {
             let (desc_sets, layout) = match overlay.kind {
                 OverlayKind::RGB => {
-                    let sets =
-                        [self.pipeline_rgb.overlay_set, selection_descriptor];
+                    let sets = [
+                        self.pipeline_rgb.layer_set(layer),
+                        selection_descriptor,
+                    ];
                     let layout = self.pipeline_rgb.pipeline_layout;
                     (sets, layout)
                 }
                 OverlayKind::Value => {
-                    let sets =
-                        [self.pipeline_value.overlay_set, selection_descriptor];
+                    let sets = [
+                        self.pipeline_value.layer_set(layer),
+                        selection_descriptor,
+                    ];
                     let layout = self.pipeline_value.pipeline_layout;
                     (sets, layout)
                 }
@@ -157,6 +196,31 @@ impl OverlayPipelines {
         Ok(())
     }
 
+    pub fn overlay(&self, overlay_id: usize) -> Option<&Overlay> {
+        self.overlays.get(&overlay_id)
+    }
+
+    /// Returns the overlay with the given name and kind, and its ID,
+    /// if it exists
+    pub fn overlay_by_name_mut(
+        &mut self,
+        name: &str,
+        kind: OverlayKind,
+    ) -> Option<(usize, &mut Overlay)> {
+        self.overlays
+            .iter_mut()
+            .find(|(_, overlay)| overlay.name == name && overlay.kind == kind)
+            .map(|(id, overlay)| (*id, overlay))
+    }
+
+    /// The total size of the overlay buffer allocations in bytes
+    pub fn overlay_buffer_size(&self) -> usize {
+        self.overlays
+            .values()
+            .map(|overlay| overlay.alloc_info.get_size())
+            .sum()
+    }
+
     pub fn overlay_names(&self) -> Vec<(usize, OverlayKind, &str)> {
         let mut overlays = Vec::with_capacity(self.overlays.len());
 
@@ -190,6 +254,7 @@ pub struct OverlayPipelineRGB {
     pub(super) descriptor_set_layout: vk::DescriptorSetLayout,
 
     pub(super) overlay_set: vk::DescriptorSet,
+    pub(super) base_overlay_set: vk::DescriptorSet,
 
     pub(super) pipeline_layout: vk::PipelineLayout,
     pub(super) pipeline: vk::Pipeline,
@@ -204,6 +269,7 @@ pub struct OverlayPipelineValue {
     sampler: vk::Sampler,
 
     pub(super) overlay_set: vk::DescriptorSet,
+    pub(super) base_overlay_set: vk::DescriptorSet,
 
     pub(super) pipeline_layout: vk::PipelineLayout,
     pub(super) pipeline: vk::Pipeline,
@@ -212,16 +278,24 @@ pub struct OverlayPipelineValue {
 }
 
 impl OverlayPipelineValue {
+    fn layer_set(&self, layer: OverlayLayer) -> vk::DescriptorSet {
+        match layer {
+            OverlayLayer::Base => self.base_overlay_set,
+            OverlayLayer::Active => self.overlay_set,
+        }
+    }
+
     fn write_active_overlay(
         &mut self,
         color_scheme: &GradientTexture,
         overlay: &Overlay,
+        layer: OverlayLayer,
     ) -> Result<()> {
         overlay.write_value_descriptor_set(
             &self.device,
             color_scheme,
             self.sampler,
-            &self.overlay_set,
+            &self.layer_set(layer),
         )?;
 
         Ok(())
@@ -296,31 +370,32 @@ impl OverlayPipelineValue {
             selection_set_layout,
         )?;
 
-        let image_count = 1;
+        // one set for the active overlay, and one for the base overlay
+        let set_count = 2;
 
         let descriptor_pool = {
             let sampler_size = vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
-                descriptor_count: image_count,
+                descriptor_count: set_count,
             };
 
             let value_size = vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::STORAGE_BUFFER,
-                descriptor_count: image_count,
+                descriptor_count: set_count,
             };
 
             let pool_sizes = [sampler_size, value_size];
 
             let pool_info = vk::DescriptorPoolCreateInfo::builder()
                 .pool_sizes(&pool_sizes)
-                .max_sets(image_count)
+                .max_sets(set_count)
                 .build();
 
             unsafe { device.create_descriptor_pool(&pool_info, None) }
         }?;
 
         let descriptor_sets = {
-            let layouts = vec![desc_set_layout];
+            let layouts = vec![desc_set_layout; set_count as usize];
 
             let alloc_info = vk::DescriptorSetAllocateInfo::builder()
                 .descriptor_pool(descriptor_pool)
@@ -349,6 +424,7 @@ impl OverlayPipelineValue {
             descriptor_set_layout: desc_set_layout,
 
             overlay_set: descriptor_sets[0],
+            base_overlay_set: descriptor_sets[1],
 
             sampler,
 
@@ -360,6 +436,33 @@ impl OverlayPipelineValue {
         })
     }
 
+    fn recreate_pipeline(
+        &mut self,
+        app: &GfaestusVk,
+        renderer_type: NodeRendererType,
+        selection_set_layout: vk::DescriptorSetLayout,
+    ) -> Result<()> {
+        unsafe {
+            self.device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            self.device.destroy_pipeline(self.pipeline, None);
+        }
+
+        let (pipeline, pipeline_layout) = Self::create_pipeline(
+            app,
+            renderer_type,
+            self.descriptor_set_layout,
+            selection_set_layout,
+        )?;
+
+        app.set_debug_object_name(pipeline, "Node Overlay Value Pipeline")?;
+
+        self.pipeline = pipeline;
+        self.pipeline_layout = pipeline_layout;
+
+        Ok(())
+    }
+
     pub fn destroy(&self) {
         unsafe {
             self.device.destroy_descriptor_set_layout(
@@ -378,8 +481,20 @@ impl OverlayPipelineValue {
 }
 
 impl OverlayPipelineRGB {
-    fn write_active_overlay(&mut self, overlay: &Overlay) -> Result<()> {
-        overlay.write_rgb_descriptor_set(&self.device, &self.overlay_set)?;
+    fn layer_set(&self, layer: OverlayLayer) -> vk::DescriptorSet {
+        match layer {
+            OverlayLayer::Base => self.base_overlay_set,
+            OverlayLayer::Active => self.overlay_set,
+        }
+    }
+
+    fn write_active_overlay(
+        &mut self,
+        overlay: &Overlay,
+        layer: OverlayLayer,
+    ) -> Result<()> {
+        overlay
+            .write_rgb_descriptor_set(&self.device, &self.layer_set(layer))?;
 
         Ok(())
     }
@@ -445,26 +560,27 @@ impl OverlayPipelineRGB {
             selection_set_layout,
         )?;
 
-        let image_count = 1;
+        // one set for the active overlay, and one for the base overlay
+        let set_count = 2;
 
         let descriptor_pool = {
             let pool_size = vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::UNIFORM_TEXEL_BUFFER,
-                descriptor_count: image_count,
+                descriptor_count: set_count,
             };
 
             let pool_sizes = [pool_size];
 
             let pool_info = vk::DescriptorPoolCreateInfo::builder()
                 .pool_sizes(&pool_sizes)
-                .max_sets(image_count)
+                .max_sets(set_count)
                 .build();
 
             unsafe { device.create_descriptor_pool(&pool_info, None) }
         }?;
 
         let descriptor_sets = {
-            let layouts = vec![desc_set_layout];
+            let layouts = vec![desc_set_layout; set_count as usize];
 
             let alloc_info = vk::DescriptorSetAllocateInfo::builder()
                 .descriptor_pool(descriptor_pool)
@@ -489,6 +605,7 @@ impl OverlayPipelineRGB {
             descriptor_set_layout: desc_set_layout,
 
             overlay_set: descriptor_sets[0],
+            base_overlay_set: descriptor_sets[1],
 
             pipeline_layout,
             pipeline,
@@ -497,6 +614,34 @@ impl OverlayPipelineRGB {
             device: device.clone(),
         })
     }
+
+    fn recreate_pipeline(
+        &mut self,
+        app: &GfaestusVk,
+        renderer_type: NodeRendererType,
+        selection_set_layout: vk::DescriptorSetLayout,
+    ) -> Result<()> {
+        unsafe {
+            self.device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            self.device.destroy_pipeline(self.pipeline, None);
+        }
+
+        let (pipeline, pipeline_layout) = Self::create_pipeline(
+            app,
+            renderer_type,
+            self.descriptor_set_layout,
+            selection_set_layout,
+        )?;
+
+        app.set_debug_object_name(pipeline, "Node Overlay RGB Pipeline")?;
+
+        self.pipeline = pipeline;
+        self.pipeline_layout = pipeline_layout;
+
+        Ok(())
+    }
+
     pub fn destroy(&self) {
         unsafe {
             self.device.destroy_descriptor_set_layout(
@@ -686,6 +831,57 @@ impl Overlay {
         Ok(())
     }
 
+    /// Reads back the color of each of the first `node_count` nodes,
+    /// in node ID order; the values of single-channel overlays are
+    /// mapped to colors using `gradient`
+    pub fn node_colors(
+        &self,
+        node_count: usize,
+        gradient: colorous::Gradient,
+    ) -> Vec<rgb::RGBA<f32>> {
+        assert!(self.host_visible);
+
+        let mut colors = Vec::with_capacity(node_count);
+
+        unsafe {
+            let ptr = self.alloc_info.get_mapped_data();
+
+            match self.kind {
+                OverlayKind::RGB => {
+                    let bytes = std::slice::from_raw_parts(ptr, node_count * 4);
+
+                    colors.extend(bytes.chunks_exact(4).map(|c| {
+                        rgb::RGBA::new(
+                            c[0] as f32 / 255.0,
+                            c[1] as f32 / 255.0,
+                            c[2] as f32 / 255.0,
+                            c[3] as f32 / 255.0,
+                        )
+                    }));
+                }
+                OverlayKind::Value => {
+                    let values = std::slice::from_raw_parts(
+                        ptr as *const f32,
+                        node_count,
+                    );
+
+                    colors.extend(values.iter().map(|&v| {
+                        let c = gradient
+                            .eval_continuous(v.max(0.0).min(1.0) as f64);
+                        rgb::RGBA::new(
+                            c.r as f32 / 255.0,
+                            c.g as f32 / 255.0,
+                            c.b as f32 / 255.0,
+                            1.0,
+                        )
+                    }));
+                }
+            }
+        }
+
+        colors
+    }
+
     fn write_value_descriptor_set(
         &self,
         device: &Device,
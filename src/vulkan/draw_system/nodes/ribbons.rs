@@ -0,0 +1,145 @@
+use ash::vk;
+
+use handlegraph::{handlegraph::*, pathhandlegraph::*};
+
+use handlegraph::packedgraph::PackedGraph;
+
+use rustc_hash::FxHashMap;
+
+use anyhow::*;
+
+use crate::overlays::PathColors;
+use crate::vulkan::GfaestusVk;
+
+/// The per-node path colors used to draw path ribbons, where each
+/// node is split into one band per path on the node, across its
+/// width.
+///
+/// The buffer starts with one offset per node, followed by the total
+/// length; the colors of the paths on node `n` are in
+/// `data[offset[n - 1]..offset[n]]`, packed as RGBA8.
+pub struct PathRibbons {
+    buffer: vk::Buffer,
+
+    allocation: vk_mem::Allocation,
+
+    // false for the placeholder buffer used until the ribbons are
+    // first enabled
+    has_ribbons: bool,
+}
+
+impl PathRibbons {
+    /// A placeholder, so that the node descriptor set always has a
+    /// valid ribbon buffer
+    pub fn empty(app: &GfaestusVk) -> Result<Self> {
+        let mut ribbons = Self::from_data(app, &[0, 0])?;
+        ribbons.has_ribbons = false;
+        Ok(ribbons)
+    }
+
+    pub fn new(
+        app: &GfaestusVk,
+        graph: &PackedGraph,
+        path_colors: &PathColors,
+    ) -> Result<Self> {
+        let data = Self::ribbon_data(graph, path_colors);
+        Self::from_data(app, &data)
+    }
+
+    fn from_data(app: &GfaestusVk, data: &[u32]) -> Result<Self> {
+        let usage = vk::BufferUsageFlags::STORAGE_BUFFER;
+        let memory_usage = vk_mem::MemoryUsage::GpuOnly;
+
+        let (buffer, allocation, _allocation_info) =
+            app.create_buffer_with_data(usage, memory_usage, false, data)?;
+
+        app.set_debug_object_name(buffer, "Node Path Ribbons")?;
+
+        Ok(Self {
+            buffer,
+            allocation,
+
+            has_ribbons: true,
+        })
+    }
+
+    pub fn buffer(&self) -> vk::Buffer {
+        self.buffer
+    }
+
+    pub fn has_ribbons(&self) -> bool {
+        self.has_ribbons
+    }
+
+    pub fn destroy(&self, app: &GfaestusVk) {
+        app.allocator.destroy_buffer(self.buffer, &self.allocation);
+    }
+
+    /// Builds the ribbon buffer contents; each path is counted once
+    /// per node, no matter how many times it visits it, and the
+    /// ribbons are ordered by path ID
+    fn ribbon_data(graph: &PackedGraph, path_colors: &PathColors) -> Vec<u32> {
+        let node_count = graph.node_count();
+
+        let mut node_paths: Vec<Vec<PathId>> = vec![Vec::new(); node_count];
+
+        let mut colors: FxHashMap<PathId, u32> = FxHashMap::default();
+
+        for path in graph.path_ids() {
+            let color = graph
+                .get_path_name_vec(path)
+                .map(|name| path_colors.display_color(&name))
+                .unwrap_or(rgb::RGB::new(0.5, 0.5, 0.5));
+
+            let packed = u32::from_le_bytes([
+                (color.r * 255.0) as u8,
+                (color.g * 255.0) as u8,
+                (color.b * 255.0) as u8,
+                255,
+            ]);
+
+            colors.insert(path, packed);
+
+            let steps = match graph.path_steps(path) {
+                Some(steps) => steps,
+                None => continue,
+            };
+
+            for step in steps {
+                let ix = (step.handle().id().0 - 1) as usize;
+
+                if let Some(paths) = node_paths.get_mut(ix) {
+                    if paths.last() != Some(&path) {
+                        paths.push(path);
+                    }
+                }
+            }
+        }
+
+        for paths in node_paths.iter_mut() {
+            paths.sort();
+            paths.dedup();
+        }
+
+        let ribbon_count: usize = node_paths.iter().map(|p| p.len()).sum();
+
+        let mut data: Vec<u32> =
+            Vec::with_capacity(node_count + 1 + ribbon_count);
+
+        let mut offset = (node_count + 1) as u32;
+
+        for paths in node_paths.iter() {
+            data.push(offset);
+            offset += paths.len() as u32;
+        }
+        data.push(offset);
+
+        data.extend(
+            node_paths
+                .iter()
+                .flat_map(|paths| paths.iter().map(|path| colors[path])),
+        );
+
+        data
+    }
+}
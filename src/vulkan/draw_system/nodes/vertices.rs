@@ -43,6 +43,14 @@ impl NodeVertices {
         self.allocation_info.is_some()
     }
 
+    /// The size of the vertex buffer allocation in bytes
+    pub fn buffer_size(&self) -> usize {
+        self.allocation_info
+            .as_ref()
+            .map(|info| info.get_size())
+            .unwrap_or(0)
+    }
+
     pub fn destroy(&mut self, app: &GfaestusVk) -> Result<()> {
         if self.has_vertices() {
             app.allocator
@@ -149,6 +157,11 @@ impl NodeVertices {
         Ok(())
     }
 
+    /// Uploads the vertices for every node in the graph, in node ID
+    /// order. This is only done when the layout changes, not per frame;
+    /// the node shaders derive each node's ID from its vertex index, so
+    /// the buffer can't be culled without an index or indirect draw
+    /// buffer.
     pub fn upload_vertices(
         &mut self,
         app: &GfaestusVk,
@@ -7,8 +7,8 @@ use anyhow::Result;
 
 use super::create_shader_module;
 
+use crate::geometry::Point;
 use crate::vulkan::{texture::Texture, GfaestusVk};
-use crate::{geometry::Point, vulkan::render_pass::Framebuffers};
 
 pub struct PostProcessPipeline {
     descriptor_pool: vk::DescriptorPool,
@@ -179,9 +179,10 @@ impl PostProcessPipeline {
         device: &Device,
         cmd_buf: vk::CommandBuffer,
         render_pass: vk::RenderPass,
-        framebuffers: &Framebuffers,
+        framebuffer: vk::Framebuffer,
         screen_size: Point,
         sample_size: Point,
+        adjustment: ColorAdjustment,
     ) -> Result<()> {
         let clear_values = {
             [vk::ClearValue {
@@ -198,7 +199,7 @@ impl PostProcessPipeline {
 
         let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
             .render_pass(render_pass)
-            .framebuffer(framebuffers.selection_blur)
+            .framebuffer(framebuffer)
             .render_area(vk::Rect2D {
                 offset: vk::Offset2D { x: 0, y: 0 },
                 extent,
@@ -236,7 +237,8 @@ impl PostProcessPipeline {
             );
         };
 
-        let push_constants = PushConstants::new(sample_size, screen_size, true);
+        let push_constants =
+            PushConstants::new(sample_size, screen_size, true, adjustment);
 
         let pc_bytes = push_constants.bytes();
 
@@ -494,27 +496,56 @@ pub(crate) fn create_pipeline(
     (pipeline, layout)
 }
 
+/// Brightness, contrast, and gamma correction applied to the
+/// rendered nodes and edges by the `post_adjust` fragment shader
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorAdjustment {
+    /// Added to each color channel, after contrast
+    pub brightness: f32,
+    /// Scales each color channel around 0.5
+    pub contrast: f32,
+    /// Each color channel is raised to the power of `1.0 / gamma`
+    pub gamma: f32,
+}
+
+impl std::default::Default for ColorAdjustment {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
 pub struct PushConstants {
     source_size: Point,
     target_size: Point,
     enabled: bool,
+    adjustment: ColorAdjustment,
 }
 
 impl PushConstants {
     pub const PC_RANGE: u32 =
-        (std::mem::size_of::<u32>() + std::mem::size_of::<f32>() * 4) as u32;
+        (std::mem::size_of::<u32>() + std::mem::size_of::<f32>() * 7) as u32;
 
     #[inline]
-    pub fn new(source_size: Point, target_size: Point, enabled: bool) -> Self {
+    pub fn new(
+        source_size: Point,
+        target_size: Point,
+        enabled: bool,
+        adjustment: ColorAdjustment,
+    ) -> Self {
         Self {
             source_size,
             target_size,
             enabled,
+            adjustment,
         }
     }
 
     #[inline]
-    pub fn bytes(&self) -> [u8; 20] {
+    pub fn bytes(&self) -> [u8; Self::PC_RANGE as usize] {
         let mut bytes = [0u8; Self::PC_RANGE as usize];
 
         {
@@ -541,6 +572,22 @@ impl PushConstants {
             bytes[19] = 0;
         }
 
+        {
+            let mut offset = 20;
+
+            let mut add_float = |f: f32| {
+                let f_bytes = f.to_ne_bytes();
+                for i in 0..4 {
+                    bytes[offset] = f_bytes[i];
+                    offset += 1;
+                }
+            };
+
+            add_float(self.adjustment.brightness);
+            add_float(self.adjustment.contrast);
+            add_float(self.adjustment.gamma);
+        }
+
         bytes
     }
 }
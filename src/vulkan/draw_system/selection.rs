@@ -10,6 +10,44 @@ use super::create_shader_module;
 use crate::vulkan::render_pass::Framebuffers;
 use crate::vulkan::{texture::Texture, GfaestusVk};
 
+/// How the border around the selected nodes is drawn, from the edges
+/// detected in the selection mask.
+///
+/// `Blur` draws a soft, blurred border, while `Outline` draws a crisp
+/// border in a single highlight color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SelectionStyle {
+    Blur,
+    Outline,
+}
+
+impl std::default::Default for SelectionStyle {
+    fn default() -> Self {
+        Self::Blur
+    }
+}
+
+impl SelectionStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelectionStyle::Blur => "blur",
+            SelectionStyle::Outline => "outline",
+        }
+    }
+}
+
+impl std::str::FromStr for SelectionStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "blur" => Ok(SelectionStyle::Blur),
+            "outline" => Ok(SelectionStyle::Outline),
+            _ => Err(()),
+        }
+    }
+}
+
 pub struct SelectionOutlineEdgePipeline {
     descriptor_pool: vk::DescriptorPool,
     descriptor_set_layout: vk::DescriptorSetLayout,
@@ -249,10 +287,14 @@ impl SelectionOutlineEdgePipeline {
             descriptor_set_layout,
             crate::include_shader!("post/post.vert.spv"),
             crate::include_shader!("post/post_edge.frag.spv"),
+            PushConstants::SIZE,
         )
     }
 }
 
+/// Draws the selection border onto the swapchain image, from the
+/// output of the edge detection pass; the border is either blurred
+/// or drawn as a solid outline, depending on the `SelectionStyle`
 pub struct SelectionOutlineBlurPipeline {
     descriptor_pool: vk::DescriptorPool,
     descriptor_set_layout: vk::DescriptorSetLayout,
@@ -260,9 +302,16 @@ pub struct SelectionOutlineBlurPipeline {
 
     pipeline_layout: vk::PipelineLayout,
     pipeline: vk::Pipeline,
+
+    // uses the same descriptor set, but also takes the outline color
+    // in its push constants
+    outline_pipeline_layout: vk::PipelineLayout,
+    outline_pipeline: vk::Pipeline,
 }
 
 impl SelectionOutlineBlurPipeline {
+    const OUTLINE_COLOR_PC_OFFSET: u32 = 16;
+
     pub fn new(app: &GfaestusVk, image_count: u32) -> Result<Self> {
         let vk_context = app.vk_context();
         let device = vk_context.device();
@@ -323,12 +372,18 @@ impl SelectionOutlineBlurPipeline {
         let (pipeline, pipeline_layout) =
             Self::create_pipeline(device, render_pass, layout);
 
+        let (outline_pipeline, outline_pipeline_layout) =
+            Self::create_outline_pipeline(device, render_pass, layout);
+
         Ok(Self {
             descriptor_pool,
             descriptor_set_layout: layout,
             descriptor_set: descriptor_sets[0],
             pipeline_layout,
             pipeline,
+
+            outline_pipeline_layout,
+            outline_pipeline,
         })
     }
 
@@ -364,9 +419,18 @@ impl SelectionOutlineBlurPipeline {
         render_pass: vk::RenderPass,
         framebuffers: &Framebuffers,
         viewport_dims: [f32; 2],
+        style: SelectionStyle,
+        outline_color: rgb::RGB<f32>,
     ) -> Result<()> {
         let clear_values = [];
 
+        let (pipeline, pipeline_layout) = match style {
+            SelectionStyle::Blur => (self.pipeline, self.pipeline_layout),
+            SelectionStyle::Outline => {
+                (self.outline_pipeline, self.outline_pipeline_layout)
+            }
+        };
+
         let extent = vk::Extent2D {
             width: viewport_dims[0] as u32,
             height: viewport_dims[1] as u32,
@@ -394,7 +458,7 @@ impl SelectionOutlineBlurPipeline {
             device.cmd_bind_pipeline(
                 cmd_buf,
                 vk::PipelineBindPoint::GRAPHICS,
-                self.pipeline,
+                pipeline,
             )
         };
 
@@ -405,7 +469,7 @@ impl SelectionOutlineBlurPipeline {
             device.cmd_bind_descriptor_sets(
                 cmd_buf,
                 vk::PipelineBindPoint::GRAPHICS,
-                self.pipeline_layout,
+                pipeline_layout,
                 0,
                 &desc_sets[0..=0],
                 &null,
@@ -420,11 +484,31 @@ impl SelectionOutlineBlurPipeline {
             use vk::ShaderStageFlags as Flags;
             device.cmd_push_constants(
                 cmd_buf,
-                self.pipeline_layout,
+                pipeline_layout,
                 Flags::VERTEX | Flags::FRAGMENT,
                 0,
                 &pc_bytes,
-            )
+            );
+
+            // the outline color follows the shared push constants,
+            // aligned as a vec4
+            if style == SelectionStyle::Outline {
+                let mut color_bytes = [0u8; 16];
+                let color =
+                    [outline_color.r, outline_color.g, outline_color.b, 1.0];
+                for (chunk, val) in color_bytes.chunks_mut(4).zip(color.iter())
+                {
+                    chunk.copy_from_slice(&val.to_ne_bytes());
+                }
+
+                device.cmd_push_constants(
+                    cmd_buf,
+                    pipeline_layout,
+                    Flags::VERTEX | Flags::FRAGMENT,
+                    Self::OUTLINE_COLOR_PC_OFFSET,
+                    &color_bytes,
+                );
+            }
         };
 
         unsafe { device.cmd_draw(cmd_buf, 3u32, 1, 0, 0) };
@@ -445,6 +529,9 @@ impl SelectionOutlineBlurPipeline {
 
             device.destroy_pipeline(self.pipeline, None);
             device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            device.destroy_pipeline(self.outline_pipeline, None);
+            device.destroy_pipeline_layout(self.outline_pipeline_layout, None);
         }
     }
 
@@ -486,6 +573,22 @@ impl SelectionOutlineBlurPipeline {
             descriptor_set_layout,
             crate::include_shader!("post/post.vert.spv"),
             crate::include_shader!("post/post_blur.frag.spv"),
+            PushConstants::SIZE,
+        )
+    }
+
+    fn create_outline_pipeline(
+        device: &Device,
+        render_pass: vk::RenderPass,
+        descriptor_set_layout: vk::DescriptorSetLayout,
+    ) -> (vk::Pipeline, vk::PipelineLayout) {
+        create_pipeline(
+            device,
+            render_pass,
+            descriptor_set_layout,
+            crate::include_shader!("post/post.vert.spv"),
+            crate::include_shader!("post/post_outline.frag.spv"),
+            Self::OUTLINE_COLOR_PC_OFFSET + 16,
         )
     }
 }
@@ -496,6 +599,7 @@ fn create_pipeline(
     descriptor_set_layout: vk::DescriptorSetLayout,
     vert_shader: &[u8],
     frag_shader: &[u8],
+    push_constants_size: u32,
 ) -> (vk::Pipeline, vk::PipelineLayout) {
     let vert_src = {
         let mut cursor = std::io::Cursor::new(vert_shader);
@@ -598,7 +702,7 @@ fn create_pipeline(
         let pc_range = vk::PushConstantRange::builder()
             .stage_flags(Flags::VERTEX | Flags::FRAGMENT)
             .offset(0)
-            .size(12)
+            .size(push_constants_size)
             .build();
 
         let pc_ranges = [pc_range];
@@ -652,6 +756,8 @@ pub struct PushConstants {
 }
 
 impl PushConstants {
+    pub const SIZE: u32 = 12;
+
     #[inline]
     pub fn new(viewport_dims: [f32; 2], enabled: bool) -> Self {
         let width = viewport_dims[0];
@@ -15,7 +15,8 @@ use std::ffi::{CStr, CString};
 use anyhow::Result;
 
 use super::{
-    context::*, debug::*, SwapchainProperties, SwapchainSupportDetails,
+    context::*, debug::*, PresentMode, SwapchainProperties,
+    SwapchainSupportDetails,
 };
 
 #[allow(unused_imports)]
@@ -323,6 +324,7 @@ pub(super) fn create_swapchain_and_images(
     graphics_ix: u32,
     present_ix: u32,
     dimensions: [u32; 2],
+    present_mode: PresentMode,
 ) -> Result<(
     Swapchain,
     vk::SwapchainKHR,
@@ -335,7 +337,8 @@ pub(super) fn create_swapchain_and_images(
         vk_context.surface_khr(),
     )?;
 
-    let props = details.get_ideal_swapchain_properties(dimensions);
+    let props =
+        details.get_ideal_swapchain_properties(dimensions, present_mode);
 
     let image_count = {
         let max = details.capabilities.max_image_count;
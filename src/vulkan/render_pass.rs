@@ -12,9 +12,14 @@ pub struct RenderPasses {
     pub edges: vk::RenderPass,
     pub selection_edge_detect: vk::RenderPass,
     pub selection_blur: vk::RenderPass,
+    pub post_adjust: vk::RenderPass,
     pub gui: vk::RenderPass,
 
     pub id_format: vk::Format,
+
+    // with a single sample, the node and edge passes render directly
+    // into the resolve images, so the framebuffers differ
+    msaa_samples: vk::SampleCountFlags,
 }
 
 pub struct Framebuffers {
@@ -22,6 +27,7 @@ pub struct Framebuffers {
     pub edges: vk::Framebuffer,
     pub selection_edge_detect: vk::Framebuffer,
     pub selection_blur: vk::Framebuffer,
+    pub post_adjust: vk::Framebuffer,
     pub gui: vk::Framebuffer,
 }
 
@@ -37,6 +43,10 @@ impl Framebuffers {
             self.selection_blur,
             "Selection Border Blur Framebuffer",
         )?;
+        app.set_debug_object_name(
+            self.post_adjust,
+            "Color Adjustment Framebuffer",
+        )?;
         app.set_debug_object_name(self.gui, "GUI Framebuffer")?;
 
         Ok(())
@@ -48,6 +58,7 @@ impl Framebuffers {
             device.destroy_framebuffer(self.edges, None);
             device.destroy_framebuffer(self.selection_edge_detect, None);
             device.destroy_framebuffer(self.selection_blur, None);
+            device.destroy_framebuffer(self.post_adjust, None);
             device.destroy_framebuffer(self.gui, None);
         }
     }
@@ -332,17 +343,42 @@ impl NodeAttachments {
         queue: vk::Queue,
         swapchain_props: SwapchainProperties,
     ) -> Result<Texture> {
+        let device = vk_context.device();
         let extent = swapchain_props.extent;
 
+        // sampled by the color adjustment post-processing pass, which
+        // writes the final node and edge colors to the swapchain
+        let sampler = {
+            let sampler_info = vk::SamplerCreateInfo::builder()
+                .mag_filter(vk::Filter::NEAREST)
+                .min_filter(vk::Filter::NEAREST)
+                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .anisotropy_enable(false)
+                .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+                .unnormalized_coordinates(false)
+                .compare_enable(false)
+                .compare_op(vk::CompareOp::ALWAYS)
+                .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+                .mip_lod_bias(0.0)
+                .min_lod(0.0)
+                .max_lod(1.0)
+                .build();
+
+            unsafe { device.create_sampler(&sampler_info, None) }
+        }?;
+
         let resolve = Texture::create_attachment_image(
             vk_context,
             command_pool,
             queue,
-            vk::ImageUsageFlags::COLOR_ATTACHMENT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::SAMPLED,
             vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
             extent,
             swapchain_props.format.format,
-            None,
+            Some(sampler),
         )?;
 
         Ok(resolve)
@@ -525,6 +561,7 @@ impl RenderPasses {
         )?;
         let selection_blur =
             Self::create_selection_blur(device, swapchain_props)?;
+        let post_adjust = Self::create_post_adjust(device, swapchain_props)?;
         let gui = Self::create_gui(device, swapchain_props)?;
 
         Ok(Self {
@@ -532,9 +569,12 @@ impl RenderPasses {
             edges,
             selection_edge_detect,
             selection_blur,
+            post_adjust,
             gui,
 
             id_format,
+
+            msaa_samples,
         })
     }
 
@@ -548,19 +588,28 @@ impl RenderPasses {
     ) -> Result<Framebuffers> {
         let extent = swapchain_props.extent;
 
+        let single_sampled = self.msaa_samples == vk::SampleCountFlags::TYPE_1;
+
         let nodes = {
-            let attachments = [
-                // color attachments
-                node_attachments.color.view,
-                node_attachments.id_color.view,
-                node_attachments.mask.view,
-                //
-                // resolve attachments
-                // node_attachments.resolve.view,
-                swapchain_image_view,
-                node_attachments.id_resolve.view,
-                node_attachments.mask_resolve.view,
-            ];
+            let attachments = if single_sampled {
+                vec![
+                    node_attachments.resolve.view,
+                    node_attachments.id_resolve.view,
+                    node_attachments.mask_resolve.view,
+                ]
+            } else {
+                vec![
+                    // color attachments
+                    node_attachments.color.view,
+                    node_attachments.id_color.view,
+                    node_attachments.mask.view,
+                    //
+                    // resolve attachments
+                    node_attachments.resolve.view,
+                    node_attachments.id_resolve.view,
+                    node_attachments.mask_resolve.view,
+                ]
+            };
 
             let framebuffer_info = vk::FramebufferCreateInfo::builder()
                 .render_pass(self.nodes)
@@ -574,13 +623,16 @@ impl RenderPasses {
         }?;
 
         let edges = {
-            let attachments = [
-                // color attachments
-                node_attachments.color.view,
-                // resolve attachments
-                // node_attachments.resolve.view,
-                swapchain_image_view,
-            ];
+            let attachments = if single_sampled {
+                vec![node_attachments.resolve.view]
+            } else {
+                vec![
+                    // color attachments
+                    node_attachments.color.view,
+                    // resolve attachments
+                    node_attachments.resolve.view,
+                ]
+            };
 
             let framebuffer_info = vk::FramebufferCreateInfo::builder()
                 .render_pass(self.edges)
@@ -621,6 +673,20 @@ impl RenderPasses {
             unsafe { device.create_framebuffer(&framebuffer_info, None) }
         }?;
 
+        let post_adjust = {
+            let attachments = [swapchain_image_view];
+
+            let framebuffer_info = vk::FramebufferCreateInfo::builder()
+                .render_pass(self.post_adjust)
+                .attachments(&attachments)
+                .width(extent.width)
+                .height(extent.height)
+                .layers(1)
+                .build();
+
+            unsafe { device.create_framebuffer(&framebuffer_info, None) }
+        }?;
+
         let gui = {
             let attachments = [swapchain_image_view];
 
@@ -640,6 +706,7 @@ impl RenderPasses {
             edges,
             selection_edge_detect,
             selection_blur,
+            post_adjust,
             gui,
         })
     }
@@ -655,6 +722,10 @@ impl RenderPasses {
             self.selection_blur,
             "Selection Border Blur Render Pass",
         )?;
+        app.set_debug_object_name(
+            self.post_adjust,
+            "Color Adjustment Render Pass",
+        )?;
         app.set_debug_object_name(self.gui, "GUI Render Pass")?;
 
         Ok(())
@@ -682,14 +753,18 @@ impl RenderPasses {
         )?;
         let selection_blur =
             Self::create_selection_blur(device, swapchain_props)?;
+        let post_adjust = Self::create_post_adjust(device, swapchain_props)?;
         let gui = Self::create_gui(device, swapchain_props)?;
 
         self.nodes = nodes;
         self.edges = edges;
         self.selection_edge_detect = selection_edge_detect;
         self.selection_blur = selection_blur;
+        self.post_adjust = post_adjust;
         self.gui = gui;
 
+        self.msaa_samples = msaa_samples;
+
         Ok(())
     }
 
@@ -699,6 +774,7 @@ impl RenderPasses {
             device.destroy_render_pass(self.edges, None);
             device.destroy_render_pass(self.selection_edge_detect, None);
             device.destroy_render_pass(self.selection_blur, None);
+            device.destroy_render_pass(self.post_adjust, None);
             device.destroy_render_pass(self.gui, None);
         }
     }
@@ -708,6 +784,10 @@ impl RenderPasses {
         swapchain_props: SwapchainProperties,
         msaa_samples: vk::SampleCountFlags,
     ) -> Result<vk::RenderPass> {
+        // without MSAA, the edges are drawn directly on top of the
+        // resolved node image
+        let single_sampled = msaa_samples == vk::SampleCountFlags::TYPE_1;
+
         let color_attch_desc = vk::AttachmentDescription::builder()
             .format(swapchain_props.format.format)
             .samples(msaa_samples)
@@ -720,13 +800,25 @@ impl RenderPasses {
         let resolve_attch_desc = vk::AttachmentDescription::builder()
             .format(swapchain_props.format.format)
             .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .load_op(if single_sampled {
+                vk::AttachmentLoadOp::LOAD
+            } else {
+                vk::AttachmentLoadOp::DONT_CARE
+            })
             .store_op(vk::AttachmentStoreOp::STORE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .initial_layout(if single_sampled {
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+            } else {
+                vk::ImageLayout::UNDEFINED
+            })
             .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
             .build();
 
-        let attch_descs = [color_attch_desc, resolve_attch_desc];
+        let attch_descs = if single_sampled {
+            vec![resolve_attch_desc]
+        } else {
+            vec![color_attch_desc, resolve_attch_desc]
+        };
 
         let color_attch_ref = vk::AttachmentReference::builder()
             .attachment(0)
@@ -742,11 +834,15 @@ impl RenderPasses {
 
         let resolve_attchs = [resolve_attch_ref];
 
-        let subpass_desc = vk::SubpassDescription::builder()
+        let mut subpass_desc = vk::SubpassDescription::builder()
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(&color_attchs)
-            .resolve_attachments(&resolve_attchs)
-            .build();
+            .color_attachments(&color_attchs);
+
+        if !single_sampled {
+            subpass_desc = subpass_desc.resolve_attachments(&resolve_attchs);
+        }
+
+        let subpass_desc = subpass_desc.build();
 
         let subpass_descs = [subpass_desc];
 
@@ -785,6 +881,16 @@ impl RenderPasses {
         // attachments:
         // TODO depth
 
+        // without MSAA, the resolve images are rendered to directly,
+        // and cleared instead of the multisampled images
+        let single_sampled = msaa_samples == vk::SampleCountFlags::TYPE_1;
+
+        let resolve_load_op = if single_sampled {
+            vk::AttachmentLoadOp::CLEAR
+        } else {
+            vk::AttachmentLoadOp::DONT_CARE
+        };
+
         let color_attch_desc = vk::AttachmentDescription::builder()
             .format(swapchain_props.format.format)
             .samples(msaa_samples)
@@ -797,7 +903,7 @@ impl RenderPasses {
         let resolve_attch_desc = vk::AttachmentDescription::builder()
             .format(swapchain_props.format.format)
             .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .load_op(resolve_load_op)
             .store_op(vk::AttachmentStoreOp::STORE)
             .initial_layout(vk::ImageLayout::UNDEFINED)
             .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
@@ -815,7 +921,7 @@ impl RenderPasses {
         let id_resolve_attch_desc = vk::AttachmentDescription::builder()
             .format(id_format)
             .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .load_op(resolve_load_op)
             .store_op(vk::AttachmentStoreOp::STORE)
             .initial_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
             .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
@@ -833,20 +939,28 @@ impl RenderPasses {
         let mask_resolve_attch_desc = vk::AttachmentDescription::builder()
             .format(vk::Format::R8G8B8A8_UNORM)
             .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .load_op(resolve_load_op)
             .store_op(vk::AttachmentStoreOp::STORE)
             .initial_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
             .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
             .build();
 
-        let attch_descs = [
-            color_attch_desc,
-            id_color_attch_desc,
-            mask_attch_desc,
-            resolve_attch_desc,
-            id_resolve_attch_desc,
-            mask_resolve_attch_desc,
-        ];
+        let attch_descs = if single_sampled {
+            vec![
+                resolve_attch_desc,
+                id_resolve_attch_desc,
+                mask_resolve_attch_desc,
+            ]
+        } else {
+            vec![
+                color_attch_desc,
+                id_color_attch_desc,
+                mask_attch_desc,
+                resolve_attch_desc,
+                id_resolve_attch_desc,
+                mask_resolve_attch_desc,
+            ]
+        };
 
         let color_attch_ref = vk::AttachmentReference::builder()
             .attachment(0)
@@ -886,11 +1000,18 @@ impl RenderPasses {
             mask_resolve_attch_ref,
         ];
 
-        let subpass_desc = vk::SubpassDescription::builder()
+        // the color attachment references are the same in both cases,
+        // as the resolve images take the first three slots when
+        // they're rendered to directly
+        let mut subpass_desc = vk::SubpassDescription::builder()
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(&color_attchs)
-            .resolve_attachments(&resolve_attchs)
-            .build();
+            .color_attachments(&color_attchs);
+
+        if !single_sampled {
+            subpass_desc = subpass_desc.resolve_attachments(&resolve_attchs);
+        }
+
+        let subpass_desc = subpass_desc.build();
 
         let subpass_descs = [subpass_desc];
 
@@ -1030,6 +1151,63 @@ impl RenderPasses {
         Ok(render_pass)
     }
 
+    fn create_post_adjust(
+        device: &Device,
+        swapchain_props: SwapchainProperties,
+    ) -> Result<vk::RenderPass> {
+        // this is the first pass to write to the swapchain image each
+        // frame, and it covers the entire image
+        let color_attch_desc = vk::AttachmentDescription::builder()
+            .format(swapchain_props.format.format)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            .build();
+
+        let attch_descs = [color_attch_desc];
+
+        let color_attch_ref = vk::AttachmentReference::builder()
+            .attachment(0)
+            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            .build();
+
+        let color_attchs = [color_attch_ref];
+
+        let subpass_desc = vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(&color_attchs)
+            .build();
+
+        let subpass_descs = [subpass_desc];
+
+        let subpass_dep = vk::SubpassDependency::builder()
+            .src_subpass(vk::SUBPASS_EXTERNAL)
+            .dst_subpass(0)
+            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .src_access_mask(vk::AccessFlags::empty())
+            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .dst_access_mask(
+                vk::AccessFlags::COLOR_ATTACHMENT_READ
+                    | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            )
+            .build();
+
+        let subpass_deps = [subpass_dep];
+
+        let render_pass_info = vk::RenderPassCreateInfo::builder()
+            .attachments(&attch_descs)
+            .subpasses(&subpass_descs)
+            .dependencies(&subpass_deps)
+            .build();
+
+        let render_pass =
+            unsafe { device.create_render_pass(&render_pass_info, None) }?;
+
+        Ok(render_pass)
+    }
+
     fn create_gui(
         device: &Device,
         swapchain_props: SwapchainProperties,